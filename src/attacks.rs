@@ -37,7 +37,7 @@
 //! assert!(!attacks.contains(Square::H7));
 //! ```
 
-use crate::square::Square;
+use crate::square::{Rank, Square};
 use crate::bitboard::Bitboard;
 use crate::types::{Color, Piece, Role};
 use crate::magics;
@@ -53,6 +53,63 @@ pub fn pawn_attacks(color: Color, sq: Square) -> Bitboard {
     })
 }
 
+/// Target squares of single pawn pushes for pawns of `color` on
+/// `pawns`, given the `occupied` squares.
+///
+/// # Example
+///
+/// ```
+/// use shakmaty::{attacks, Bitboard, Color, Rank};
+///
+/// let pawns = Bitboard::rank(Rank::Second);
+/// let pushes = attacks::single_pushes(Color::White, pawns, pawns);
+/// assert_eq!(pushes, Bitboard::rank(Rank::Third));
+/// ```
+#[inline]
+pub fn single_pushes(color: Color, pawns: Bitboard, occupied: Bitboard) -> Bitboard {
+    pawns.relative_shift(color, 8) & !occupied
+}
+
+/// Target squares of double pawn pushes for pawns of `color` on `pawns`,
+/// given the `occupied` squares. Both the skipped square and the target
+/// square have to be empty.
+///
+/// Double pushes land on the fourth rank, or on the third rank for pawns
+/// starting from the first rank, as in Horde.
+///
+/// # Example
+///
+/// ```
+/// use shakmaty::{attacks, Bitboard, Color, Rank, Square};
+///
+/// let pawns = Bitboard::rank(Rank::Seventh);
+/// let occupied = pawns.with(Square::E6).with(Square::D5);
+/// let pushes = attacks::double_pushes(Color::Black, pawns, occupied);
+/// assert_eq!(pushes, Bitboard::rank(Rank::Fifth).without(Square::E5).without(Square::D5));
+/// ```
+#[inline]
+pub fn double_pushes(color: Color, pawns: Bitboard, occupied: Bitboard) -> Bitboard {
+    single_pushes(color, single_pushes(color, pawns, occupied), occupied) &
+        (Bitboard::relative_rank(color, Rank::Fourth) | Bitboard::relative_rank(color, Rank::Third))
+}
+
+/// Target squares of single and double pushes for a pawn of `color` on
+/// `sq`, given the `occupied` squares.
+///
+/// # Example
+///
+/// ```
+/// use shakmaty::{attacks, Bitboard, Color, Square};
+///
+/// let pushes = attacks::pawn_pushes(Color::White, Square::E2, Bitboard::from_square(Square::E2));
+/// assert_eq!(pushes, Bitboard::from_square(Square::E3).with(Square::E4));
+/// ```
+#[inline]
+pub fn pawn_pushes(color: Color, sq: Square, occupied: Bitboard) -> Bitboard {
+    let pawn = Bitboard::from_square(sq);
+    single_pushes(color, pawn, occupied) | double_pushes(color, pawn, occupied)
+}
+
 /// Looks up attacks for a knight on `sq`.
 #[inline]
 pub fn knight_attacks(sq: Square) -> Bitboard {
@@ -145,6 +202,23 @@ pub fn queen_attacks(sq: Square, occupied: Bitboard) -> Bitboard {
 }
 
 /// Looks up attacks for `piece` on `sq` with `occupied` squares.
+///
+/// Dispatches on the role of the piece, and on its color for pawns.
+///
+/// # Example
+///
+/// ```
+/// use shakmaty::{attacks, Bitboard, Color, Role, Square};
+///
+/// let occupied = Bitboard::from_square(Square::D6);
+/// let piece = Role::Rook.of(Color::White);
+/// assert_eq!(attacks::attacks(Square::D4, piece, occupied),
+///            attacks::rook_attacks(Square::D4, occupied));
+///
+/// let pawn = Role::Pawn.of(Color::Black);
+/// assert_eq!(attacks::attacks(Square::D4, pawn, occupied),
+///            attacks::pawn_attacks(Color::Black, Square::D4));
+/// ```
 pub fn attacks(sq: Square, piece: Piece, occupied: Bitboard) -> Bitboard {
     match piece.role {
         Role::Pawn => pawn_attacks(piece.color, sq),
@@ -159,6 +233,9 @@ pub fn attacks(sq: Square, piece: Piece, occupied: Bitboard) -> Bitboard {
 /// The rank, file or diagonal with the two squares (or an empty [`Bitboard`]
 /// if they are not aligned).
 ///
+/// This is the full line through both squares, extending to the edges of
+/// the board in both directions.
+///
 /// # Example
 ///
 /// ```
@@ -174,12 +251,34 @@ pub fn attacks(sq: Square, piece: Piece, occupied: Bitboard) -> Bitboard {
 /// // . . . . . 1 . .
 /// // . . . . 1 . . .
 /// // . . . 1 . . . .
+///
+/// assert!(ray.contains(Square::D1));
+/// assert!(ray.contains(Square::H5));
+/// assert!(attacks::ray(Square::E2, Square::F4).is_empty());
 /// ```
 #[inline]
 pub fn ray(a: Square, b: Square) -> Bitboard {
     Bitboard(BB_RAYS[usize::from(a)][usize::from(b)])
 }
 
+/// The full line through two distinct squares, from edge to edge of the
+/// board, or an empty [`Bitboard`] if they are not on the same rank, file
+/// or diagonal. This is the same as [`ray()`].
+///
+/// # Example
+///
+/// ```
+/// # use shakmaty::attacks;
+/// # use shakmaty::{Bitboard, Rank, Square};
+/// #
+/// assert_eq!(attacks::line(Square::C4, Square::F4), Bitboard::rank(Rank::Fourth));
+/// assert!(attacks::line(Square::C4, Square::D6).is_empty());
+/// ```
+#[inline]
+pub fn line(a: Square, b: Square) -> Bitboard {
+    ray(a, b)
+}
+
 /// The squares between the two squares (bounds not included), or an empty
 /// [`Bitboard`] if they are not on the same rank, file or diagonal.
 ///
@@ -229,4 +328,50 @@ mod tests {
         assert_eq!(rook_attacks(Square::D6, Bitboard(0x3f7f28802826f5b9)),
                    Bitboard(0x8370808000000));
     }
+
+    #[test]
+    fn test_geometry() {
+        assert_eq!(ray(Square::B3, Square::F3), Bitboard::rank(crate::square::Rank::Third));
+        assert_eq!(ray(Square::F3, Square::B3), ray(Square::B3, Square::F3));
+        assert!(ray(Square::A1, Square::A1).is_empty());
+        assert_eq!(between(Square::A1, Square::D4), Bitboard::from_square(Square::B2).with(Square::C3));
+        assert!(between(Square::A1, Square::B3).is_empty());
+        assert!(aligned(Square::H1, Square::A8, Square::D5));
+        assert!(!aligned(Square::H1, Square::A8, Square::D4));
+    }
+
+    #[test]
+    fn test_line() {
+        assert_eq!(line(Square::B2, Square::B6), Bitboard::file(crate::square::File::B));
+        assert_eq!(line(Square::C3, Square::E5), Bitboard::from_square(Square::A1).with(Square::B2).with(Square::C3)
+            .with(Square::D4).with(Square::E5).with(Square::F6).with(Square::G7).with(Square::H8));
+        assert_eq!(line(Square::G2, Square::E4), line(Square::E4, Square::G2));
+        assert!(line(Square::G2, Square::E4).contains(Square::A8));
+        assert!(line(Square::A1, Square::B3).is_empty());
+        assert!(line(Square::D4, Square::D4).is_empty());
+    }
+
+    #[test]
+    fn test_pawn_pushes() {
+        let occupied = Bitboard::from_square(Square::E2).with(Square::D2).with(Square::D3).with(Square::C2).with(Square::C4);
+        assert_eq!(pawn_pushes(Color::White, Square::E2, occupied), Bitboard::from_square(Square::E3).with(Square::E4));
+        assert!(pawn_pushes(Color::White, Square::D2, occupied).is_empty());
+        assert_eq!(pawn_pushes(Color::White, Square::C2, occupied), Bitboard::from_square(Square::C3));
+        assert_eq!(pawn_pushes(Color::White, Square::E3, occupied), Bitboard::from_square(Square::E4));
+        assert_eq!(pawn_pushes(Color::White, Square::A1, Bitboard(0)), Bitboard::from_square(Square::A2).with(Square::A3));
+        assert!(pawn_pushes(Color::Black, Square::H1, Bitboard(0)).is_empty());
+    }
+
+    #[test]
+    fn test_attacks() {
+        use crate::types::{Black, White};
+
+        let occupied = Bitboard(0x3f7f28802826f5b9);
+        assert_eq!(attacks(Square::E4, White.pawn(), occupied), Bitboard::from_square(Square::D5).with(Square::F5));
+        assert_eq!(attacks(Square::E4, Black.pawn(), occupied), Bitboard::from_square(Square::D3).with(Square::F3));
+        assert_eq!(attacks(Square::D6, Black.rook(), occupied), rook_attacks(Square::D6, occupied));
+        assert_eq!(attacks(Square::D6, White.queen(), occupied), queen_attacks(Square::D6, occupied));
+        assert_eq!(attacks(Square::A1, White.knight(), occupied), knight_attacks(Square::A1));
+        assert_eq!(attacks(Square::A1, Black.king(), occupied).count(), 3);
+    }
 }
@@ -15,6 +15,10 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 //! Sets of squares.
+//!
+//! The board size is fixed at 8x8, matching the 64 bits of the underlying
+//! integer. Attack tables and move generation rely on this layout, so
+//! smaller or larger boards are not supported.
 
 use std::ops;
 use std::fmt;
@@ -424,6 +428,120 @@ impl Bitboard {
         self.flip_vertical().flip_diagonal()
     }
 
+    /// Extends every square to the north, up to the edge of the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Square};
+    ///
+    /// let bitboard = Bitboard::from_square(Square::B3).with(Square::F6);
+    /// assert_eq!(bitboard.north_fill(), Bitboard(0x2222_2202_0202_0000));
+    /// // . 1 . . . 1 . .
+    /// // . 1 . . . 1 . .
+    /// // . 1 . . . 1 . .
+    /// // . 1 . . . . . .
+    /// // . 1 . . . . . .
+    /// // . 1 . . . . . .
+    /// // . . . . . . . .
+    /// // . . . . . . . .
+    /// ```
+    #[must_use]
+    pub fn north_fill(self) -> Bitboard {
+        let x = self.0;
+        let x = x | (x << 8);
+        let x = x | (x << 16);
+        let x = x | (x << 32);
+        Bitboard(x)
+    }
+
+    /// Extends every square to the south, down to the edge of the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Square};
+    ///
+    /// let bitboard = Bitboard::from_square(Square::B3).with(Square::F6);
+    /// assert_eq!(bitboard.south_fill(), Bitboard(0x0000_2020_2022_2222));
+    /// // . . . . . . . .
+    /// // . . . . . . . .
+    /// // . . . . . 1 . .
+    /// // . . . . . 1 . .
+    /// // . . . . . 1 . .
+    /// // . 1 . . . 1 . .
+    /// // . 1 . . . 1 . .
+    /// // . 1 . . . 1 . .
+    /// ```
+    #[must_use]
+    pub fn south_fill(self) -> Bitboard {
+        let x = self.0;
+        let x = x | (x >> 8);
+        let x = x | (x >> 16);
+        let x = x | (x >> 32);
+        Bitboard(x)
+    }
+
+    /// All squares on the files of the squares in the bitboard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, File, Square};
+    ///
+    /// let bitboard = Bitboard::from_square(Square::B3).with(Square::B5);
+    /// assert_eq!(bitboard.file_fill(), Bitboard::file(File::B));
+    /// ```
+    #[must_use]
+    pub fn file_fill(self) -> Bitboard {
+        self.north_fill() | self.south_fill()
+    }
+
+    /// Squares in front of the squares in the bitboard, from the point of
+    /// view of `color`, not including the squares themselves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Color, Square};
+    ///
+    /// let bitboard = Bitboard::from_square(Square::E6);
+    /// assert_eq!(bitboard.front_span(Color::White), Bitboard::from_square(Square::E7).with(Square::E8));
+    /// assert_eq!(bitboard.front_span(Color::Black).count(), 5);
+    /// ```
+    #[must_use]
+    pub fn front_span(self, color: Color) -> Bitboard {
+        match color {
+            Color::White => Bitboard(self.0 << 8).north_fill(),
+            Color::Black => Bitboard(self.0 >> 8).south_fill(),
+        }
+    }
+
+    /// Squares behind the squares in the bitboard, from the point of view
+    /// of `color`, not including the squares themselves.
+    #[must_use]
+    pub fn rear_span(self, color: Color) -> Bitboard {
+        self.front_span(!color)
+    }
+
+    /// Squares that pawns of `color` on the squares in the bitboard could
+    /// ever attack while advancing, i.e. the front spans of the adjacent
+    /// files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Color, Square};
+    ///
+    /// let pawn = Bitboard::from_square(Square::A6);
+    /// assert_eq!(pawn.attack_span(Color::White), Bitboard::from_square(Square::B7).with(Square::B8));
+    /// ```
+    #[must_use]
+    pub fn attack_span(self, color: Color) -> Bitboard {
+        let span = self.front_span(color).0;
+        Bitboard(((span << 1) & !FILES[0]) | ((span >> 1) & !FILES[7]))
+    }
+
     /// An empty bitboard.
     pub const EMPTY: Bitboard = Bitboard(0);
 
@@ -742,6 +860,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_more_than_one() {
         assert_eq!(Bitboard(0).more_than_one(), false);
         assert_eq!(Bitboard(1).more_than_one(), false);
@@ -782,4 +901,23 @@ mod tests {
         assert_eq!(Bitboard::from_iter(Some(Square::D2)),
                    Bitboard::from_square(Square::D2));
     }
+
+    #[test]
+    fn test_fills_and_spans() {
+        let h1 = Bitboard::from_square(Square::H1);
+        assert_eq!(h1.north_fill(), Bitboard::file(File::H));
+        assert_eq!(h1.south_fill(), h1);
+        assert_eq!(Bitboard::ALL.file_fill(), Bitboard::ALL);
+        assert_eq!(Bitboard(0).file_fill(), Bitboard(0));
+
+        let pawns = Bitboard::from_square(Square::D4).with(Square::H5);
+        assert_eq!(pawns.front_span(Color::White).count(), 4 + 3);
+        assert_eq!(pawns.rear_span(Color::White), pawns.front_span(Color::Black));
+        assert_eq!(pawns.front_span(Color::White).rear_span(Color::White) & pawns, pawns);
+
+        let span = pawns.attack_span(Color::Black);
+        assert!(span.contains(Square::C3) && span.contains(Square::E1) && span.contains(Square::G4));
+        assert!(!span.contains(Square::C4) && !span.contains(Square::G5));
+        assert_eq!(span.count(), 3 + 3 + 4);
+    }
 }
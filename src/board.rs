@@ -21,7 +21,7 @@ use std::iter::FromIterator;
 use crate::attacks;
 use crate::bitboard::Bitboard;
 use crate::square::{File, Rank, Square};
-use crate::types::{Color, Piece, Role};
+use crate::types::{Color, Piece, Role, ROLES};
 use crate::material::{Material, MaterialSide};
 
 /// [`Piece`] positions on a board.
@@ -75,6 +75,61 @@ impl Board {
         }
     }
 
+    /// The Chess960 starting position with the given Scharnagl number,
+    /// or `None` if `scharnagl` is not in `0..960`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::Board;
+    ///
+    /// assert_eq!(Board::chess960(518), Some(Board::new()));
+    /// assert_eq!(Board::chess960(960), None);
+    /// ```
+    pub fn chess960(scharnagl: u32) -> Option<Board> {
+        Board::double_chess960(scharnagl, scharnagl)
+    }
+
+    /// A Double Fischer Random starting position, where White and Black
+    /// set up their pieces according to separate Scharnagl numbers.
+    /// Returns `None` if either number is not in `0..960`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::{Board, Chess, CastlingMode, Position, Setup};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let board = Board::double_chess960(518, 0).expect("valid scharnagl numbers");
+    /// assert_eq!(board.to_string(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
+    ///
+    /// let fen = Fen {
+    ///     castling_rights: board.rooks(),
+    ///     board,
+    ///     ..Fen::default()
+    /// };
+    /// let pos: Chess = fen.position(CastlingMode::Chess960)?;
+    /// assert_eq!(pos.castling_rights(), pos.board().rooks());
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    pub fn double_chess960(white: u32, black: u32) -> Option<Board> {
+        let white = chess960_backrank(white)?;
+        let black = chess960_backrank(black)?;
+
+        let mut board = Board::empty();
+        for (file, (w, b)) in white.iter().zip(black.iter()).enumerate() {
+            let file = File::new(file as u32);
+            board.set_piece_at(Square::from_coords(file, Rank::First), w.of(Color::White), false);
+            board.set_piece_at(Square::from_coords(file, Rank::Second), Color::White.pawn(), false);
+            board.set_piece_at(Square::from_coords(file, Rank::Seventh), Color::Black.pawn(), false);
+            board.set_piece_at(Square::from_coords(file, Rank::Eighth), b.of(Color::Black), false);
+        }
+        Some(board)
+    }
+
     pub fn racing_kings() -> Board {
         Board {
             occupied_co: [Bitboard(0x0f0f), Bitboard(0xf0f0)],
@@ -258,6 +313,29 @@ impl Board {
         })
     }
 
+    /// Gets the pieces of color `attacker` that attack `sq`, with sliding
+    /// attacks computed as if exactly the squares in `occupied` were
+    /// occupied.
+    ///
+    /// Passing a modified occupancy allows what-if queries, for example to
+    /// find x-ray attackers behind a piece. Pieces removed from `occupied`
+    /// are still reported if they attack `sq`, so mask the result to
+    /// exclude them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Board, Bitboard, Color, Square};
+    ///
+    /// let board: Board = "4k3/8/8/8/8/8/R7/R3K3".parse()?;
+    /// let occupied = board.occupied();
+    /// assert_eq!(board.attacks_to(Square::A8, Color::White, occupied), Bitboard::from_square(Square::A2));
+    ///
+    /// let without = occupied.without(Square::A2);
+    /// let xray = board.attacks_to(Square::A8, Color::White, without) & without;
+    /// assert_eq!(xray, Bitboard::from_square(Square::A1));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
     #[inline]
     pub fn attacks_to(&self, sq: Square, attacker: Color, occupied: Bitboard) -> Bitboard {
         self.by_color(attacker) & (
@@ -268,6 +346,121 @@ impl Board {
             (attacks::pawn_attacks(!attacker, sq) & self.pawns()))
     }
 
+    /// Finds the least valuable piece of color `attacker` that attacks `sq`,
+    /// with the same `occupied` semantics as [`Board::attacks_to()`].
+    /// Only pieces on `occupied` squares are considered, so that captured
+    /// pieces can be removed from it, for example in static exchange
+    /// evaluation.
+    ///
+    /// Returns the square and role of the attacker, or `None` if `sq` is
+    /// not attacked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Board, Color, Role, Square};
+    ///
+    /// let board: Board = "4k3/8/8/3p4/8/2N5/8/3QK3".parse()?;
+    /// let occupied = board.occupied();
+    /// assert_eq!(board.least_valuable_attacker(Square::D5, Color::White, occupied),
+    ///            Some((Square::C3, Role::Knight)));
+    ///
+    /// let occupied = occupied.without(Square::C3);
+    /// assert_eq!(board.least_valuable_attacker(Square::D5, Color::White, occupied),
+    ///            Some((Square::D1, Role::Queen)));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn least_valuable_attacker(&self, sq: Square, attacker: Color, occupied: Bitboard) -> Option<(Square, Role)> {
+        let attackers = self.attacks_to(sq, attacker, occupied) & occupied;
+        ROLES.iter().find_map(|&role| {
+            (attackers & self.by_role(role)).first().map(|from| (from, role))
+        })
+    }
+
+    /// Gets all squares attacked by pieces of color `attacker`, with
+    /// sliding attacks computed as if exactly the squares in `occupied`
+    /// were occupied.
+    ///
+    /// To find squares where the enemy king would be in check, remove the
+    /// enemy king from `occupied`, so that sliding attacks extend behind it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Board, Color, Square};
+    ///
+    /// let board: Board = "8/8/8/4k3/8/8/8/4RK2".parse()?;
+    /// let attacked = board.attacked_squares(Color::White, board.occupied());
+    /// assert!(attacked.contains(Square::E5));
+    /// assert!(!attacked.contains(Square::E6));
+    ///
+    /// let without_king = board.occupied().without(Square::E5);
+    /// assert!(board.attacked_squares(Color::White, without_king).contains(Square::E6));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn attacked_squares(&self, attacker: Color, occupied: Bitboard) -> Bitboard {
+        let mut attacked = Bitboard(0);
+        for sq in self.by_color(attacker) {
+            if let Some(piece) = self.piece_at(sq) {
+                attacked |= attacks::attacks(sq, piece, occupied);
+            }
+        }
+        attacked
+    }
+
+    /// Pawns of `color` without enemy pawns in front of them on the same
+    /// or adjacent files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Board, Color, Square};
+    ///
+    /// let board: Board = "4k3/2p5/8/1P2P3/8/8/8/4K3".parse()?;
+    /// assert_eq!(board.passed_pawns(Color::White), Bitboard::from_square(Square::E5));
+    /// assert!(board.passed_pawns(Color::Black).is_empty());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn passed_pawns(&self, color: Color) -> Bitboard {
+        let theirs = self.pawns() & self.by_color(!color);
+        let blocked = theirs.front_span(!color) | theirs.attack_span(!color);
+        self.pawns() & self.by_color(color) & !blocked
+    }
+
+    /// Pawns of `color` without pawns of the same color on adjacent files.
+    pub fn isolated_pawns(&self, color: Color) -> Bitboard {
+        let ours = self.pawns() & self.by_color(color);
+        ours & !adjacent_files(ours.file_fill())
+    }
+
+    /// Pawns of `color` that share their file with another pawn of the
+    /// same color. All pawns on such a file are included.
+    pub fn doubled_pawns(&self, color: Color) -> Bitboard {
+        let ours = self.pawns() & self.by_color(color);
+        ours & (ours.front_span(color) | ours.rear_span(color))
+    }
+
+    /// Pawns of `color` that can not advance to their stop square without
+    /// it being attacked by an enemy pawn, and that no pawn of the same
+    /// color on an adjacent file could ever defend there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Board, Color, Square};
+    ///
+    /// let board: Board = "4k3/8/8/3p4/1P6/2P5/8/4K3".parse()?;
+    /// assert_eq!(board.backward_pawns(Color::White), Bitboard::from_square(Square::C3));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn backward_pawns(&self, color: Color) -> Bitboard {
+        let ours = self.pawns() & self.by_color(color);
+        let theirs = self.pawns() & self.by_color(!color);
+        let their_attacks = adjacent_files(theirs.relative_shift(!color, 8));
+        let stops = ours.relative_shift(color, 8) & !ours.attack_span(color) & their_attacks;
+        ours & stops.relative_shift(!color, 8)
+    }
+
     pub fn pieces(&self) -> Pieces {
         Pieces {
             pawns: self.pawns(),
@@ -280,6 +473,54 @@ impl Board {
         }
     }
 
+    /// Applies a transformation like [`Bitboard::flip_vertical()`] to all
+    /// pieces on the board.
+    pub fn transform<F: Fn(Bitboard) -> Bitboard>(&mut self, f: F) {
+        for bb in self.occupied_co.iter_mut().chain(self.occupied.iter_mut()) {
+            *bb = f(*bb);
+        }
+        self.promoted = f(self.promoted);
+    }
+
+    /// Mirrors the board at the horizontal center line, so that the first
+    /// rank becomes the eighth rank.
+    pub fn flip_vertical(&mut self) {
+        self.transform(Bitboard::flip_vertical);
+    }
+
+    /// Mirrors the board at the vertical center line, so that the a-file
+    /// becomes the h-file.
+    pub fn flip_horizontal(&mut self) {
+        self.transform(Bitboard::flip_horizontal);
+    }
+
+    /// Rotates the board by 180 degrees.
+    pub fn rotate_180(&mut self) {
+        self.transform(Bitboard::rotate_180);
+    }
+
+    /// Swaps the colors of all pieces.
+    pub fn swap_colors(&mut self) {
+        self.occupied_co.swap(0, 1);
+    }
+
+    /// Flips the board vertically and swaps the colors of all pieces, so
+    /// that it looks the same from the other side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::Board;
+    ///
+    /// let mut board = Board::new();
+    /// board.mirror();
+    /// assert_eq!(board, Board::new());
+    /// ```
+    pub fn mirror(&mut self) {
+        self.flip_vertical();
+        self.swap_colors();
+    }
+
     pub fn material_side(&self, color: Color) -> MaterialSide {
         let side = self.by_color(color);
 
@@ -301,6 +542,53 @@ impl Board {
     }
 }
 
+/// Derives the back rank from a Scharnagl number, indexed by file.
+fn chess960_backrank(scharnagl: u32) -> Option<[Role; 8]> {
+    if scharnagl >= 960 {
+        return None;
+    }
+
+    let mut backrank = [None; 8];
+
+    let mut n = scharnagl;
+    backrank[(n % 4 * 2 + 1) as usize] = Some(Role::Bishop); // light squares
+    n /= 4;
+    backrank[(n % 4 * 2) as usize] = Some(Role::Bishop); // dark squares
+    n /= 4;
+
+    fn place(backrank: &mut [Option<Role>; 8], nth_empty: u32, role: Role) {
+        let idx = backrank.iter()
+            .enumerate()
+            .filter(|(_, r)| r.is_none())
+            .nth(nth_empty as usize)
+            .map(|(idx, _)| idx)
+            .expect("enough empty squares");
+        backrank[idx] = Some(role);
+    }
+
+    place(&mut backrank, n % 6, Role::Queen);
+    n /= 6;
+
+    let (first, second) = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)][n as usize];
+    place(&mut backrank, second, Role::Knight);
+    place(&mut backrank, first, Role::Knight);
+
+    for &role in &[Role::Rook, Role::King, Role::Rook] {
+        place(&mut backrank, 0, role);
+    }
+
+    let mut roles = [Role::Pawn; 8];
+    for (role, placed) in roles.iter_mut().zip(backrank.iter()) {
+        *role = placed.expect("all squares filled");
+    }
+    Some(roles)
+}
+
+/// Squares one file to the east or west of the given squares.
+fn adjacent_files(bb: Bitboard) -> Bitboard {
+    Bitboard(((bb.0 << 1) & !Bitboard::file(File::A).0) | ((bb.0 >> 1) & !Bitboard::file(File::H).0))
+}
+
 impl Default for Board {
     fn default() -> Self {
         Board::new()
@@ -427,4 +715,65 @@ mod tests {
         assert_eq!(board.piece_at(Square::C1), Some(Black.queen()));
         assert!(board.promoted().contains(Square::C1));
     }
+
+    #[test]
+    fn test_attacks_to() {
+        let board: Board = "4k3/8/8/3p4/4P3/8/1B6/q3K3".parse().expect("valid fen");
+        let occupied = board.occupied();
+        assert_eq!(board.attacks_to(Square::D5, White, occupied), Bitboard::from_square(Square::E4));
+        assert_eq!(board.attacks_to(Square::E4, Black, occupied), Bitboard::from_square(Square::D5));
+        assert_eq!(board.attacks_to(Square::B2, Black, occupied), Bitboard::from_square(Square::A1));
+
+        let without = occupied.without(Square::B2);
+        assert_eq!(board.attacks_to(Square::D4, Black, without), Bitboard::from_square(Square::A1));
+        assert!(board.attacks_to(Square::D4, Black, occupied).is_empty());
+    }
+
+    #[test]
+    fn test_attacked_squares() {
+        let board = Board::new();
+        let attacked = board.attacked_squares(White, board.occupied());
+        assert_eq!(attacked, Bitboard::rank(Rank::Second) | Bitboard::rank(Rank::Third) |
+                             Bitboard::rank(Rank::First).without(Square::A1).without(Square::H1));
+        assert!(board.attacked_squares(Black, board.occupied()).contains(Square::F6));
+    }
+
+    #[test]
+    fn test_least_valuable_attacker() {
+        let board: Board = "4k3/8/2n5/3p4/4P3/8/6B1/3RK3".parse().expect("valid fen");
+        let mut occupied = board.occupied();
+        let mut sequence = Vec::new();
+        while let Some((from, role)) = board.least_valuable_attacker(Square::D5, White, occupied) {
+            sequence.push(role);
+            occupied.discard(from);
+        }
+        assert_eq!(sequence, [Role::Pawn, Role::Bishop, Role::Rook]);
+        assert_eq!(board.least_valuable_attacker(Square::D5, Black, board.occupied()), None);
+        assert_eq!(board.least_valuable_attacker(Square::E4, Black, board.occupied()), Some((Square::D5, Role::Pawn)));
+    }
+
+    #[test]
+    fn test_pawn_structure() {
+        let board: Board = "4k3/p7/8/2P5/8/8/P7/4K3".parse().expect("valid fen");
+        assert_eq!(board.passed_pawns(White), Bitboard::from_square(Square::C5));
+        assert!(board.passed_pawns(Black).is_empty());
+        assert_eq!(board.isolated_pawns(White), Bitboard::from_square(Square::A2).with(Square::C5));
+        assert_eq!(board.isolated_pawns(Black), Bitboard::from_square(Square::A7));
+
+        let board: Board = "4k3/8/8/8/2P5/2P5/2PP4/4K3".parse().expect("valid fen");
+        assert_eq!(board.doubled_pawns(White), Bitboard::file(File::C) & board.pawns());
+        assert!(board.doubled_pawns(Black).is_empty());
+        assert!(board.isolated_pawns(White).is_empty());
+
+        let board: Board = "4k3/5p2/6p1/4P3/3P4/8/8/4K3".parse().expect("valid fen");
+        assert_eq!(board.backward_pawns(Black), Bitboard::from_square(Square::F7));
+        assert!(board.backward_pawns(White).is_empty());
+    }
+
+    #[test]
+    fn test_chess960() {
+        assert_eq!(Board::chess960(0).expect("valid").to_string(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR");
+        assert_eq!(Board::chess960(959).expect("valid").to_string(), "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB");
+        assert_eq!(Board::double_chess960(0, 960), None);
+    }
 }
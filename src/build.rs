@@ -8,6 +8,7 @@ use std::io::Write;
 use std::path::Path;
 
 mod errors;
+#[allow(unused_imports)] // re-exports are only used by the library
 mod types;
 mod square;
 mod bitboard;
@@ -72,7 +73,7 @@ fn init_magics(sq: Square, magic: &Magic, shift: u32, attacks: &mut [Bitboard],
 fn dump_slice<W: Write, T: Clone + LowerHex>(w: &mut W, name: &str, tname: &str, slice: &[T]) -> io::Result<()> {
     writeln!(w, "#[allow(clippy::unreadable_literal)]")?;
     write!(w, "static {}: [{}; {}] = [", name, tname, slice.len())?;
-    for v in slice.iter().cloned() {
+    for v in slice {
         write!(w, "0x{:x}, ", v)?;
     }
     writeln!(w, "];")
@@ -83,7 +84,7 @@ fn dump_table<W: Write, T: Clone + LowerHex>(w: &mut W, name: &str, tname: &str,
     write!(w, "static {}: [[{}; 64]; 64] = [", name, tname)?;
     for row in table.iter() {
         write!(w, "[")?;
-        for column in row.iter().cloned() {
+        for column in row {
             write!(w, "0x{:x}, ", column)?;
         }
         write!(w, "], ")?;
@@ -97,7 +98,17 @@ fn main() -> io::Result<()> {
     let dest_path = Path::new(&out_dir).join("attacks.rs");
     let mut f = File::create(&dest_path).expect("created attacks.rs");
     generate_basics(&mut f)?;
-    generate_sliding_attacks(&mut f)
+    generate_sliding_attacks(&mut f)?;
+
+    // generate zobrist.rs
+    let dest_path = Path::new(&out_dir).join("zobrist.rs");
+    let mut f = File::create(&dest_path).expect("created zobrist.rs");
+    generate_zobrist_keys(&mut f)?;
+
+    // generate kpk.rs
+    let dest_path = Path::new(&out_dir).join("kpk.rs");
+    let mut f = File::create(&dest_path).expect("created kpk.rs");
+    generate_kpk(&mut f)
 }
 
 fn generate_basics<W: Write>(f: &mut W) -> io::Result<()> {
@@ -150,3 +161,125 @@ fn generate_sliding_attacks<W: Write>(f: &mut W) -> io::Result<()> {
 
     dump_slice(f, "ATTACKS", "u64", &attacks)
 }
+
+fn generate_zobrist_keys<W: Write>(f: &mut W) -> io::Result<()> {
+    // SplitMix64 with a fixed seed, so that keys are stable across builds.
+    let mut state: u64 = 0x5348_414b_4d41_5459;
+    let mut keys = [0u64; 781 + 288];
+    for key in keys.iter_mut() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        *key = z ^ (z >> 31);
+    }
+
+    dump_slice(f, "KEYS", "u64", &keys[..781])?;
+    dump_slice(f, "VARIANT_KEYS", "u64", &keys[781..])
+}
+
+const KPK_INVALID: u8 = 0;
+const KPK_UNKNOWN: u8 = 1;
+const KPK_DRAW: u8 = 2;
+const KPK_WIN: u8 = 4;
+
+// 24 pawn squares (files a-d, ranks 2-7), side to move, black king and
+// white king.
+const KPK_SIZE: usize = 24 * 2 * 64 * 64;
+
+fn kpk_index(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> usize {
+    let pawn_index = usize::from(pawn.file()) * 6 + usize::from(pawn.rank()) - 1;
+    ((pawn_index * 2 + if white_to_move { 0 } else { 1 }) * 64 + usize::from(black_king)) * 64 + usize::from(white_king)
+}
+
+fn kpk_squares(idx: usize) -> (bool, Square, Square, Square) {
+    let white_king = Square::new((idx & 63) as u32);
+    let black_king = Square::new((idx >> 6 & 63) as u32);
+    let white_to_move = idx >> 12 & 1 == 0;
+    let pawn_index = (idx >> 13) as u32;
+    let pawn = Square::new((pawn_index % 6 + 1) * 8 + pawn_index / 6);
+    (white_to_move, white_king, black_king, pawn)
+}
+
+fn kpk_init(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> u8 {
+    let white_king_attacks = step_attacks(white_king, &KING_DELTAS);
+    let black_king_attacks = step_attacks(black_king, &KING_DELTAS);
+    let pawn_attacks = step_attacks(pawn, &WHITE_PAWN_DELTAS);
+    let push = pawn.offset(8).expect("pawn not on last rank");
+
+    if white_king.distance(black_king) <= 1 || white_king == pawn || black_king == pawn ||
+       (white_to_move && pawn_attacks.contains(black_king)) {
+        KPK_INVALID
+    } else if white_to_move && pawn.rank() == crate::square::Rank::Seventh &&
+              white_king != push && black_king != push &&
+              (black_king.distance(push) > 1 || white_king.distance(push) == 1) {
+        // Promotes without the queen being captured.
+        KPK_WIN
+    } else if !white_to_move &&
+              ((black_king_attacks & !(white_king_attacks | pawn_attacks)).is_empty() ||
+               (black_king_attacks & !white_king_attacks).contains(pawn)) {
+        // Stalemate or pawn can be captured.
+        KPK_DRAW
+    } else {
+        KPK_UNKNOWN
+    }
+}
+
+fn kpk_classify(db: &[u8], white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> u8 {
+    let mut r = KPK_INVALID;
+
+    if white_to_move {
+        for to in step_attacks(white_king, &KING_DELTAS) {
+            r |= db[kpk_index(false, to, black_king, pawn)];
+        }
+
+        if pawn.rank() < crate::square::Rank::Seventh {
+            let push = pawn.offset(8).expect("single push");
+            r |= db[kpk_index(false, white_king, black_king, push)];
+
+            if pawn.rank() == crate::square::Rank::Second && push != white_king && push != black_king {
+                r |= db[kpk_index(false, white_king, black_king, push.offset(8).expect("double push"))];
+            }
+        }
+
+        if r & KPK_WIN != 0 { KPK_WIN } else if r & KPK_UNKNOWN != 0 { KPK_UNKNOWN } else { KPK_DRAW }
+    } else {
+        for to in step_attacks(black_king, &KING_DELTAS) {
+            r |= db[kpk_index(true, white_king, to, pawn)];
+        }
+
+        if r & KPK_DRAW != 0 { KPK_DRAW } else if r & KPK_UNKNOWN != 0 { KPK_UNKNOWN } else { KPK_WIN }
+    }
+}
+
+fn generate_kpk<W: Write>(f: &mut W) -> io::Result<()> {
+    let mut db = vec![KPK_UNKNOWN; KPK_SIZE];
+    for (idx, result) in db.iter_mut().enumerate() {
+        let (white_to_move, white_king, black_king, pawn) = kpk_squares(idx);
+        *result = kpk_init(white_to_move, white_king, black_king, pawn);
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for idx in 0..KPK_SIZE {
+            if db[idx] == KPK_UNKNOWN {
+                let (white_to_move, white_king, black_king, pawn) = kpk_squares(idx);
+                let result = kpk_classify(&db, white_to_move, white_king, black_king, pawn);
+                if result != KPK_UNKNOWN {
+                    db[idx] = result;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut bitbase = vec![0u64; KPK_SIZE / 64];
+    for (idx, &result) in db.iter().enumerate() {
+        if result == KPK_WIN {
+            bitbase[idx / 64] |= 1 << (idx % 64);
+        }
+    }
+
+    dump_slice(f, "KPK", "u64", &bitbase)
+}
@@ -0,0 +1,349 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Parse and write Extended Position Description.
+//!
+//! An EPD consists of the first four fields of a FEN, followed by
+//! operations like `bm Nf3;` or `id "WAC.001";`.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! #
+//! use shakmaty::{CastlingMode, Chess, Position};
+//! use shakmaty::epd::Epd;
+//!
+//! let epd: Epd = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";".parse()?;
+//! assert_eq!(epd.id(), Some("WAC.001"));
+//!
+//! let pos: Chess = epd.position(CastlingMode::Standard)?;
+//! let bm = epd.bm().expect("valid bm");
+//! assert!(pos.is_legal(&bm[0].to_move(&pos)?));
+//!
+//! assert_eq!(epd.to_string(), "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";");
+//! #
+//! # Ok::<_, Box<dyn Error>>(())
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::ops::Range;
+use std::slice;
+use std::str::FromStr;
+
+use crate::bitboard::Bitboard;
+use crate::board::Board;
+use crate::fen::{self, Fen, ParseFenError};
+use crate::material::Material;
+use crate::position::{FromSetup, PositionError};
+use crate::san::San;
+use crate::setup::Setup;
+use crate::square::Square;
+use crate::types::{CastlingMode, Color, RemainingChecks};
+
+/// Error when parsing an invalid EPD.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseEpdError {
+    /// The position part is not valid.
+    InvalidFen(ParseFenError),
+    /// An operation is not syntactically valid.
+    InvalidOperation { span: Range<usize> },
+}
+
+impl ParseEpdError {
+    /// Byte range of the offending token in the input.
+    pub fn span(&self) -> Range<usize> {
+        match *self {
+            ParseEpdError::InvalidFen(ref err) => err.span(),
+            ParseEpdError::InvalidOperation { ref span } => span.clone(),
+        }
+    }
+}
+
+impl From<ParseFenError> for ParseEpdError {
+    fn from(err: ParseFenError) -> ParseEpdError {
+        ParseEpdError::InvalidFen(err)
+    }
+}
+
+impl fmt::Display for ParseEpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseEpdError::InvalidFen(ref err) => err.fmt(f),
+            ParseEpdError::InvalidOperation { .. } => "invalid epd operation".fmt(f),
+        }
+    }
+}
+
+impl Error for ParseEpdError {
+    fn description(&self) -> &str {
+        "invalid epd"
+    }
+}
+
+/// A position with EPD operations.
+///
+/// Operations are kept in order. The halfmove clock and fullmove number
+/// of the position are taken from the `hmvc` and `fmvn` operations, if
+/// present.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Epd {
+    setup: Fen,
+    operations: Vec<(String, Vec<String>)>,
+}
+
+/// Returns the range of the next token, stopping at whitespace or `;`.
+fn token(epd: &[u8], start: usize) -> Range<usize> {
+    let len = epd[start..].iter().take_while(|ch| !ch.is_ascii_whitespace() && **ch != b';').count();
+    start..(start + len)
+}
+
+fn skip_whitespace(epd: &[u8], start: usize) -> usize {
+    start + epd[start..].iter().take_while(|ch| ch.is_ascii_whitespace()).count()
+}
+
+impl Epd {
+    /// Creates an EPD of a setup without any operations.
+    pub fn from_setup<S: Setup>(setup: &S) -> Epd {
+        Epd {
+            setup: Fen::from_setup(setup),
+            operations: Vec::new(),
+        }
+    }
+
+    /// Parses an EPD.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseEpdError`] if the input is not a valid EPD.
+    pub fn from_ascii(epd: &[u8]) -> Result<Epd, ParseEpdError> {
+        // Find the end of the four position fields, optionally followed by
+        // remaining checks.
+        let mut end = 0;
+        for _ in 0..4 {
+            let next = token(epd, skip_whitespace(epd, end));
+            if next.start == next.end {
+                break;
+            }
+            end = next.end;
+        }
+        let checks = token(epd, skip_whitespace(epd, end));
+        if RemainingChecks::from_ascii(&epd[checks.clone()]).is_some() {
+            end = checks.end;
+        }
+
+        let mut result = Epd {
+            setup: Fen::from_ascii(&epd[..end])?,
+            operations: Vec::new(),
+        };
+
+        let mut idx = skip_whitespace(epd, end);
+        while idx < epd.len() {
+            let opcode = token(epd, idx);
+            if opcode.start == opcode.end || !epd[opcode.clone()].iter().all(|ch| ch.is_ascii_alphanumeric() || *ch == b'_') {
+                return Err(ParseEpdError::InvalidOperation { span: idx..(idx + opcode.len().max(1)) });
+            }
+            idx = opcode.end;
+
+            let mut operands = Vec::new();
+            loop {
+                idx = skip_whitespace(epd, idx);
+                match epd.get(idx) {
+                    None => break, // tolerate missing final semicolon
+                    Some(b';') => {
+                        idx += 1;
+                        break;
+                    }
+                    Some(b'"') => {
+                        let len = epd[idx + 1..].iter().position(|ch| *ch == b'"')
+                            .ok_or(ParseEpdError::InvalidOperation { span: idx..epd.len() })?;
+                        operands.push(String::from_utf8_lossy(&epd[idx + 1..idx + 1 + len]).into_owned());
+                        idx += len + 2;
+                    }
+                    Some(_) => {
+                        let operand = token(epd, idx);
+                        operands.push(String::from_utf8_lossy(&epd[operand.clone()]).into_owned());
+                        idx = operand.end;
+                    }
+                }
+            }
+
+            result.operations.push((String::from_utf8_lossy(&epd[opcode]).into_owned(), operands));
+            idx = skip_whitespace(epd, idx);
+        }
+
+        Ok(result)
+    }
+
+    /// Set up a [`Position`](super::Position).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PositionError`] if the setup is not a legal position.
+    pub fn position<P: FromSetup>(&self, mode: CastlingMode) -> Result<P, PositionError<P>> {
+        P::from_setup(self, mode)
+    }
+
+    /// Gets the operands of an operation.
+    pub fn get(&self, opcode: &str) -> Option<&[String]> {
+        self.operations.iter().find(|(o, _)| o == opcode).map(|(_, operands)| operands.as_slice())
+    }
+
+    /// Sets the operands of an operation, replacing any previous operands
+    /// but keeping its position.
+    pub fn insert<O: Into<String>>(&mut self, opcode: O, operands: Vec<String>) {
+        let opcode = opcode.into();
+        match self.operations.iter_mut().find(|(o, _)| *o == opcode) {
+            Some(op) => op.1 = operands,
+            None => self.operations.push((opcode, operands)),
+        }
+    }
+
+    /// Removes an operation and returns its operands.
+    pub fn remove(&mut self, opcode: &str) -> Option<Vec<String>> {
+        let idx = self.operations.iter().position(|(o, _)| o == opcode)?;
+        Some(self.operations.remove(idx).1)
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, (String, Vec<String>)> {
+        self.operations.iter()
+    }
+
+    fn sans(&self, opcode: &str) -> Option<Vec<San>> {
+        self.get(opcode)?.iter().map(|san| san.parse().ok()).collect()
+    }
+
+    fn number<T: FromStr>(&self, opcode: &str) -> Option<T> {
+        match self.get(opcode)? {
+            [n] => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The best moves (`bm`), or `None` if missing or invalid.
+    pub fn bm(&self) -> Option<Vec<San>> {
+        self.sans("bm")
+    }
+
+    /// The moves to avoid (`am`), or `None` if missing or invalid.
+    pub fn am(&self) -> Option<Vec<San>> {
+        self.sans("am")
+    }
+
+    /// The identifier (`id`).
+    pub fn id(&self) -> Option<&str> {
+        match self.get("id")? {
+            [id] => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The halfmove clock (`hmvc`), or `None` if missing or invalid.
+    pub fn hmvc(&self) -> Option<u32> {
+        self.number("hmvc")
+    }
+
+    /// The fullmove number (`fmvn`), or `None` if missing or invalid.
+    pub fn fmvn(&self) -> Option<NonZeroU32> {
+        self.number("fmvn").and_then(NonZeroU32::new)
+    }
+
+    /// The centipawn evaluation (`ce`) from the point of view of the side
+    /// to move, or `None` if missing or invalid.
+    pub fn ce(&self) -> Option<i32> {
+        self.number("ce")
+    }
+}
+
+impl Setup for Epd {
+    fn board(&self) -> &Board { self.setup.board() }
+    fn pockets(&self) -> Option<&Material> { self.setup.pockets() }
+    fn turn(&self) -> Color { self.setup.turn() }
+    fn castling_rights(&self) -> Bitboard { self.setup.castling_rights() }
+    fn ep_square(&self) -> Option<Square> { self.setup.ep_square() }
+    fn maybe_ep_square(&self) -> Option<Square> { self.setup.maybe_ep_square() }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { self.setup.remaining_checks() }
+    fn halfmoves(&self) -> u32 { self.hmvc().unwrap_or(0) }
+    fn fullmoves(&self) -> NonZeroU32 { self.fmvn().unwrap_or_else(|| NonZeroU32::new(1).unwrap()) }
+}
+
+impl FromStr for Epd {
+    type Err = ParseEpdError;
+
+    fn from_str(epd: &str) -> Result<Epd, ParseEpdError> {
+        Epd::from_ascii(epd.as_bytes())
+    }
+}
+
+fn needs_quotes(opcode: &str, operand: &str) -> bool {
+    let is_comment = opcode == "id" || (opcode.len() == 2 && opcode.starts_with('c') && opcode.as_bytes()[1].is_ascii_digit());
+    is_comment || operand.is_empty() || operand.contains(|ch: char| ch.is_ascii_whitespace() || ch == ';' || ch == '"')
+}
+
+impl fmt::Display for Epd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&fen::epd(&self.setup))?;
+        for (opcode, operands) in &self.operations {
+            write!(f, " {}", opcode)?;
+            for operand in operands {
+                if needs_quotes(opcode, operand) {
+                    write!(f, " \"{}\"", operand)?;
+                } else {
+                    write!(f, " {}", operand)?;
+                }
+            }
+            f.write_str(";")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operations() {
+        let epd: Epd = "r1bqk1r1/1p1p1n2/p1n2pN1/2p1b2Q/2P1Pp2/1PN5/PB4PP/R4RK1 w q - bm Rxf4; am Nxe5 Qxe5; ce -150; hmvc 3; fmvn 21; c0 \"\"; foo bar baz"
+            .parse().expect("valid epd");
+        assert_eq!(epd.bm(), Some(vec!["Rxf4".parse().expect("valid san")]));
+        assert_eq!(epd.am().map(|am| am.len()), Some(2));
+        assert_eq!(epd.ce(), Some(-150));
+        assert_eq!(epd.halfmoves(), 3);
+        assert_eq!(epd.fullmoves().get(), 21);
+        assert_eq!(epd.id(), None);
+        assert_eq!(epd.get("foo"), Some(&["bar".to_owned(), "baz".to_owned()][..]));
+        assert_eq!(epd.to_string(), "r1bqk1r1/1p1p1n2/p1n2pN1/2p1b2Q/2P1Pp2/1PN5/PB4PP/R4RK1 w q - bm Rxf4; am Nxe5 Qxe5; ce -150; hmvc 3; fmvn 21; c0 \"\"; foo bar baz;");
+    }
+
+    #[test]
+    fn test_three_check() {
+        let epd: Epd = "4k3/8/8/8/8/8/8/4K3 w - - 1+2 id \"3check\";".parse().expect("valid epd");
+        assert!(epd.remaining_checks().is_some());
+        assert_eq!(epd.to_string(), "4k3/8/8/8/8/8/8/4K3 w - - 1+2 id \"3check\";");
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = "4k3/8/8/8/8/8/8/4K3 w - - id \"WAC.001".parse::<Epd>().expect_err("unterminated string");
+        assert_eq!(err.span(), 29..37);
+        let err = "4k3/8/8/8/8/8/8/4K3 w - - bm.x Nf3;".parse::<Epd>().expect_err("invalid opcode");
+        assert_eq!(err.span(), 26..30);
+        assert!(matches!("4k3/8/8/8/8/8/8/4K3 x - -".parse::<Epd>(), Err(ParseEpdError::InvalidFen(_))));
+    }
+}
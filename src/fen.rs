@@ -70,14 +70,16 @@ use std::str::FromStr;
 use std::fmt;
 use std::char;
 use std::error::Error;
+use std::ops::Range;
 
 use crate::square::{File, Rank, Square};
-use crate::types::{Black, Color, Piece, RemainingChecks, White, CastlingMode};
+use crate::attacks;
+use crate::types::{Black, Color, EnPassantMode, Piece, RemainingChecks, Role, White, CastlingMode};
 use crate::material::Material;
 use crate::bitboard::Bitboard;
 use crate::board::Board;
 use crate::setup::Setup;
-use crate::position::{FromSetup, PositionError};
+use crate::position::{FromSetup, Position, PositionError};
 
 /// FEN formatting options.
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
@@ -85,6 +87,9 @@ pub struct FenOpts {
     promoted: bool,
     shredder: bool,
     scid: bool,
+    pockets: bool,
+    remaining_checks: bool,
+    ep_mode: EnPassantMode,
 }
 
 impl FenOpts {
@@ -94,6 +99,9 @@ impl FenOpts {
             promoted: false,
             shredder: false,
             scid: false,
+            pockets: true,
+            remaining_checks: true,
+            ep_mode: EnPassantMode::Legal,
         }
     }
 
@@ -105,6 +113,24 @@ impl FenOpts {
 
     /// Decide if castling rights should be displayed in Shredder format,
     /// e.g. `HAha` instead of `KQkq`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::{CastlingMode, Chess};
+    /// use shakmaty::fen::{Fen, FenOpts};
+    ///
+    /// let pos: Chess = "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .position(CastlingMode::Chess960)?;
+    ///
+    /// assert_eq!(FenOpts::default().shredder(true).epd(&pos),
+    ///            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf -");
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
     pub fn shredder(&mut self, shredder: bool) -> &mut FenOpts {
         self.shredder = shredder;
         self
@@ -112,11 +138,105 @@ impl FenOpts {
 
     /// Decide if Crazyhouse pockets and remaining check counters should use
     /// Scid-style, e.g. `/q` instead of `[q]` and `+0+0` instead of `3+3`.
+    ///
+    /// The default style writes remaining checks before the move counters,
+    /// as used by multi-variant Stockfish. The Scid style (also used by
+    /// lichess) counts checks already given and appends them after the move
+    /// counters. Both styles are accepted when parsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::fen::{Fen, FenOpts};
+    ///
+    /// let fen: Fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 2+3 0 1".parse()?;
+    ///
+    /// assert_eq!(FenOpts::default().fen(&fen),
+    ///            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 2+3 0 1");
+    /// assert_eq!(FenOpts::default().scid(true).fen(&fen),
+    ///            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +1+0");
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
     pub fn scid(&mut self, scid: bool) -> &mut FenOpts {
         self.scid = scid;
         self
     }
 
+    /// Decide if Crazyhouse pockets should be included. Defaults to `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::fen::{Fen, FenOpts};
+    ///
+    /// let mut fen = Fen::default();
+    /// fen.pockets = Some(Default::default());
+    ///
+    /// assert_eq!(FenOpts::default().epd(&fen),
+    ///            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq -");
+    /// assert_eq!(FenOpts::default().pockets(false).epd(&fen),
+    ///            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+    /// ```
+    pub fn pockets(&mut self, pockets: bool) -> &mut FenOpts {
+        self.pockets = pockets;
+        self
+    }
+
+    /// Decide if remaining check counters should be included. Defaults to
+    /// `true`.
+    pub fn remaining_checks(&mut self, remaining_checks: bool) -> &mut FenOpts {
+        self.remaining_checks = remaining_checks;
+        self
+    }
+
+    /// Decide when to include the en passant square. Defaults to
+    /// [`EnPassantMode::Legal`].
+    ///
+    /// Setups that are not positions, like [`Fen`], can not tell if an
+    /// en passant capture is legal. Their en passant square is included
+    /// as is for [`EnPassantMode::Legal`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::{Chess, EnPassantMode, Move, Position, Role, Square};
+    /// use shakmaty::fen::FenOpts;
+    ///
+    /// let pos = Chess::default().play(&Move::Normal {
+    ///     role: Role::Pawn,
+    ///     from: Square::E2,
+    ///     capture: None,
+    ///     to: Square::E4,
+    ///     promotion: None,
+    /// })?;
+    ///
+    /// assert_eq!(FenOpts::default().ep_mode(EnPassantMode::Legal).epd(&pos),
+    ///            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq -");
+    /// assert_eq!(FenOpts::default().ep_mode(EnPassantMode::Always).epd(&pos),
+    ///            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3");
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    pub fn ep_mode(&mut self, ep_mode: EnPassantMode) -> &mut FenOpts {
+        self.ep_mode = ep_mode;
+        self
+    }
+
+    fn ep_square(&self, setup: &dyn Setup) -> Option<Square> {
+        match self.ep_mode {
+            EnPassantMode::Legal => setup.ep_square(),
+            EnPassantMode::PseudoLegal => setup.maybe_ep_square().filter(|sq| {
+                (attacks::pawn_attacks(!setup.turn(), *sq) & setup.our(Role::Pawn)).any()
+            }),
+            EnPassantMode::Always => setup.maybe_ep_square(),
+        }
+    }
+
     /// Create a board FEN such as
     /// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR`.
     pub fn board_fen(&self, board: &Board) -> String {
@@ -182,7 +302,7 @@ impl FenOpts {
     /// Create an EPD such as
     /// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -`.
     pub fn epd(&self, setup: &dyn Setup) -> String {
-        let pockets = setup.pockets().map_or("".to_owned(), |p| {
+        let pockets = setup.pockets().filter(|_| self.pockets).map_or("".to_owned(), |p| {
             if self.scid {
                 format!("/{}", p.fen())
             } else {
@@ -190,7 +310,7 @@ impl FenOpts {
             }
         });
 
-        let checks = setup.remaining_checks().map_or("".to_owned(), |r| {
+        let checks = setup.remaining_checks().filter(|_| self.remaining_checks).map_or("".to_owned(), |r| {
             if self.scid {
                 format!(" +{}+{}", 3u8.saturating_sub(r.white), 3u8.saturating_sub(r.black))
             } else {
@@ -203,21 +323,21 @@ impl FenOpts {
                 pockets,
                 setup.turn().char(),
                 self.castling_fen(setup.board(), setup.castling_rights()),
-                setup.ep_square().map_or("-".to_owned(), |sq| sq.to_string()),
+                self.ep_square(setup).map_or("-".to_owned(), |sq| sq.to_string()),
                 checks)
     }
 
     /// Create a FEN such as
     /// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1`.
     pub fn fen(&self, setup: &dyn Setup) -> String {
-        match setup.remaining_checks() {
+        match setup.remaining_checks().filter(|_| self.remaining_checks) {
             Some(checks) if self.scid => {
                 format!("{}{} {} {} {} {} {} +{}+{}",
                     self.board_fen(setup.board()),
-                    setup.pockets().map_or("".to_owned(), |p| format!("/{}", p.fen())),
+                    setup.pockets().filter(|_| self.pockets).map_or("".to_owned(), |p| format!("/{}", p.fen())),
                     setup.turn().char(),
                     self.castling_fen(setup.board(), setup.castling_rights()),
-                    setup.ep_square().map_or("-".to_owned(), |sq| sq.to_string()),
+                    self.ep_square(setup).map_or("-".to_owned(), |sq| sq.to_string()),
                     setup.halfmoves(),
                     setup.fullmoves(),
                     3u8.saturating_sub(checks.white),
@@ -234,9 +354,9 @@ impl Default for FenOpts {
     }
 }
 
-/// Errors that can occur when parsing a FEN.
-#[derive(Clone, Eq, PartialEq, Debug)]
-pub enum ParseFenError {
+/// The part of a FEN that could not be parsed.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ParseFenErrorKind {
     InvalidFen,
     InvalidBoard,
     InvalidPocket,
@@ -248,34 +368,63 @@ pub enum ParseFenError {
     InvalidFullmoves,
 }
 
-impl ParseFenError {
-    fn desc(&self) -> &str {
-        match *self {
-            ParseFenError::InvalidFen => "invalid fen",
-            ParseFenError::InvalidBoard => "invalid board part in fen",
-            ParseFenError::InvalidPocket => "invalid pocket in fen",
-            ParseFenError::InvalidTurn => "invalid turn part in fen",
-            ParseFenError::InvalidCastling => "invalid castling part in fen",
-            ParseFenError::InvalidEpSquare => "invalid ep square in fen",
-            ParseFenError::InvalidRemainingChecks => "invalid remaining checks in fen",
-            ParseFenError::InvalidHalfmoveClock => "invalid halfmove clock in fen",
-            ParseFenError::InvalidFullmoves => "invalid fullmove part in fen",
+impl ParseFenErrorKind {
+    fn desc(self) -> &'static str {
+        match self {
+            ParseFenErrorKind::InvalidFen => "invalid fen",
+            ParseFenErrorKind::InvalidBoard => "invalid board part in fen",
+            ParseFenErrorKind::InvalidPocket => "invalid pocket in fen",
+            ParseFenErrorKind::InvalidTurn => "invalid turn part in fen",
+            ParseFenErrorKind::InvalidCastling => "invalid castling part in fen",
+            ParseFenErrorKind::InvalidEpSquare => "invalid ep square in fen",
+            ParseFenErrorKind::InvalidRemainingChecks => "invalid remaining checks in fen",
+            ParseFenErrorKind::InvalidHalfmoveClock => "invalid halfmove clock in fen",
+            ParseFenErrorKind::InvalidFullmoves => "invalid fullmove part in fen",
         }
     }
 }
 
+/// Errors that can occur when parsing a FEN.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ParseFenError {
+    kind: ParseFenErrorKind,
+    span: Range<usize>,
+}
+
+impl ParseFenError {
+    fn new(kind: ParseFenErrorKind, span: Range<usize>) -> ParseFenError {
+        ParseFenError { kind, span }
+    }
+
+    /// The part of the FEN that could not be parsed.
+    pub fn kind(&self) -> ParseFenErrorKind {
+        self.kind
+    }
+
+    /// Byte range of the offending token in the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
 impl fmt::Display for ParseFenError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.desc().fmt(f)
+        self.kind.desc().fmt(f)
     }
 }
 
 impl Error for ParseFenError {
     fn description(&self) -> &str {
-        self.desc()
+        self.kind.desc()
     }
 }
 
+/// Byte range of `part` within `whole`, which must be a subslice.
+fn span_of(whole: &[u8], part: &[u8]) -> Range<usize> {
+    let start = part.as_ptr() as usize - whole.as_ptr() as usize;
+    start..(start + part.len())
+}
+
 impl Board {
     fn from_board_fen(board_fen: &[u8]) -> Result<Board, ParseFenError> {
         let mut board = Board::empty();
@@ -283,52 +432,54 @@ impl Board {
         let mut rank = 7i8;
         let mut file = 0i8;
 
-        let mut iter = board_fen.iter().cloned().peekable();
+        let invalid = |idx: usize| ParseFenError::new(ParseFenErrorKind::InvalidBoard, idx..(idx + 1));
+
+        let mut iter = board_fen.iter().cloned().enumerate().peekable();
 
-        while let Some(ch) = iter.next() {
+        while let Some((idx, ch)) = iter.next() {
             if ch == b'/' && file == 8 {
                 file = 0;
                 rank -= 1;
-            } else if b'1' <= ch && ch <= b'8' {
+            } else if (b'1'..=b'8').contains(&ch) {
                 file += (ch - b'0') as i8;
                 if file > 8 {
-                    return Err(ParseFenError::InvalidBoard);
+                    return Err(invalid(idx));
                 }
             } else if let Some(piece) = Piece::from_char(char::from(ch)) {
                 match (File::try_from(file), Rank::try_from(rank)) {
                     (Ok(f), Ok(r)) => {
                         let sq = Square::from_coords(f, r);
-                        let promoted = iter.peek() == Some(&b'~');
+                        let promoted = iter.peek().map(|&(_, ch)| ch) == Some(b'~');
                         if promoted {
                             iter.next();
                         }
                         board.set_piece_at(sq, piece, promoted);
                     }
-                    _ => return Err(ParseFenError::InvalidBoard),
+                    _ => return Err(invalid(idx)),
                 }
                 file += 1;
             } else {
-                return Err(ParseFenError::InvalidBoard);
+                return Err(invalid(idx));
             }
         }
 
         if rank == 0 && file == 8 {
             Ok(board)
         } else {
-            Err(ParseFenError::InvalidBoard)
+            Err(ParseFenError::new(ParseFenErrorKind::InvalidBoard, 0..board_fen.len()))
         }
     }
 }
 
 impl RemainingChecks {
-    fn from_ascii(s: &[u8]) -> Option<RemainingChecks> {
+    pub(crate) fn from_ascii(s: &[u8]) -> Option<RemainingChecks> {
         let mut split = s.splitn(3, |ch| *ch == b'+');
         Some(match (split.next(), split.next(), split.next()) {
             (Some(b""), Some(white_given), Some(black_given)) => {
                 // format: +0+0
                 RemainingChecks {
                     white: 3u8.checked_sub(btoi::btou(white_given).ok()?)?,
-                    black: 3u8.checked_sub(btoi::btoi(black_given).ok()?)?,
+                    black: 3u8.checked_sub(btoi::btou(black_given).ok()?)?,
                 }
             }
             (Some(white), Some(black), None) => {
@@ -448,7 +599,7 @@ impl Fen {
             let split_point = board_part
                 .iter()
                 .position(|ch| *ch == b'[')
-                .ok_or(ParseFenError::InvalidBoard)?;
+                .ok_or_else(|| ParseFenError::new(ParseFenErrorKind::InvalidBoard, span_of(fen, board_part)))?;
             let pocket_part = &board_part[(split_point + 1)..(board_part.len() - 1)];
             (&board_part[..split_point], Some(pocket_part))
         } else if let Some(split_point) = board_part.iter().enumerate().filter_map(|(idx, ch)| Some(idx).filter(|_| *ch == b'/')).nth(7) {
@@ -461,19 +612,22 @@ impl Fen {
         result.board = Board::from_board_fen(board_part)?;
 
         if let Some(pocket_part) = pocket_part {
-            result.pockets = Some(Material::from_ascii_fen(pocket_part).map_err(|_| ParseFenError::InvalidPocket)?);
+            result.pockets = Some(Material::from_ascii_fen(pocket_part).map_err(|_| ParseFenError::new(ParseFenErrorKind::InvalidPocket, span_of(fen, pocket_part)))?);
         }
 
         result.turn = match parts.next() {
             Some(b"w") | None => White,
             Some(b"b") => Black,
-            Some(_) => return Err(ParseFenError::InvalidTurn),
+            Some(turn_part) => return Err(ParseFenError::new(ParseFenErrorKind::InvalidTurn, span_of(fen, turn_part))),
         };
 
         match parts.next() {
             Some(b"-") | None => (),
             Some(castling_part) => {
-                for &ch in castling_part {
+                let offset = span_of(fen, castling_part).start;
+                for (idx, &ch) in castling_part.iter().enumerate() {
+                    let invalid = || ParseFenError::new(ParseFenErrorKind::InvalidCastling, (offset + idx)..(offset + idx + 1));
+
                     let color = Color::from_white(ch < b'a'); // uppercase
 
                     let candidates = Bitboard::relative_rank(color, Rank::First) &
@@ -483,12 +637,12 @@ impl Fen {
                         b'k' => candidates.last(),
                         b'q' => candidates.first(),
                         file @ b'a'..=b'h' => {
-                            (candidates & File::new(u32::from(file as u8 - b'a'))).first()
+                            (candidates & File::new(u32::from(file - b'a'))).first()
                         }
-                        _ => return Err(ParseFenError::InvalidCastling),
+                        _ => return Err(invalid()),
                     };
 
-                    result.castling_rights.add(flag.ok_or(ParseFenError::InvalidCastling)?);
+                    result.castling_rights.add(flag.ok_or_else(invalid)?);
                 }
             }
         }
@@ -497,14 +651,20 @@ impl Fen {
             Some(b"-") | None => (),
             Some(ep_part) => {
                 result.ep_square =
-                    Some(Square::from_ascii(ep_part).map_err(|_| ParseFenError::InvalidEpSquare)?);
+                    Some(Square::from_ascii(ep_part).map_err(|_| ParseFenError::new(ParseFenErrorKind::InvalidEpSquare, span_of(fen, ep_part)))?);
             }
         }
 
+        let invalid_checks = |checks_part: &[u8]| {
+            ParseFenError::new(ParseFenErrorKind::InvalidRemainingChecks, span_of(fen, checks_part))
+        };
+
         let halfmoves_part = if let Some(checks_part) = parts.next() {
             if let Some(remaining_checks) = RemainingChecks::from_ascii(checks_part) {
                 result.remaining_checks = Some(remaining_checks);
                 parts.next()
+            } else if checks_part.contains(&b'+') {
+                return Err(invalid_checks(checks_part));
             } else {
                 Some(checks_part)
             }
@@ -514,11 +674,12 @@ impl Fen {
 
         if let Some(halfmoves_part) = halfmoves_part {
             result.halfmoves = btoi::btou_saturating(halfmoves_part)
-                .map_err(|_| ParseFenError::InvalidHalfmoveClock)?;
+                .map_err(|_| ParseFenError::new(ParseFenErrorKind::InvalidHalfmoveClock, span_of(fen, halfmoves_part)))?;
         }
 
         if let Some(fullmoves_part) = parts.next() {
-            let fullmoves = btoi::btou_saturating(fullmoves_part).map_err(|_| ParseFenError::InvalidFullmoves)?;
+            let fullmoves = btoi::btou_saturating(fullmoves_part)
+                .map_err(|_| ParseFenError::new(ParseFenErrorKind::InvalidFullmoves, span_of(fen, fullmoves_part)))?;
             result.fullmoves = NonZeroU32::new(max(fullmoves, 1)).unwrap();
         }
 
@@ -528,6 +689,8 @@ impl Fen {
             } else if let Some(remaining_checks) = RemainingChecks::from_ascii(checks_part) {
                 result.remaining_checks = Some(remaining_checks);
                 parts.next()
+            } else if checks_part.contains(&b'+') {
+                return Err(invalid_checks(checks_part));
             } else {
                 Some(checks_part)
             }
@@ -535,13 +698,30 @@ impl Fen {
             None
         };
 
-        if last_part.is_some() {
-            Err(ParseFenError::InvalidFen)
+        if let Some(last_part) = last_part {
+            let start = span_of(fen, last_part).start;
+            Err(ParseFenError::new(ParseFenErrorKind::InvalidFen, start..fen.len()))
         } else {
             Ok(result)
         }
     }
 
+    /// Mirrors the setup, so that it looks the same from the other side:
+    /// The board is flipped vertically, all colors are swapped, and it is
+    /// the other side to move.
+    pub fn mirror(&mut self) {
+        self.board.mirror();
+        if let Some(ref mut pockets) = self.pockets {
+            pockets.flip();
+        }
+        self.turn = !self.turn;
+        self.castling_rights = self.castling_rights.flip_vertical();
+        self.ep_square = self.ep_square.map(Square::flip_vertical);
+        if let Some(ref mut checks) = self.remaining_checks {
+            *checks = RemainingChecks { white: checks.black, black: checks.white };
+        }
+    }
+
     pub fn from_setup<S: Setup>(setup: &S) -> Fen {
         Fen {
             board: setup.board().clone(),
@@ -579,21 +759,167 @@ pub fn board_fen(board: &Board) -> String {
 /// Create an EPD such as
 /// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -` with default
 /// [`FenOpts`].
+///
+/// Promoted pieces are marked if the setup has pockets, so that Crazyhouse
+/// positions survive a round trip.
 pub fn epd(setup: &dyn Setup) -> String {
-    FenOpts::default().epd(setup)
+    FenOpts::default().promoted(setup.pockets().is_some()).epd(setup)
 }
 
 /// Create a FEN such as
 /// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1` with default
 /// [`FenOpts`].
+///
+/// Promoted pieces are marked if the setup has pockets, so that Crazyhouse
+/// positions survive a round trip.
 pub fn fen(setup: &dyn Setup) -> String {
-    FenOpts::default().fen(setup)
+    FenOpts::default().promoted(setup.pockets().is_some()).fen(setup)
+}
+
+/// Create a canonical EPD for deduplicating positions.
+///
+/// Move counters are dropped. Positions report only en passant squares with
+/// a legal capture and only castling rights that still have the king and
+/// rook on their original squares, so positions that differ only in
+/// irrelevant information will have the same key. Like in [`fen()`],
+/// promoted pieces are only marked if the position has pockets.
+///
+/// If `color_symmetric` is set, a position and its
+/// [mirrored](Fen::mirror()) counterpart with colors swapped also
+/// have the same key.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::{CastlingMode, Chess};
+/// use shakmaty::fen::{canonical_epd, Fen};
+///
+/// let pos: Chess = "4k3/8/8/8/3Pp3/8/8/4K3 b - d3 12 42"
+///     .parse::<Fen>()?
+///     .position(CastlingMode::Standard)?;
+/// assert_eq!(canonical_epd(&pos, false), "4k3/8/8/8/3Pp3/8/8/4K3 b - d3");
+///
+/// let mirrored: Chess = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1"
+///     .parse::<Fen>()?
+///     .position(CastlingMode::Standard)?;
+/// assert_eq!(canonical_epd(&pos, true), canonical_epd(&mirrored, true));
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+pub fn canonical_epd<P: Position>(pos: &P, color_symmetric: bool) -> String {
+    let mut opts = FenOpts::new();
+    opts.promoted(pos.pockets().is_some());
+
+    let mut fen = Fen::from_setup(pos);
+    let epd = opts.epd(&fen);
+
+    if color_symmetric {
+        fen.mirror();
+        let mirrored = opts.epd(&fen);
+        if mirrored < epd {
+            return mirrored;
+        }
+    }
+
+    epd
+}
+
+/// Error when parsing a FEN and setting up a position from it.
+#[cfg(feature = "rayon")]
+pub enum FenPositionError<P> {
+    /// The FEN could not be parsed.
+    Parse(ParseFenError),
+    /// The FEN was parsed, but does not describe a legal position.
+    Position(PositionError<P>),
+}
+
+#[cfg(feature = "rayon")]
+impl<P> From<ParseFenError> for FenPositionError<P> {
+    fn from(err: ParseFenError) -> FenPositionError<P> {
+        FenPositionError::Parse(err)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<P> From<PositionError<P>> for FenPositionError<P> {
+    fn from(err: PositionError<P>) -> FenPositionError<P> {
+        FenPositionError::Position(err)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<P> fmt::Debug for FenPositionError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FenPositionError::Parse(ref err) => f.debug_tuple("Parse").field(err).finish(),
+            FenPositionError::Position(ref err) => f.debug_tuple("Position").field(err).finish(),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<P> fmt::Display for FenPositionError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FenPositionError::Parse(ref err) => err.fmt(f),
+            FenPositionError::Position(ref err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<P> Error for FenPositionError<P> {
+    fn description(&self) -> &str {
+        match *self {
+            FenPositionError::Parse(_) => "invalid fen",
+            FenPositionError::Position(_) => "illegal position",
+        }
+    }
+}
+
+/// Parses and validates a batch of FENs in parallel, using the global
+/// rayon thread pool.
+///
+/// The results are in the same order as the input.
+///
+/// # Example
+///
+/// ```
+/// use shakmaty::{CastlingMode, Chess};
+/// use shakmaty::fen::{par_parse_positions, FenPositionError};
+///
+/// let fens = [
+///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+///     "8/8/8/8/8/8/8/8 w - - 0 1",
+///     "not a fen",
+/// ];
+///
+/// let results = par_parse_positions::<Chess, _>(&fens, CastlingMode::Standard);
+/// assert!(results[0].is_ok());
+/// assert!(matches!(results[1], Err(FenPositionError::Position(_))));
+/// assert!(matches!(results[2], Err(FenPositionError::Parse(_))));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_parse_positions<P, S>(fens: &[S], mode: CastlingMode) -> Vec<Result<P, FenPositionError<P>>>
+where
+    P: FromSetup + Send,
+    S: AsRef<str> + Sync,
+{
+    use rayon::prelude::*;
+
+    fens.par_iter().map(|fen| {
+        let fen: Fen = fen.as_ref().parse()?;
+        Ok(fen.position(mode)?)
+    }).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::position::Chess;
+    use crate::types::Move;
 
     #[test]
     fn test_legal_ep_square() {
@@ -606,6 +932,54 @@ mod tests {
         assert_eq!(epd(&pos), "4k3/8/8/8/3Pp3/8/8/3KR3 b - -");
     }
 
+    #[test]
+    fn test_ep_mode() {
+        // The black pawn on e4 is pinned.
+        let fen: Fen = "4k3/8/8/8/3Pp3/8/8/3KR3 b - d3".parse().expect("valid fen");
+        let pos: Chess = fen.position(CastlingMode::Standard).expect("legal position");
+        assert_eq!(FenOpts::new().ep_mode(EnPassantMode::Legal).epd(&pos), "4k3/8/8/8/3Pp3/8/8/3KR3 b - -");
+        assert_eq!(FenOpts::new().ep_mode(EnPassantMode::PseudoLegal).epd(&pos), "4k3/8/8/8/3Pp3/8/8/3KR3 b - d3");
+        assert_eq!(FenOpts::new().ep_mode(EnPassantMode::Always).epd(&pos), "4k3/8/8/8/3Pp3/8/8/3KR3 b - d3");
+
+        // No black pawn next to the double pushed pawn.
+        let fen: Fen = "4k3/8/8/8/3P4/8/8/3KR3 b - d3".parse().expect("valid fen");
+        let pos: Chess = fen.position(CastlingMode::Standard).expect("legal position");
+        assert_eq!(FenOpts::new().ep_mode(EnPassantMode::PseudoLegal).epd(&pos), "4k3/8/8/8/3P4/8/8/3KR3 b - -");
+        assert_eq!(FenOpts::new().ep_mode(EnPassantMode::Always).epd(&pos), "4k3/8/8/8/3P4/8/8/3KR3 b - d3");
+    }
+
+    #[test]
+    fn test_canonical_epd() {
+        // Castling rights without rook and the en passant square without
+        // legal capture are dropped.
+        let fen: Fen = "4k3/8/8/8/3Pp3/8/8/3KR3 b KQ d3 7 9".parse().expect("valid fen");
+        let pos: Chess = fen.position(CastlingMode::Standard)
+            .or_else(PositionError::ignore_invalid_castling_rights)
+            .expect("legal position");
+        assert_eq!(canonical_epd(&pos, false), "4k3/8/8/8/3Pp3/8/8/3KR3 b - -");
+
+        let start = canonical_epd(&Chess::default(), true);
+        let mut fen = Fen::default();
+        fen.mirror();
+        let mirrored: Chess = fen.position(CastlingMode::Standard).expect("legal position");
+        assert_eq!(canonical_epd(&mirrored, true), start);
+        assert_ne!(canonical_epd(&mirrored, false), canonical_epd(&Chess::default(), false));
+
+        // Promoted pieces are irrelevant in standard chess.
+        let mut promoted: Chess = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("legal position");
+        promoted.play_unchecked(&Move::Normal { role: Role::Pawn, from: Square::A7, capture: None, to: Square::A8, promotion: Some(Role::Queen) });
+        assert!(promoted.board().promoted().any());
+        let twin: Chess = "Q3k3/8/8/8/8/8/8/4K3 b - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("legal position");
+        assert_eq!(canonical_epd(&promoted, false), canonical_epd(&twin, false));
+        assert_eq!(promoted.zobrist_hash(), twin.zobrist_hash());
+    }
+
     #[test]
     fn test_invalid_fen() {
         assert!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQQKBNR w cq - 0P1".parse::<Fen>().is_err());
@@ -615,6 +989,12 @@ mod tests {
     fn test_pockets() {
         let fen: Fen = "8/8/8/8/8/8/8/8[Q]".parse().expect("valid fen");
         assert_eq!(fen.pockets().map_or(0, |p| p.by_piece(White.queen())), 1);
+
+        let bracket: Fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[Qn] w KQkq - 0 1".parse().expect("valid fen");
+        let slash: Fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/Qn w KQkq - 0 1".parse().expect("valid fen");
+        assert_eq!(bracket, slash);
+        assert_eq!(FenOpts::default().fen(&slash), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[Qn] w KQkq - 0 1");
+        assert_eq!(FenOpts::default().scid(true).fen(&bracket), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/Qn w KQkq - 0 1");
     }
 
     #[test]
@@ -625,6 +1005,27 @@ mod tests {
         assert_eq!(FenOpts::default().scid(true).promoted(true).fen(&fen), input);
     }
 
+    #[test]
+    #[cfg(feature = "crazyhouse")]
+    fn test_crazyhouse_round_trip() {
+        use crate::variants::Crazyhouse;
+
+        for input in &["r2q1r1k/ppp2pQ~p/2np4/2b1p3/4P3/2NP1N2/PPP2PPP/R1B1K2R[NBpnb] b KQ - 0 12",
+                       "4k3/8/8/8/8/8/8/4K2R~[] w K - 0 1"] {
+            let pos: Crazyhouse = input.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+            assert_eq!(fen(&pos), *input);
+        }
+
+        let pos: Crazyhouse = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/Qn w KQkq - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        assert_eq!(fen(&pos), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[Qn] w KQkq - 0 1");
+    }
+
     #[test]
     fn test_lichess_pockets() {
         let input = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1";
@@ -649,6 +1050,14 @@ mod tests {
         assert_eq!(fen.fullmoves.get(), 42);
     }
 
+    #[test]
+    fn test_exclude_remaining_checks() {
+        let fen: Fen = "8/8/8/8/8/8/8/8 w - - 1+2 12 42".parse().expect("valid fen");
+        assert_eq!(FenOpts::new().remaining_checks(false).fen(&fen), "8/8/8/8/8/8/8/8 w - - 12 42");
+        assert_eq!(FenOpts::new().remaining_checks(false).scid(true).fen(&fen), "8/8/8/8/8/8/8/8 w - - 12 42");
+        assert_eq!(FenOpts::new().scid(true).fen(&fen), "8/8/8/8/8/8/8/8 w - - 12 42 +2+1");
+    }
+
     #[test]
     fn test_lichess_remaining_checks() {
         let input = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 1 2 +0+0";
@@ -658,6 +1067,41 @@ mod tests {
         assert_eq!(fen.halfmoves, 1);
         assert_eq!(fen.fullmoves.get(), 2);
         assert_eq!(FenOpts::default().scid(true).fen(&fen), input);
+
+        let stockfish: Fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 1+2 1 2".parse().expect("valid fen");
+        let lichess: Fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 1 2 +2+1".parse().expect("valid fen");
+        assert_eq!(stockfish, lichess);
+    }
+
+    #[test]
+    fn test_error_span() {
+        let error = "8/8/8/8/8/8/8/8 x - - 0 1".parse::<Fen>().expect_err("invalid turn");
+        assert_eq!(error.kind(), ParseFenErrorKind::InvalidTurn);
+        assert_eq!(error.span(), 16..17);
+
+        let error = "8/8/8/8/8/8/8/8 w - e9 0 1".parse::<Fen>().expect_err("invalid ep square");
+        assert_eq!(error.kind(), ParseFenErrorKind::InvalidEpSquare);
+        assert_eq!(error.span(), 20..22);
+
+        let error = "8/8/8/4X3/8/8/8/8 w - - 0 1".parse::<Fen>().expect_err("invalid piece");
+        assert_eq!(error.kind(), ParseFenErrorKind::InvalidBoard);
+        assert_eq!(error.span(), 7..8);
+
+        let error = "4k3/8/8/8/8/8/8/4K3 w Kq - 0 1".parse::<Fen>().expect_err("castling without rook");
+        assert_eq!(error.kind(), ParseFenErrorKind::InvalidCastling);
+        assert_eq!(error.span(), 22..23);
+
+        let error = "8/8/8/8/8/8/8/8 w - - x 1".parse::<Fen>().expect_err("invalid halfmove clock");
+        assert_eq!(error.kind(), ParseFenErrorKind::InvalidHalfmoveClock);
+        assert_eq!(error.span(), 22..23);
+
+        let error = "8/8/8/8/8/8/8/8 w - - 0 -1".parse::<Fen>().expect_err("invalid fullmoves");
+        assert_eq!(error.kind(), ParseFenErrorKind::InvalidFullmoves);
+        assert_eq!(error.span(), 24..26);
+
+        let error = "8/8/8/8/8/8/8/8 w - - 0 1 foo".parse::<Fen>().expect_err("trailing garbage");
+        assert_eq!(error.kind(), ParseFenErrorKind::InvalidFen);
+        assert_eq!(error.span(), 26..29);
     }
 
     #[test]
@@ -665,6 +1109,7 @@ mod tests {
         // mind the dot in the castling part
         let input = "8/8/8/8/8/8/8/8 w · - 0 1";
         let error = input.parse::<Fen>().expect_err("invalid fen");
-        assert_eq!(error, ParseFenError::InvalidCastling);
+        assert_eq!(error.kind(), ParseFenErrorKind::InvalidCastling);
+        assert_eq!(error.span(), 18..19);
     }
 }
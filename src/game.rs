@@ -0,0 +1,526 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Move history of a game, and detection of repetitions.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! #
+//! use shakmaty::{Chess, Position};
+//! use shakmaty::game::Game;
+//! use shakmaty::san::San;
+//!
+//! let mut game = Game::new(Chess::default());
+//! for san in &["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"] {
+//!     let m = san.parse::<San>()?.to_move(game.position())?;
+//!     game.push(&m)?;
+//! }
+//! assert!(game.position().is_checkmate());
+//!
+//! // Take back the last two moves and play something else.
+//! game.undo();
+//! game.undo();
+//! assert_eq!(game.ply(), 5);
+//! let m = "g6".parse::<San>()?.to_move(game.position())?;
+//! game.push(&m)?;
+//! assert_eq!(game.len(), 6);
+//! #
+//! # Ok::<_, Box<dyn Error>>(())
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use crate::position::{Outcome, Position};
+use crate::types::Move;
+
+/// Error when trying to push an illegal move.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IllegalMoveError;
+
+impl fmt::Display for IllegalMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "illegal move".fmt(f)
+    }
+}
+
+impl Error for IllegalMoveError {
+    fn description(&self) -> &str {
+        "illegal move"
+    }
+}
+
+/// A starting position and a list of moves, with the position after each
+/// move.
+///
+/// A cursor points to the current position. Undoing moves only moves the
+/// cursor back, so that they can be redone, until a different move is
+/// pushed.
+#[derive(Clone, Debug)]
+pub struct Game<P> {
+    positions: Vec<P>,
+    moves: Vec<Move>,
+    ply: usize,
+}
+
+impl<P: Position + Clone> Game<P> {
+    /// Starts a game from `start`.
+    pub fn new(start: P) -> Game<P> {
+        Game {
+            positions: vec![start],
+            moves: Vec::new(),
+            ply: 0,
+        }
+    }
+
+    /// The starting position.
+    pub fn start(&self) -> &P {
+        &self.positions[0]
+    }
+
+    /// The current position.
+    pub fn position(&self) -> &P {
+        &self.positions[self.ply]
+    }
+
+    /// The position after `ply` moves, or `None` if there are not that many
+    /// moves.
+    pub fn position_at(&self, ply: usize) -> Option<&P> {
+        self.positions.get(ply)
+    }
+
+    /// The number of moves played to reach the current position.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// The number of moves, including moves that can be redone.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// All moves, including moves that can be redone.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The moves that lead to the current position.
+    pub fn moves_played(&self) -> &[Move] {
+        &self.moves[..self.ply]
+    }
+
+    /// Validates and plays a move in the current position. Moves that could
+    /// have been redone are discarded, unless `m` is the next of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IllegalMoveError`] if the move is not legal.
+    pub fn push(&mut self, m: &Move) -> Result<(), IllegalMoveError> {
+        if self.position().is_legal(m) {
+            self.push_unchecked(m);
+            Ok(())
+        } else {
+            Err(IllegalMoveError)
+        }
+    }
+
+    /// Plays a move in the current position, like [`Game::push()`].
+    ///
+    /// It is the callers responsibility to ensure the move is legal.
+    ///
+    /// # Panics
+    ///
+    /// Illegal moves can corrupt the state of the position and may
+    /// (or may not) panic or cause panics on future calls.
+    pub fn push_unchecked(&mut self, m: &Move) {
+        if self.moves.get(self.ply) == Some(m) {
+            self.ply += 1;
+            return;
+        }
+
+        self.moves.truncate(self.ply);
+        self.positions.truncate(self.ply + 1);
+
+        let mut pos = self.position().clone();
+        pos.play_unchecked(m);
+        self.moves.push(m.clone());
+        self.positions.push(pos);
+        self.ply += 1;
+    }
+
+    /// Removes the move that lead to the current position, discarding all
+    /// moves that could have been redone.
+    pub fn pop(&mut self) -> Option<Move> {
+        if self.ply == 0 {
+            return None;
+        }
+        self.moves.truncate(self.ply);
+        self.positions.truncate(self.ply);
+        self.ply -= 1;
+        self.moves.pop()
+    }
+
+    /// Goes back one move. Returns `false` if already at the starting
+    /// position.
+    pub fn undo(&mut self) -> bool {
+        self.seek(self.ply.wrapping_sub(1))
+    }
+
+    /// Goes forward one move. Returns `false` if there is no move to redo.
+    pub fn redo(&mut self) -> bool {
+        self.seek(self.ply + 1)
+    }
+
+    /// Goes to the position after `ply` moves. Returns `false` if there are
+    /// not that many moves.
+    pub fn seek(&mut self, ply: usize) -> bool {
+        if ply < self.positions.len() {
+            self.ply = ply;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<P: Position + Clone> Game<P> {
+    /// How often the current position occurred up to the current ply,
+    /// including the current occurrence.
+    pub fn repetition_count(&self) -> usize {
+        let current = self.position().zobrist_hash();
+        self.positions[..=self.ply].iter().filter(|pos| pos.zobrist_hash() == current).count()
+    }
+
+    /// Whether the game is drawn or a draw can be claimed in the current
+    /// position. See [`DrawState`].
+    pub fn draw_state(&self) -> DrawState {
+        DrawState::new(self.position(), self.repetition_count())
+    }
+}
+
+impl<P: Position + Clone + Default> Default for Game<P> {
+    fn default() -> Game<P> {
+        Game::new(P::default())
+    }
+}
+
+/// Reason for a draw.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DrawReason {
+    Stalemate,
+    /// Neither side can possibly win.
+    InsufficientMaterial,
+    /// Drawn by a special variant end condition.
+    VariantEnd,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    FiftyMoves,
+    SeventyFiveMoves,
+}
+
+/// Distinguishes draws that can be claimed from draws that end the game
+/// automatically.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::Chess;
+/// use shakmaty::game::{DrawReason, DrawState, Game};
+/// use shakmaty::san::San;
+///
+/// let mut game = Game::new(Chess::default());
+/// for san in &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+///     let m = san.parse::<San>()?.to_move(game.position())?;
+///     game.push(&m)?;
+/// }
+/// assert_eq!(game.draw_state(), DrawState::Claimable(DrawReason::ThreefoldRepetition));
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DrawState {
+    /// The game is not drawn, but may be over for other reasons, e.g.
+    /// checkmate.
+    None,
+    /// A draw can be claimed, by threefold repetition or the fifty-move
+    /// rule.
+    Claimable(DrawReason),
+    /// The game is drawn, by stalemate, insufficient material, fivefold
+    /// repetition, the seventy-five-move rule or a variant end condition.
+    Automatic(DrawReason),
+}
+
+impl DrawState {
+    /// Determines the draw state of `pos`, given how often it occurred,
+    /// including the current occurrence.
+    ///
+    /// Automatic draws take precedence over claimable draws, and checkmate
+    /// takes precedence over all draws.
+    pub fn new<P: Position + ?Sized>(pos: &P, repetitions: usize) -> DrawState {
+        if let Some(outcome) = pos.variant_outcome() {
+            return match outcome {
+                Outcome::Draw => DrawState::Automatic(DrawReason::VariantEnd),
+                Outcome::Decisive { .. } => DrawState::None,
+            };
+        }
+
+        if pos.is_checkmate() {
+            DrawState::None
+        } else if pos.is_stalemate() {
+            DrawState::Automatic(DrawReason::Stalemate)
+        } else if pos.is_insufficient_material() {
+            DrawState::Automatic(DrawReason::InsufficientMaterial)
+        } else if repetitions >= 5 {
+            DrawState::Automatic(DrawReason::FivefoldRepetition)
+        } else if pos.is_seventyfive_moves() {
+            DrawState::Automatic(DrawReason::SeventyFiveMoves)
+        } else if repetitions >= 3 {
+            DrawState::Claimable(DrawReason::ThreefoldRepetition)
+        } else if pos.is_fifty_moves() {
+            DrawState::Claimable(DrawReason::FiftyMoves)
+        } else {
+            DrawState::None
+        }
+    }
+
+    /// The reason for the draw, if any.
+    pub fn reason(self) -> Option<DrawReason> {
+        match self {
+            DrawState::None => None,
+            DrawState::Claimable(reason) | DrawState::Automatic(reason) => Some(reason),
+        }
+    }
+
+    pub fn is_claimable(self) -> bool {
+        matches!(self, DrawState::Claimable(_))
+    }
+
+    pub fn is_automatic(self) -> bool {
+        matches!(self, DrawState::Automatic(_))
+    }
+}
+
+/// Records [Zobrist hashes](crate::zobrist) of the positions of a game, to
+/// detect repetitions.
+///
+/// Positions before an [irreversible](Position::is_irreversible()) move can
+/// never repeat, so they are forgotten.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::{Chess, Position};
+/// use shakmaty::game::Repetitions;
+/// use shakmaty::san::San;
+///
+/// let mut pos = Chess::default();
+/// let mut repetitions = Repetitions::new(&pos);
+/// for san in &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+///     let m = san.parse::<San>()?.to_move(&pos)?;
+///     repetitions.play(&mut pos, &m);
+/// }
+/// assert_eq!(repetitions.count(), 3);
+/// assert!(repetitions.is_threefold());
+/// assert!(!repetitions.is_fivefold());
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Repetitions {
+    hashes: Vec<u64>,
+}
+
+impl Repetitions {
+    /// Starts recording at `pos`.
+    pub fn new<P: Position + ?Sized>(pos: &P) -> Repetitions {
+        Repetitions {
+            hashes: vec![pos.zobrist_hash()],
+        }
+    }
+
+    /// Plays a move in `pos` and records the resulting position.
+    ///
+    /// It is the callers responsibility to ensure the move is legal.
+    pub fn play<P: Position>(&mut self, pos: &mut P, m: &Move) {
+        let irreversible = pos.is_irreversible(m);
+        pos.play_unchecked(m);
+        self.push(pos.zobrist_hash(), irreversible);
+    }
+
+    /// Records the hash of a position, reached by an irreversible move if
+    /// `irreversible` is set. Useful to maintain hashes incrementally.
+    pub fn push(&mut self, hash: u64, irreversible: bool) {
+        if irreversible {
+            self.hashes.clear();
+        }
+        self.hashes.push(hash);
+    }
+
+    /// Forgets all recorded positions.
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+    }
+
+    /// How often the current position occurred, including the current
+    /// occurrence. `0` if nothing has been recorded.
+    pub fn count(&self) -> usize {
+        match self.hashes.last() {
+            Some(&current) => self.hashes.iter().filter(|&&hash| hash == current).count(),
+            None => 0,
+        }
+    }
+
+    /// Tests if the current position occurred at least three times, so that
+    /// a draw can be claimed.
+    pub fn is_threefold(&self) -> bool {
+        self.count() >= 3
+    }
+
+    /// Tests if the current position occurred at least five times, which
+    /// ends the game in a draw.
+    pub fn is_fivefold(&self) -> bool {
+        self.count() >= 5
+    }
+
+    /// Whether the game is drawn or a draw can be claimed in `pos`, which
+    /// must be the last recorded position. See [`DrawState`].
+    pub fn draw_state<P: Position + ?Sized>(&self, pos: &P) -> DrawState {
+        DrawState::new(pos, self.count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::{epd, Fen};
+    use crate::position::Chess;
+    use crate::types::CastlingMode;
+    use crate::uci::Uci;
+
+    fn uci(game: &Game<Chess>, uci: &str) -> Move {
+        uci.parse::<Uci>().expect("valid uci").to_move(game.position()).expect("legal uci")
+    }
+
+    #[test]
+    fn test_history() {
+        let mut game: Game<Chess> = Game::default();
+        assert!(!game.undo());
+        assert_eq!(game.pop(), None);
+
+        for m in &["e2e4", "e7e5", "g1f3"] {
+            let m = uci(&game, m);
+            game.push(&m).expect("legal move");
+        }
+        assert_eq!(game.ply(), 3);
+
+        assert!(game.undo());
+        assert!(game.undo());
+        assert_eq!(epd(game.position()), epd(game.position_at(1).expect("position after e4")));
+        assert_eq!(game.moves_played().len(), 1);
+
+        // Pushing the next move keeps the history.
+        let e5 = uci(&game, "e7e5");
+        game.push(&e5).expect("legal move");
+        assert_eq!(game.len(), 3);
+        assert!(game.redo());
+        assert!(!game.redo());
+
+        // Pushing a different move discards it.
+        assert!(game.seek(1));
+        let c5 = uci(&game, "c7c5");
+        game.push(&c5).expect("legal move");
+        assert_eq!(game.len(), 2);
+        assert_eq!(game.moves()[1], c5);
+
+        assert_eq!(game.push(&c5), Err(IllegalMoveError));
+
+        assert!(game.undo());
+        assert_eq!(game.pop().map(|m| m.to_string()), Some("e2-e4".to_owned()));
+        assert!(game.is_empty());
+        assert_eq!(epd(game.position()), epd(game.start()));
+    }
+
+    #[test]
+    fn test_repetitions() {
+        let mut pos = Chess::default();
+        let mut repetitions = Repetitions::new(&pos);
+        assert_eq!(repetitions.count(), 1);
+
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for _ in 0..4 {
+            for m in &shuffle {
+                let m = m.parse::<Uci>().expect("valid uci").to_move(&pos).expect("legal uci");
+                repetitions.play(&mut pos, &m);
+            }
+        }
+        assert_eq!(repetitions.count(), 5);
+        assert!(repetitions.is_fivefold());
+
+        // Pawn moves reset.
+        let m = "e2e4".parse::<Uci>().expect("valid uci").to_move(&pos).expect("legal uci");
+        repetitions.play(&mut pos, &m);
+        assert_eq!(repetitions.count(), 1);
+
+        repetitions.clear();
+        assert_eq!(repetitions.count(), 0);
+        assert!(!repetitions.is_threefold());
+    }
+
+    #[test]
+    fn test_draw_state() {
+        let mut game: Game<Chess> = Game::default();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for round in 1..=4 {
+            for m in &shuffle {
+                let m = uci(&game, m);
+                game.push(&m).expect("legal move");
+            }
+            assert_eq!(game.repetition_count(), round + 1);
+        }
+        assert_eq!(game.draw_state(), DrawState::Automatic(DrawReason::FivefoldRepetition));
+
+        game.seek(8);
+        assert_eq!(game.draw_state(), DrawState::Claimable(DrawReason::ThreefoldRepetition));
+        assert!(game.draw_state().is_claimable());
+
+        game.seek(7);
+        assert_eq!(game.draw_state(), DrawState::None);
+
+        for &(fen, state) in &[
+            ("8/5k2/8/8/8/8/3KR3/8 w - - 100 80", DrawState::Claimable(DrawReason::FiftyMoves)),
+            ("8/5k2/8/8/8/8/3KR3/8 w - - 150 80", DrawState::Automatic(DrawReason::SeventyFiveMoves)),
+            ("8/5k2/8/8/8/8/3KN3/8 w - - 100 80", DrawState::Automatic(DrawReason::InsufficientMaterial)),
+            ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", DrawState::Automatic(DrawReason::Stalemate)),
+            ("4R2k/6pp/8/8/8/8/8/6K1 b - - 150 99", DrawState::None),
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+            assert_eq!(DrawState::new(&pos, 1), state);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Read and write moves in ICCF numeric notation, as used in correspondence
+//! chess.
+//!
+//! Files and ranks are both numbered from `1` to `8`, so that `5254` is the
+//! move from e2 to e4. Promotions are given by a fifth digit: `1` for a
+//! queen, `2` for a rook, `3` for a bishop and `4` for a knight. Castling is
+//! written as the king move, e.g. `5171`.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! #
+//! use shakmaty::{Chess, Position, Square};
+//! use shakmaty::iccf::IccfMove;
+//!
+//! let iccf: IccfMove = "5254".parse()?;
+//! assert_eq!(iccf.from, Square::E2);
+//! assert_eq!(iccf.to, Square::E4);
+//!
+//! let pos = Chess::default();
+//! let m = iccf.to_move(&pos)?;
+//! assert_eq!(IccfMove::from_move(&m), Some(iccf));
+//! #
+//! # Ok::<_, Box<dyn Error>>(())
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::square::{File, Rank, Square};
+use crate::types::{CastlingSide, Move, Role};
+use crate::position::Position;
+use crate::movelist::MoveList;
+
+/// Error when parsing an invalid ICCF move.
+#[derive(Clone, Debug)]
+pub struct ParseIccfError;
+
+impl fmt::Display for ParseIccfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid iccf".fmt(f)
+    }
+}
+
+impl Error for ParseIccfError {
+    fn description(&self) -> &str {
+        "invalid iccf"
+    }
+}
+
+/// Error when an ICCF move is illegal.
+#[derive(Clone, Debug)]
+pub struct IllegalIccfError;
+
+impl fmt::Display for IllegalIccfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "illegal iccf".fmt(f)
+    }
+}
+
+impl Error for IllegalIccfError {
+    fn description(&self) -> &str {
+        "illegal iccf"
+    }
+}
+
+/// A move in ICCF numeric notation, e.g. `5254` or `17181`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct IccfMove {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<Role>,
+}
+
+fn square_from_digits(file: u8, rank: u8) -> Option<Square> {
+    if (b'1'..=b'8').contains(&file) {
+        Rank::from_char(char::from(rank)).map(|rank| Square::from_coords(File::new(u32::from(file - b'1')), rank))
+    } else {
+        None
+    }
+}
+
+impl IccfMove {
+    /// Parses a move in ICCF numeric notation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseIccfError`] if `iccf` is not syntactically valid.
+    pub fn from_ascii(iccf: &[u8]) -> Result<IccfMove, ParseIccfError> {
+        let (from, to, promotion) = match *iccf {
+            [f1, r1, f2, r2] => (square_from_digits(f1, r1), square_from_digits(f2, r2), None),
+            [f1, r1, f2, r2, promotion] => (square_from_digits(f1, r1), square_from_digits(f2, r2), Some(match promotion {
+                b'1' => Role::Queen,
+                b'2' => Role::Rook,
+                b'3' => Role::Bishop,
+                b'4' => Role::Knight,
+                _ => return Err(ParseIccfError),
+            })),
+            _ => return Err(ParseIccfError),
+        };
+
+        Ok(IccfMove {
+            from: from.ok_or(ParseIccfError)?,
+            to: to.ok_or(ParseIccfError)?,
+            promotion,
+        })
+    }
+
+    /// Converts a move to ICCF numeric notation, or `None` for piece drops
+    /// and king promotions, which can not be represented.
+    pub fn from_move(m: &Move) -> Option<IccfMove> {
+        match *m {
+            Move::Normal { promotion: Some(Role::King), .. } | Move::Put { .. } => None,
+            Move::Normal { from, to, promotion, .. } => Some(IccfMove { from, to, promotion }),
+            Move::EnPassant { from, to } => Some(IccfMove { from, to, promotion: None }),
+            Move::Castle { king, rook } => {
+                let side = CastlingSide::from_king_side(king < rook);
+                Some(IccfMove {
+                    from: king,
+                    to: Square::from_coords(side.king_to_file(), king.rank()),
+                    promotion: None,
+                })
+            }
+        }
+    }
+
+    /// Tries to convert the `IccfMove` to a legal move in the context of a
+    /// position.
+    ///
+    /// In Chess960, castling can have the same king destination as an
+    /// ordinary king move. ICCF notation can not tell them apart, so
+    /// castling is always preferred in that case.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IllegalIccfError`] if the move is not legal.
+    pub fn to_move<P: Position>(&self, pos: &P) -> Result<Move, IllegalIccfError> {
+        let mut legals = MoveList::new();
+        pos.legal_moves(&mut legals);
+        legals.iter()
+            .filter(|m| IccfMove::from_move(m).as_ref() == Some(self))
+            .max_by_key(|m| m.is_castle())
+            .cloned()
+            .ok_or(IllegalIccfError)
+    }
+}
+
+impl FromStr for IccfMove {
+    type Err = ParseIccfError;
+
+    fn from_str(iccf: &str) -> Result<IccfMove, ParseIccfError> {
+        IccfMove::from_ascii(iccf.as_bytes())
+    }
+}
+
+impl fmt::Display for IccfMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for sq in &[self.from, self.to] {
+            write!(f, "{}{}", u32::from(sq.file()) + 1, sq.rank().char())?;
+        }
+        match self.promotion {
+            Some(Role::Queen) => f.write_str("1"),
+            Some(Role::Rook) => f.write_str("2"),
+            Some(Role::Bishop) => f.write_str("3"),
+            Some(Role::Knight) => f.write_str("4"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::position::Chess;
+    use crate::types::CastlingMode;
+
+    #[test]
+    fn test_read_write() {
+        for iccf in &["5254", "7163", "17181", "88874"] {
+            assert_eq!(iccf.parse::<IccfMove>().expect("valid iccf").to_string(), *iccf);
+        }
+        for iccf in &["", "525", "5294", "0254", "52545", "525400"] {
+            assert!(iccf.parse::<IccfMove>().is_err(), "{}", iccf);
+        }
+    }
+
+    #[test]
+    fn test_to_move() {
+        let pos: Chess = "4k3/1P6/8/8/8/8/8/4K2R w K - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+
+        let castle = "5171".parse::<IccfMove>().expect("valid iccf").to_move(&pos).expect("legal");
+        assert_eq!(castle, Move::Castle { king: Square::E1, rook: Square::H1 });
+
+        let promotion = "27284".parse::<IccfMove>().expect("valid iccf").to_move(&pos).expect("legal");
+        assert_eq!(promotion.promotion(), Some(Role::Knight));
+
+        assert!("5161".parse::<IccfMove>().expect("valid iccf").to_move(&pos).is_ok());
+        assert!("5153".parse::<IccfMove>().expect("valid iccf").to_move(&pos).is_err());
+    }
+
+    #[test]
+    fn test_to_move_chess960() {
+        // Both castling and the king move from d1 to c1 are written as 4131.
+        let pos: Chess = "4k3/8/8/8/8/8/8/R2K4 w A - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Chess960)
+            .expect("valid position");
+
+        let castle = "4131".parse::<IccfMove>().expect("valid iccf").to_move(&pos).expect("legal");
+        assert_eq!(castle, Move::Castle { king: Square::D1, rook: Square::A1 });
+    }
+}
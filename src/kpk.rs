@@ -0,0 +1,89 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Bitbase for king and pawn versus king endgames.
+//!
+//! The bitbase is generated at build time by retrograde analysis and takes
+//! 24 KiB.
+//!
+//! # Examples
+//!
+//! ```
+//! use shakmaty::{Color, Square};
+//! use shakmaty::kpk;
+//!
+//! // The king escorts the pawn.
+//! assert!(kpk::probe(Square::E6, Square::E5, Square::E8, Color::White));
+//!
+//! // The defending king holds the opposition.
+//! assert!(!kpk::probe(Square::E4, Square::E3, Square::E6, Color::White));
+//! ```
+
+use crate::square::{File, Rank, Square};
+use crate::types::Color;
+
+include!(concat!(env!("OUT_DIR"), "/kpk.rs")); // generated by build.rs
+
+/// Tests if white wins the position with a white king, a white pawn and a
+/// black king, with best play. Otherwise the position is a draw.
+///
+/// Positions with a black pawn can be probed by flipping the board
+/// vertically and swapping the colors.
+///
+/// The result for illegal positions, for example with the kings next to
+/// each other, is unspecified.
+///
+/// # Panics
+///
+/// Panics if the pawn is on the first or last rank.
+pub fn probe(white_king: Square, pawn: Square, black_king: Square, turn: Color) -> bool {
+    assert!(pawn.rank() != Rank::First && pawn.rank() != Rank::Eighth, "pawn on backrank");
+
+    let (white_king, pawn, black_king) = if pawn.file() > File::D {
+        (white_king.flip_horizontal(), pawn.flip_horizontal(), black_king.flip_horizontal())
+    } else {
+        (white_king, pawn, black_king)
+    };
+
+    let pawn_index = usize::from(pawn.file()) * 6 + usize::from(pawn.rank()) - 1;
+    let idx = ((pawn_index * 2 + turn.fold(0, 1)) * 64 + usize::from(black_king)) * 64 + usize::from(white_king);
+    KPK[idx / 64] & (1 << (idx % 64)) != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kpk() {
+        // Rook pawn with the defending king in the corner.
+        assert!(!probe(Square::B6, Square::H6, Square::H8, Color::White));
+        assert!(!probe(Square::G6, Square::A6, Square::A8, Color::Black));
+
+        // Pawn can not be caught.
+        assert!(probe(Square::A1, Square::D5, Square::H1, Color::White));
+        assert!(!probe(Square::A1, Square::D5, Square::E5, Color::Black));
+        assert!(probe(Square::A1, Square::D5, Square::H2, Color::Black));
+
+        // Key squares.
+        assert!(probe(Square::D6, Square::D4, Square::D8, Color::Black));
+        assert!(probe(Square::E6, Square::D4, Square::D8, Color::White));
+
+        // Mirrored files agree.
+        assert_eq!(probe(Square::E4, Square::C3, Square::C6, Color::White),
+                   probe(Square::D4, Square::F3, Square::F6, Color::White));
+    }
+}
@@ -0,0 +1,285 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Read and write Long Algebraic Notation.
+//!
+//! Unlike [SAN](super::san), LAN always includes the origin square, so
+//! that no disambiguation is required.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! #
+//! use shakmaty::{Chess, Move, Position, Role, Square};
+//! use shakmaty::lan::Lan;
+//!
+//! let lan: Lan = "Ng1-f3".parse()?;
+//! assert_eq!(lan.to_string(), "Ng1-f3");
+//!
+//! let pos = Chess::default();
+//! let m = lan.to_move(&pos)?;
+//! assert_eq!(m, Move::Normal {
+//!     role: Role::Knight,
+//!     from: Square::G1,
+//!     capture: None,
+//!     to: Square::F3,
+//!     promotion: None,
+//! });
+//!
+//! assert_eq!(Lan::from_move(&m), lan);
+//! #
+//! # Ok::<_, Box<dyn Error>>(())
+//! ```
+
+use std::cmp::min;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::square::Square;
+use crate::types::{CastlingSide, Move, Role};
+use crate::position::Position;
+use crate::movelist::MoveList;
+
+/// Error when parsing a syntactically invalid LAN.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseLanError {
+    span: Range<usize>,
+}
+
+impl ParseLanError {
+    fn new(span: Range<usize>) -> ParseLanError {
+        ParseLanError { span }
+    }
+
+    /// Byte range of the offending token in the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl fmt::Display for ParseLanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid lan".fmt(f)
+    }
+}
+
+impl Error for ParseLanError {
+    fn description(&self) -> &str {
+        "invalid lan"
+    }
+}
+
+/// Error when LAN is illegal.
+#[derive(Clone, Debug)]
+pub struct IllegalLanError;
+
+impl fmt::Display for IllegalLanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "illegal lan".fmt(f)
+    }
+}
+
+impl Error for IllegalLanError {
+    fn description(&self) -> &str {
+        "illegal lan"
+    }
+}
+
+/// A move in Long Algebraic Notation, e.g. `Ng1-f3` or `e7xd8=Q`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Lan {
+    Normal {
+        role: Role,
+        from: Square,
+        capture: bool,
+        to: Square,
+        promotion: Option<Role>,
+    },
+    Castle(CastlingSide),
+    Put { role: Role, to: Square },
+    Null,
+}
+
+impl Lan {
+    /// Parses a LAN. Ignores a possible check or checkmate suffix.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseLanError`] if `lan` is not syntactically valid.
+    pub fn from_ascii(mut lan: &[u8]) -> Result<Lan, ParseLanError> {
+        if lan.ends_with(b"#") || lan.ends_with(b"+") {
+            lan = &lan[0..(lan.len() - 1)];
+        }
+
+        // Span of the byte at idx, or the empty span at the end of the input.
+        let at = |idx: usize| ParseLanError::new(idx..min(idx + 1, lan.len()));
+
+        let square_at = |idx: usize| {
+            lan.get(idx..idx + 2)
+                .and_then(|s| Square::from_ascii(s).ok())
+                .ok_or_else(|| ParseLanError::new(min(idx, lan.len())..min(idx + 2, lan.len())))
+        };
+
+        match lan {
+            b"--" => Ok(Lan::Null),
+            b"O-O" => Ok(Lan::Castle(CastlingSide::KingSide)),
+            b"O-O-O" => Ok(Lan::Castle(CastlingSide::QueenSide)),
+            [b'@', _, _] => Ok(Lan::Put { role: Role::Pawn, to: square_at(1)? }),
+            [role, b'@', _, _] => Ok(Lan::Put {
+                role: Role::from_char(char::from(*role)).ok_or_else(|| at(0))?,
+                to: square_at(2)?,
+            }),
+            _ => {
+                let (role, idx) = match lan.first() {
+                    Some(&ch) if ch.is_ascii_uppercase() =>
+                        (Role::from_char(char::from(ch)).ok_or_else(|| at(0))?, 1),
+                    Some(_) => (Role::Pawn, 0),
+                    None => return Err(at(0)),
+                };
+
+                let from = square_at(idx)?;
+
+                let capture = match lan.get(idx + 2) {
+                    Some(b'-') => false,
+                    Some(b'x') => true,
+                    _ => return Err(at(idx + 2)),
+                };
+
+                let to = square_at(idx + 3)?;
+
+                let promotion = match lan[idx + 5..] {
+                    [] => None,
+                    [b'=', role] => Some(Role::from_char(char::from(role)).ok_or_else(|| at(idx + 6))?),
+                    _ => return Err(ParseLanError::new(idx + 5..lan.len())),
+                };
+
+                Ok(Lan::Normal { role, from, capture, to, promotion })
+            }
+        }
+    }
+
+    /// Converts a move to Long Algebraic Notation.
+    pub fn from_move(m: &Move) -> Lan {
+        match *m {
+            Move::Normal { role, from, capture, to, promotion } =>
+                Lan::Normal { role, from, capture: capture.is_some(), to, promotion },
+            Move::EnPassant { from, to } =>
+                Lan::Normal { role: Role::Pawn, from, capture: true, to, promotion: None },
+            Move::Castle { king, rook } =>
+                Lan::Castle(CastlingSide::from_king_side(king < rook)),
+            Move::Put { role, to } =>
+                Lan::Put { role, to },
+        }
+    }
+
+    /// Tries to convert the `Lan` to a legal move in the context of a
+    /// position.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IllegalLanError`] if the move is not legal.
+    pub fn to_move<P: Position>(&self, pos: &P) -> Result<Move, IllegalLanError> {
+        let mut legals = MoveList::new();
+
+        match *self {
+            Lan::Normal { role, to, .. } | Lan::Put { role, to } =>
+                pos.san_candidates(role, to, &mut legals),
+            Lan::Castle(side) => pos.castling_moves(side, &mut legals),
+            Lan::Null => return Err(IllegalLanError),
+        }
+
+        legals.iter()
+            .find(|m| Lan::from_move(m) == *self)
+            .cloned()
+            .ok_or(IllegalLanError)
+    }
+}
+
+impl FromStr for Lan {
+    type Err = ParseLanError;
+
+    fn from_str(lan: &str) -> Result<Lan, ParseLanError> {
+        Lan::from_ascii(lan.as_bytes())
+    }
+}
+
+impl fmt::Display for Lan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Lan::Normal { role, from, capture, to, promotion } => {
+                if role != Role::Pawn {
+                    write!(f, "{}", role.upper_char())?;
+                }
+                write!(f, "{}{}{}", from, if capture { 'x' } else { '-' }, to)?;
+                if let Some(promotion) = promotion {
+                    write!(f, "={}", promotion.upper_char())?;
+                }
+                Ok(())
+            }
+            Lan::Castle(CastlingSide::KingSide) => write!(f, "O-O"),
+            Lan::Castle(CastlingSide::QueenSide) => write!(f, "O-O-O"),
+            Lan::Put { role: Role::Pawn, to } => write!(f, "@{}", to),
+            Lan::Put { role, to } => write!(f, "{}@{}", role.upper_char(), to),
+            Lan::Null => write!(f, "--"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::position::Chess;
+    use crate::types::CastlingMode;
+
+    #[test]
+    fn test_read_write() {
+        for lan in &["e2-e4", "Ng1-f3", "e7xd8=Q", "Ke1-e2", "O-O", "O-O-O", "@e4", "N@f3", "--"] {
+            let result = lan.parse::<Lan>().expect("valid lan").to_string();
+            assert_eq!(*lan, result, "read {} write {}", lan, result);
+        }
+        assert_eq!("e7xd8=Q+".parse::<Lan>().expect("valid lan").to_string(), "e7xd8=Q");
+    }
+
+    #[test]
+    fn test_error_span() {
+        assert_eq!("".parse::<Lan>().expect_err("empty").span(), 0..0);
+        assert_eq!("Xg1-f3".parse::<Lan>().expect_err("invalid role").span(), 0..1);
+        assert_eq!("Ng1f3".parse::<Lan>().expect_err("missing separator").span(), 3..4);
+        assert_eq!("e2-e9".parse::<Lan>().expect_err("invalid square").span(), 3..5);
+        assert_eq!("e7xd8Q".parse::<Lan>().expect_err("invalid promotion").span(), 5..6);
+    }
+
+    #[test]
+    fn test_to_move() {
+        let pos: Chess = "r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+
+        for lan in &["e5xd6", "b7xa8=N", "O-O-O", "Ra1xa8"] {
+            let m = lan.parse::<Lan>().expect("valid lan").to_move(&pos).expect("legal lan");
+            assert_eq!(Lan::from_move(&m).to_string(), *lan);
+        }
+
+        for lan in &["e5-d6", "b7-a8=Q", "Ra1-a8", "Nb1-c3", "--"] {
+            assert!(lan.parse::<Lan>().expect("valid lan").to_move(&pos).is_err(), "{}", lan);
+        }
+    }
+}
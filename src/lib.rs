@@ -61,8 +61,9 @@
 //! assert_eq!(pos.outcome(), None); // no winner yet
 //! ```
 //!
-//! Also supports [FEN](fen), [SAN](san) and
-//! [UCI](uci) formats for positions and moves.
+//! Also supports [FEN](fen), [SAN](san), [LAN](lan) and
+//! [UCI](uci) formats for positions and moves, and reading and writing
+//! games in [PGN](pgn).
 
 #![doc(html_root_url = "https://docs.rs/shakmaty/0.17.1")]
 
@@ -79,21 +80,34 @@ mod setup;
 mod movelist;
 mod magics;
 mod perft;
+mod mate;
 
 pub mod attacks;
 pub mod bitboard;
 pub mod fen;
+pub mod epd;
+pub mod game;
 pub mod uci;
 pub mod san;
+pub mod lan;
+pub mod iccf;
+pub mod kpk;
+pub mod pgn;
+pub mod packed;
+pub mod polyglot;
+pub mod retro;
+pub mod tablebase;
 pub mod variants;
+pub mod zobrist;
 
 pub use crate::errors::{TryFromIntError, TryFromFloatError};
 pub use crate::square::{ParseSquareError, File, Rank, Square};
-pub use crate::types::{CastlingSide, CastlingMode, Color, Move, Piece, RemainingChecks, Role};
-pub use crate::material::{Material, MaterialSide, ParseMaterialError};
+pub use crate::types::{CastlingSide, CastlingMode, Color, EnPassantMode, Move, Piece, PromotionMode, RemainingChecks, Role};
+pub use crate::material::{EndgameClass, EndgameFamily, Material, MaterialSide, ParseMaterialError};
 pub use crate::bitboard::Bitboard;
 pub use crate::board::{Board, Pieces};
 pub use crate::setup::{Castles, Setup};
-pub use crate::movelist::MoveList;
-pub use crate::position::{Chess, Outcome, Position, FromSetup, PlayError, PositionError, PositionErrorKinds};
-pub use crate::perft::perft;
+pub use crate::movelist::{MoveGen, MoveList};
+pub use crate::position::{Chess, Outcome, ParseOutcomeError, Position, FromSetup, PlayError, PositionError, PositionErrorKinds};
+pub use crate::perft::{perft, perft_with_progress};
+pub use crate::mate::find_mates_in_one;
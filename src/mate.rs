@@ -0,0 +1,68 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::position::Position;
+use crate::movelist::MoveList;
+
+/// Collects all legal moves that deliver checkmate.
+///
+/// Only moves that give check are tested any further, so this is
+/// considerably faster than testing [`Position::is_checkmate()`] after
+/// every legal move.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{CastlingMode, Chess, find_mates_in_one};
+/// use shakmaty::fen::Fen;
+/// use shakmaty::uci::Uci;
+///
+/// let pos: Chess = "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 4 4"
+///     .parse::<Fen>()?
+///     .position(CastlingMode::Standard)?;
+///
+/// let mates = find_mates_in_one(&pos);
+/// assert_eq!(mates.len(), 1);
+/// assert_eq!(mates[0].to_uci(CastlingMode::Standard), "f3f7".parse::<Uci>()?);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn find_mates_in_one<P: Position + Clone>(pos: &P) -> MoveList {
+    let mut moves = MoveList::new();
+    pos.legal_moves(&mut moves);
+    moves.retain(|m| pos.is_mate_in_one(m));
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Chess;
+    use crate::fen::Fen;
+    use crate::types::CastlingMode;
+
+    #[test]
+    fn test_find_mates_in_one() {
+        assert!(find_mates_in_one(&Chess::default()).is_empty());
+
+        let pos: Chess = "6k1/5ppp/8/8/8/8/5PPP/RR4K1 w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("legal position");
+        let mates = find_mates_in_one(&pos);
+        assert_eq!(mates.len(), 2);
+        assert!(mates.iter().all(|m| pos.is_mate_in_one(m)));
+    }
+}
@@ -22,6 +22,8 @@ use std::mem;
 use std::str::FromStr;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
+use crate::bitboard::Bitboard;
+use crate::board::Board;
 use crate::types::{Color, Piece, Role, ROLES};
 
 /// Error when parsing an invalid material key.
@@ -251,6 +253,24 @@ impl Sub for MaterialSide {
 }
 
 /// The material configuration of both sides.
+///
+/// Material keys like `KRPvKR` list the white pieces first. The
+/// [normalized](Material::normalize()) key lists the stronger side first,
+/// which is how tablebase files and endgame classes are named.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Board, Material};
+///
+/// let board: Board = "8/8/3k4/3r4/8/3K4/3P4/3R4".parse()?;
+/// let material = board.material();
+/// assert_eq!(material.to_string(), "KRPvKR");
+///
+/// let material: Material = "KvKBN".parse()?;
+/// assert_eq!(material.normalized().to_string(), "KBNvK");
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
 #[derive(Clone, Default, Eq, PartialEq, Hash)]
 pub struct Material {
     pub white: MaterialSide,
@@ -288,6 +308,12 @@ impl Material {
         self.white == self.black
     }
 
+    /// The side that comes first in the [normalized](Material::normalize())
+    /// material configuration. `White` if the material is symmetric.
+    pub fn stronger_side(&self) -> Color {
+        Color::from_white(self.white >= self.black)
+    }
+
     pub fn by_color(&self, color: Color) -> &MaterialSide {
         match color {
             Color::Black => &self.black,
@@ -465,3 +491,212 @@ impl Sub for Material {
         self
     }
 }
+
+macro_rules! endgame_classes {
+    ($($class:ident,)+) => {
+        /// A well-known endgame, identified by its normalized material
+        /// signature with the stronger side first.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use shakmaty::{Color, EndgameClass, Material};
+        ///
+        /// let material: Material = "KvKBN".parse()?;
+        /// assert_eq!(EndgameClass::from_material(&material), Some(EndgameClass::KBNvK));
+        /// assert_eq!(material.stronger_side(), Color::Black);
+        ///
+        /// let material: Material = "KQQvK".parse()?;
+        /// assert_eq!(EndgameClass::from_material(&material), None);
+        /// # Ok::<_, shakmaty::ParseMaterialError>(())
+        /// ```
+        #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+        pub enum EndgameClass {
+            $($class,)+
+        }
+
+        impl EndgameClass {
+            /// Classifies a material configuration, regardless of which side
+            /// is stronger.
+            pub fn from_material(material: &Material) -> Option<EndgameClass> {
+                match material.normalized().to_string().as_str() {
+                    $(stringify!($class) => Some(EndgameClass::$class),)+
+                    _ => None,
+                }
+            }
+
+            /// The material signature of the endgame, e.g. `KBNvK`.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(EndgameClass::$class => stringify!($class),)+
+                }
+            }
+        }
+    }
+}
+
+endgame_classes! {
+    KvK,
+    KNvK,
+    KBvK,
+    KPvK,
+    KNNvK,
+    KBNvK,
+    KBBvK,
+    KRvK,
+    KQvK,
+    KPvKP,
+    KNvKN,
+    KNvKP,
+    KBvKN,
+    KBvKB,
+    KBvKP,
+    KRvKP,
+    KRvKN,
+    KRvKB,
+    KRvKR,
+    KQvKP,
+    KQvKN,
+    KQvKB,
+    KQvKR,
+    KQvKQ,
+    KBPvKB,
+    KRPvKR,
+    KQPvKQ,
+}
+
+impl EndgameClass {
+    /// The normalized material configuration of the endgame.
+    pub fn material(self) -> Material {
+        self.name().parse().expect("valid material signature")
+    }
+}
+
+impl fmt::Display for EndgameClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A family of endgames, determined by the kinds of pieces left on the
+/// board besides kings and pawns.
+///
+/// Unlike [`EndgameClass`], which identifies exact material signatures,
+/// families group endings that are studied together, e.g. all rook endings
+/// regardless of the number of pawns.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Board, EndgameFamily};
+///
+/// let board: Board = "8/5k2/4b3/8/3P4/2B5/5K2/8".parse()?;
+/// assert_eq!(EndgameFamily::from_board(&board), Some(EndgameFamily::OppositeColoredBishops));
+///
+/// let board: Board = "8/5k2/1p3r2/8/3P4/8/1R3K2/8".parse()?;
+/// assert_eq!(EndgameFamily::from_board(&board), Some(EndgameFamily::Rook));
+///
+/// assert_eq!(EndgameFamily::from_board(&Board::default()), None);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum EndgameFamily {
+    /// Only kings and pawns.
+    Pawn,
+    /// Only knights.
+    Knight,
+    /// Only bishops, unless they are opposite-colored.
+    Bishop,
+    /// Exactly one bishop each, on squares of different colors.
+    OppositeColoredBishops,
+    /// Knights and bishops.
+    Minor,
+    /// Only rooks.
+    Rook,
+    /// Rooks and minor pieces.
+    RookAndMinor,
+    /// Only queens.
+    Queen,
+    /// Queens and other pieces.
+    Mixed,
+}
+
+impl EndgameFamily {
+    /// Classifies the pieces on `board`.
+    ///
+    /// Returns `None` if any side has more than three pieces besides king
+    /// and pawns, which is not considered an endgame.
+    pub fn from_board(board: &Board) -> Option<EndgameFamily> {
+        let material = board.material();
+        if material.white.count() - usize::from(material.white.kings) - usize::from(material.white.pawns) > 3 ||
+           material.black.count() - usize::from(material.black.kings) - usize::from(material.black.pawns) > 3 {
+            return None;
+        }
+
+        let pieces = board.occupied() & !board.kings() & !board.pawns();
+        let minors = board.knights() | board.bishops();
+
+        Some(if pieces.is_empty() {
+            EndgameFamily::Pawn
+        } else if pieces == board.knights() {
+            EndgameFamily::Knight
+        } else if pieces == board.bishops() {
+            if material.white.bishops == 1 && material.black.bishops == 1 &&
+               (pieces & Bitboard::DARK_SQUARES).count() == 1 {
+                EndgameFamily::OppositeColoredBishops
+            } else {
+                EndgameFamily::Bishop
+            }
+        } else if pieces == minors {
+            EndgameFamily::Minor
+        } else if pieces == board.rooks() {
+            EndgameFamily::Rook
+        } else if pieces == board.rooks() | minors {
+            EndgameFamily::RookAndMinor
+        } else if pieces == board.queens() {
+            EndgameFamily::Queen
+        } else {
+            EndgameFamily::Mixed
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn test_material_key() {
+        for key in &["KvK", "KRPvKR", "KQQvKRNP", "KBNvK"] {
+            let material: Material = key.parse().expect("valid material key");
+            assert_eq!(material.to_string(), *key);
+        }
+
+        let material: Material = "KPvKRR".parse().expect("valid material key");
+        assert_eq!(material.stronger_side(), Color::Black);
+        assert_eq!(material.normalized().to_string(), "KRRvKP");
+        assert_eq!(material.normalized().normalized(), material.normalized());
+
+        assert_eq!(Board::default().material().to_string(), "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+        assert_eq!(Board::empty().material(), Material::new());
+
+        assert!("KRvKRvK".parse::<Material>().is_err());
+        assert!("KXvK".parse::<Material>().is_err());
+    }
+
+    #[test]
+    fn test_endgame_family() {
+        let family = |fen: &str| EndgameFamily::from_board(&fen.parse().expect("valid board"));
+        assert_eq!(family("8/8/4k3/8/4P3/4K3/8/8"), Some(EndgameFamily::Pawn));
+        assert_eq!(family("8/8/4k3/8/2N1P3/4K3/8/8"), Some(EndgameFamily::Knight));
+        assert_eq!(family("8/3b4/4k3/8/4P3/4K3/2B5/8"), Some(EndgameFamily::Bishop));
+        assert_eq!(family("8/2b5/4k3/8/4P3/4K3/2B5/8"), Some(EndgameFamily::OppositeColoredBishops));
+        assert_eq!(family("8/2b5/4k3/8/4P3/4K3/2BB4/8"), Some(EndgameFamily::Bishop));
+        assert_eq!(family("8/2n5/4k3/8/4P3/4K3/2B5/8"), Some(EndgameFamily::Minor));
+        assert_eq!(family("8/2n5/4k3/8/4P3/4K3/2R5/8"), Some(EndgameFamily::RookAndMinor));
+        assert_eq!(family("8/2q5/4k3/8/4P3/4K3/2Q5/8"), Some(EndgameFamily::Queen));
+        assert_eq!(family("8/2q5/4k3/8/4P3/4K3/2R5/8"), Some(EndgameFamily::Mixed));
+        assert_eq!(family("8/8/4k3/8/4P3/4K3/2RRBB2/8"), None);
+    }
+}
@@ -14,14 +14,18 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::position::Position;
 use crate::types::Move;
 
 use arrayvec::{Array, ArrayVec};
 
-/// A container for moves that can be stored inline on the stack.
+/// A container for moves that can be stored inline on the stack, used by
+/// [`Position::legal_moves()`] and friends.
 ///
-/// The capacity is limited, but there is enough space to hold the legal
-/// moves of any chess position, including any of the supported chess variants.
+/// The capacity of 512 is shared by all variants. It is enough to hold the
+/// legal moves of any chess position, including the drop-heavy positions of
+/// Crazyhouse, and a single type keeps [`Position`] object safe, so there is
+/// no per-variant capacity.
 ///
 /// # Example
 ///
@@ -66,3 +70,151 @@ impl<A: Array> ArrayVecExt for ArrayVec<A> {
         }
     }
 }
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Stage {
+    Captures,
+    Promotions,
+    Quiets,
+    Done,
+}
+
+/// A lazy iterator over legal moves, returned by [`Position::moves_iter()`].
+///
+/// Moves are generated in stages: Captures first, then promotions that are
+/// not captures, then [quiet moves](Position::quiet_moves()). A stage is
+/// only generated when the previous one is exhausted, into a buffer on the
+/// stack, so that search loops that stop early skip the remaining stages.
+/// This pays off for [`Chess`](crate::Chess), which generates each stage
+/// directly. Positions that use the default stage generators, like most
+/// variants, generate all legal moves for every stage.
+///
+/// # Example
+///
+/// ```
+/// use shakmaty::{CastlingMode, Chess, Position};
+/// use shakmaty::fen::Fen;
+///
+/// let pos: Chess = "4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1"
+///     .parse::<Fen>()?
+///     .position(CastlingMode::Standard)?;
+///
+/// let first = pos.moves_iter().next().expect("legal move");
+/// assert!(first.is_capture());
+/// assert_eq!(pos.moves_iter().count(), pos.legals().len());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct MoveGen<'a, P: ?Sized> {
+    pos: &'a P,
+    stage: Stage,
+    moves: MoveList,
+    index: usize,
+}
+
+impl<'a, P: Position + ?Sized> MoveGen<'a, P> {
+    /// Starts generating legal moves of `pos`. Also works for trait
+    /// objects, unlike [`Position::moves_iter()`].
+    pub fn new(pos: &'a P) -> MoveGen<'a, P> {
+        MoveGen {
+            pos,
+            stage: Stage::Captures,
+            moves: MoveList::new(),
+            index: 0,
+        }
+    }
+}
+
+impl<'a, P: Position + ?Sized> Iterator for MoveGen<'a, P> {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            if let Some(m) = self.moves.get(self.index) {
+                self.index += 1;
+                return Some(m.clone());
+            }
+
+            self.index = 0;
+            self.stage = match self.stage {
+                Stage::Captures => {
+                    self.pos.capture_moves(&mut self.moves);
+                    Stage::Promotions
+                }
+                Stage::Promotions => {
+                    self.pos.promotion_moves(&mut self.moves);
+                    self.moves.retain(|m| !m.is_capture());
+                    Stage::Quiets
+                }
+                Stage::Quiets => {
+                    self.pos.quiet_moves(&mut self.moves);
+                    Stage::Done
+                }
+                Stage::Done => {
+                    self.moves.clear();
+                    return None;
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::position::Chess;
+    use crate::types::CastlingMode;
+
+    #[test]
+    fn test_move_gen() {
+        for fen in &[
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4k3/8/8/8/8/8/3q4/4K1N1 w - - 0 1",
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            let moves: Vec<Move> = pos.moves_iter().collect();
+            let legals = pos.legals();
+            assert_eq!(moves.len(), legals.len(), "{}", fen);
+            assert!(legals.iter().all(|m| moves.contains(m)));
+
+            let captures = moves.iter().take_while(|m| m.is_capture()).count();
+            assert!(moves[captures..].iter().all(|m| !m.is_capture()));
+        }
+    }
+
+    #[cfg(feature = "crazyhouse")]
+    #[test]
+    fn test_move_gen_drops() {
+        use crate::variants::Crazyhouse;
+
+        let pos: Crazyhouse = "r3k3/8/8/8/8/8/8/R3K3[Nn] w Qq - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        assert_eq!(pos.moves_iter().count(), pos.legals().len());
+        assert!(pos.moves_iter().next().expect("legal move").is_capture());
+    }
+
+    #[cfg(feature = "crazyhouse")]
+    #[test]
+    fn test_capacity() {
+        use crate::variants::Crazyhouse;
+
+        // Drops of every role to almost every square: Well over 255 moves,
+        // still on the stack.
+        let pos: Crazyhouse = "k7/8/8/8/8/8/8/K7[QRBNPqrbnp] w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        let legals = pos.legals();
+        assert_eq!(legals.len(), 62 * 4 + 48 + 3);
+        assert!(legals.len() <= legals.capacity());
+    }
+}
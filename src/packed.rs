@@ -0,0 +1,320 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Compact binary encoding of positions.
+//!
+//! The board is Huffman coded square by square, from a1 to h8, so that
+//! common contents take fewer bits:
+//!
+//! Contents | Code
+//! --- | ---
+//! empty | `0`
+//! pawn | `10`
+//! knight | `1100`
+//! bishop | `1101`
+//! rook | `1110`
+//! queen | `11110`
+//! king | `11111`
+//!
+//! Each piece is followed by a bit for its color (`0` for white). Then
+//! follow a bit for the side to move, a castling bit for each rook on the
+//! first and last rank, an optional en passant file, and the move counters
+//! in Exponential-Golomb coding. Typical positions take 20 to 25 bytes.
+//!
+//! Pockets, remaining checks, promoted pieces and castling rights on
+//! squares without rooks are not included.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! #
+//! use shakmaty::{CastlingMode, Chess, Position, fen};
+//! use shakmaty::packed;
+//!
+//! let pos = Chess::default();
+//! let bytes = packed::encode(&pos);
+//! assert_eq!(bytes.len(), 22);
+//!
+//! let decoded: Chess = packed::decode(&bytes)?.position(CastlingMode::Standard)?;
+//! assert_eq!(fen::fen(&decoded), fen::fen(&pos));
+//! #
+//! # Ok::<_, Box<dyn Error>>(())
+//! ```
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::num::NonZeroU32;
+
+use crate::bitboard::Bitboard;
+use crate::board::Board;
+use crate::fen::Fen;
+use crate::setup::Setup;
+use crate::square::{File, Rank, Square};
+use crate::types::{Color, Piece, Role};
+
+/// Error when decoding invalid or truncated data.
+#[derive(Clone, Debug)]
+pub struct DecodeError;
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid packed position".fmt(f)
+    }
+}
+
+impl Error for DecodeError {
+    fn description(&self) -> &str {
+        "invalid packed position"
+    }
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    fn write(&mut self, bits: u32, n: u32) {
+        for i in (0..n).rev() {
+            if self.len & 7 == 0 {
+                self.bytes.push(0);
+            }
+            if bits & (1 << i) != 0 {
+                *self.bytes.last_mut().expect("byte") |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+
+    fn write_exp_golomb(&mut self, value: u32) {
+        let value = u64::from(value) + 1;
+        let n = 63 - value.leading_zeros();
+        self.write(0, n);
+        for i in (0..=n).rev() {
+            self.write((value >> i) as u32 & 1, 1);
+        }
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn bit(&mut self) -> Result<bool, DecodeError> {
+        let byte = self.bytes.get(self.pos / 8).ok_or(DecodeError)?;
+        let bit = byte & (0x80 >> (self.pos % 8)) != 0;
+        self.pos += 1;
+        Ok(bit)
+    }
+
+    fn read(&mut self, n: u32) -> Result<u32, DecodeError> {
+        let mut bits = 0;
+        for _ in 0..n {
+            bits = (bits << 1) | u32::from(self.bit()?);
+        }
+        Ok(bits)
+    }
+
+    fn read_exp_golomb(&mut self) -> Result<u32, DecodeError> {
+        let mut n = 0;
+        while !self.bit()? {
+            n += 1;
+            if n > 32 {
+                return Err(DecodeError);
+            }
+        }
+        let mut value = 1u64;
+        for _ in 0..n {
+            value = (value << 1) | u64::from(self.bit()?);
+        }
+        u32::try_from(value - 1).map_err(|_| DecodeError)
+    }
+}
+
+fn role_code(role: Role) -> (u32, u32) {
+    match role {
+        Role::Pawn => (0b10, 2),
+        Role::Knight => (0b1100, 4),
+        Role::Bishop => (0b1101, 4),
+        Role::Rook => (0b1110, 4),
+        Role::Queen => (0b11110, 5),
+        Role::King => (0b11111, 5),
+    }
+}
+
+/// Squares that can hold castling rights, in the order they are encoded.
+fn castling_candidates(board: &Board) -> Bitboard {
+    board.rooks() & (Bitboard::from(Rank::First) | Bitboard::from(Rank::Eighth))
+}
+
+/// Encodes a setup.
+pub fn encode(setup: &dyn Setup) -> Vec<u8> {
+    let mut writer = BitWriter { bytes: Vec::with_capacity(24), len: 0 };
+    let board = setup.board();
+
+    for sq in (0..64).map(Square::new) {
+        match board.piece_at(sq) {
+            Some(piece) => {
+                let (code, len) = role_code(piece.role);
+                writer.write(code, len);
+                writer.write(piece.color.fold(0, 1), 1);
+            }
+            None => writer.write(0, 1),
+        }
+    }
+
+    writer.write(setup.turn().fold(0, 1), 1);
+
+    for sq in castling_candidates(board) {
+        writer.write(u32::from(setup.castling_rights().contains(sq)), 1);
+    }
+
+    match setup.ep_square() {
+        Some(sq) => {
+            writer.write(1, 1);
+            writer.write(u32::from(sq.file()), 3);
+        }
+        None => writer.write(0, 1),
+    }
+
+    writer.write_exp_golomb(setup.halfmoves());
+    writer.write_exp_golomb(setup.fullmoves().get() - 1);
+
+    writer.bytes
+}
+
+/// Decodes a setup, that can then be validated as a position.
+///
+/// # Errors
+///
+/// Returns [`DecodeError`] if `bytes` is truncated or has trailing data.
+pub fn decode(bytes: &[u8]) -> Result<Fen, DecodeError> {
+    let mut reader = BitReader { bytes, pos: 0 };
+    let mut fen = Fen::empty();
+
+    for sq in (0..64).map(Square::new) {
+        if !reader.bit()? {
+            continue;
+        }
+        let role = if !reader.bit()? {
+            Role::Pawn
+        } else {
+            match reader.read(2)? {
+                0b00 => Role::Knight,
+                0b01 => Role::Bishop,
+                0b10 => Role::Rook,
+                _ if reader.bit()? => Role::King,
+                _ => Role::Queen,
+            }
+        };
+        let color = Color::from_white(!reader.bit()?);
+        fen.board.set_piece_at(sq, Piece { color, role }, false);
+    }
+
+    fen.turn = Color::from_white(!reader.bit()?);
+
+    for sq in castling_candidates(&fen.board) {
+        if reader.bit()? {
+            fen.castling_rights.add(sq);
+        }
+    }
+
+    if reader.bit()? {
+        let file = File::new(reader.read(3)?);
+        let rank = fen.turn.fold(Rank::Sixth, Rank::Third);
+        fen.ep_square = Some(Square::from_coords(file, rank));
+    }
+
+    fen.halfmoves = reader.read_exp_golomb()?;
+    fen.fullmoves = reader.read_exp_golomb()?
+        .checked_add(1)
+        .and_then(NonZeroU32::new)
+        .ok_or(DecodeError)?;
+
+    if bytes.len() != reader.pos / 8 + usize::from(reader.pos & 7 != 0) {
+        return Err(DecodeError);
+    }
+
+    Ok(fen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen;
+    use crate::position::{Chess, Position};
+    use crate::types::CastlingMode;
+
+    fn assert_roundtrip(setup: &dyn Setup) {
+        let bytes = encode(setup);
+        let decoded = decode(&bytes).expect("decode");
+        assert_eq!(fen::fen(&decoded), fen::fen(setup));
+    }
+
+    #[test]
+    fn test_random_games() {
+        // Xorshift, to play reproducible random games.
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..50 {
+            let mut pos = Chess::default();
+            for _ in 0..200 {
+                assert_roundtrip(&pos);
+                let legals = pos.legals();
+                if legals.is_empty() {
+                    break;
+                }
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                pos.play_unchecked(&legals[(state % legals.len() as u64) as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_special_fields() {
+        for fen in &[
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "1r2k1r1/8/8/8/8/8/8/1R2K1R1 b Gb - 71 1234",
+            "8/8/8/8/8/8/8/8 w - - 0 1",
+        ] {
+            let fen: fen::Fen = fen.parse().expect("valid fen");
+            assert_roundtrip(&fen);
+        }
+
+        let pos: Chess = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3".parse::<fen::Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("legal position");
+        assert!(encode(&pos).len() < 24);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let bytes = encode(&Chess::default());
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(decode(&extended).is_err());
+
+        assert!(decode(&[]).is_err());
+    }
+}
@@ -14,8 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use crate::position::Position;
 use crate::movelist::MoveList;
+use crate::types::Move;
 
 /// Counts legal move paths of a given length.
 ///
@@ -61,6 +64,71 @@ pub fn perft<P: Position + Clone>(pos: &P, depth: u32) -> u64 {
     }
 }
 
+/// Like [`perft()`], but reports progress and can be cancelled.
+///
+/// After each move at the root, `progress` is called with the number of
+/// paths counted so far and the move that was just completed.
+///
+/// If `cancel` is given and gets set, the computation stops as soon as
+/// possible and `None` is returned.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::AtomicBool;
+/// use shakmaty::{Chess, perft_with_progress};
+///
+/// let pos = Chess::default();
+/// let cancel = AtomicBool::new(false);
+///
+/// let mut root_moves = 0;
+/// let nodes = perft_with_progress(&pos, 3, Some(&cancel), |_nodes, _m| root_moves += 1);
+/// assert_eq!(nodes, Some(8902));
+/// assert_eq!(root_moves, 20);
+/// ```
+pub fn perft_with_progress<P, F>(pos: &P, depth: u32, cancel: Option<&AtomicBool>, mut progress: F) -> Option<u64>
+where
+    P: Position + Clone,
+    F: FnMut(u64, &Move),
+{
+    if depth < 1 {
+        return Some(1);
+    }
+
+    let mut moves = MoveList::new();
+    pos.legal_moves(&mut moves);
+
+    let mut nodes = 0;
+    for m in &moves {
+        let mut child = pos.clone();
+        child.play_unchecked(m);
+        nodes += perft_cancellable(&child, depth - 1, cancel)?;
+        progress(nodes, m);
+    }
+    Some(nodes)
+}
+
+fn perft_cancellable<P: Position + Clone>(pos: &P, depth: u32, cancel: Option<&AtomicBool>) -> Option<u64> {
+    if cancel.map_or(false, |c| c.load(Ordering::Relaxed)) {
+        None
+    } else if depth < 1 {
+        Some(1)
+    } else {
+        let mut moves = MoveList::new();
+        pos.legal_moves(&mut moves);
+
+        if depth == 1 {
+            Some(moves.len() as u64)
+        } else {
+            moves.drain(..).map(|m| {
+                let mut child = pos.clone();
+                child.play_unchecked(&m);
+                perft_cancellable(&child, depth - 1, cancel)
+            }).sum()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,4 +140,17 @@ mod tests {
         assert_eq!(perft(&pos, 0), 1);
         assert_eq!(perft(&pos, 1), 20);
     }
+
+    #[test]
+    fn test_perft_cancel() {
+        let pos = Chess::default();
+        let cancel = AtomicBool::new(false);
+        let mut last = 0;
+        let nodes = perft_with_progress(&pos, 4, Some(&cancel), |nodes, _| {
+            last = nodes;
+            cancel.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(nodes, None);
+        assert!(last > 0 && last < 197_281);
+    }
 }
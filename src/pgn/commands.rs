@@ -0,0 +1,398 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::square::Square;
+use super::Node;
+
+/// Error when parsing an invalid `%eval` command argument.
+#[derive(Clone, Debug)]
+pub struct ParseEvalError;
+
+impl fmt::Display for ParseEvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid eval".fmt(f)
+    }
+}
+
+impl Error for ParseEvalError {
+    fn description(&self) -> &str {
+        "invalid eval"
+    }
+}
+
+/// An engine evaluation from the point of view of white, as found in
+/// `[%eval -1.2]` or `[%eval #3]`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Eval {
+    Centipawns(i32),
+    /// Mate in the given number of moves. Negative if black is mating.
+    Mate(i32),
+}
+
+impl FromStr for Eval {
+    type Err = ParseEvalError;
+
+    fn from_str(eval: &str) -> Result<Eval, ParseEvalError> {
+        // Ignore a trailing search depth, like in 0.17,20.
+        let eval = eval.split(',').next().unwrap_or(eval);
+        if eval.starts_with('#') {
+            eval[1..].parse().map(Eval::Mate).map_err(|_| ParseEvalError)
+        } else if eval.bytes().all(|ch| ch.is_ascii_digit() || ch == b'.' || ch == b'-' || ch == b'+') {
+            let pawns: f64 = eval.parse().map_err(|_| ParseEvalError)?;
+            Ok(Eval::Centipawns((pawns * 100.0).round() as i32))
+        } else {
+            Err(ParseEvalError)
+        }
+    }
+}
+
+impl fmt::Display for Eval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Eval::Mate(moves) => write!(f, "#{}", moves),
+            Eval::Centipawns(cp) => {
+                let pawns = format!("{}{}.{:02}", if cp < 0 { "-" } else { "" }, cp.abs() / 100, cp.abs() % 100);
+                f.write_str(if pawns.ends_with('0') { &pawns[..pawns.len() - 1] } else { &pawns })
+            }
+        }
+    }
+}
+
+/// Error when parsing an invalid arrow or circle.
+#[derive(Clone, Debug)]
+pub struct ParseShapeError;
+
+impl fmt::Display for ParseShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid shape".fmt(f)
+    }
+}
+
+impl Error for ParseShapeError {
+    fn description(&self) -> &str {
+        "invalid shape"
+    }
+}
+
+/// The color of an [`Arrow`] or [`Circle`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Brush {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl Brush {
+    pub fn from_char(ch: char) -> Option<Brush> {
+        Some(match ch {
+            'G' => Brush::Green,
+            'R' => Brush::Red,
+            'Y' => Brush::Yellow,
+            'B' => Brush::Blue,
+            _ => return None,
+        })
+    }
+
+    pub fn char(self) -> char {
+        match self {
+            Brush::Green => 'G',
+            Brush::Red => 'R',
+            Brush::Yellow => 'Y',
+            Brush::Blue => 'B',
+        }
+    }
+}
+
+/// An arrow like `Ge2e4`, as found in `[%cal Ge2e4,Rd1h5]`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Arrow {
+    pub brush: Brush,
+    pub from: Square,
+    pub to: Square,
+}
+
+impl FromStr for Arrow {
+    type Err = ParseShapeError;
+
+    fn from_str(arrow: &str) -> Result<Arrow, ParseShapeError> {
+        let mut chars = arrow.chars();
+        let brush = chars.next().and_then(Brush::from_char).ok_or(ParseShapeError)?;
+        let squares = chars.as_str();
+        if squares.len() != 4 || !squares.is_char_boundary(2) {
+            return Err(ParseShapeError);
+        }
+        Ok(Arrow {
+            brush,
+            from: squares[..2].parse().map_err(|_| ParseShapeError)?,
+            to: squares[2..].parse().map_err(|_| ParseShapeError)?,
+        })
+    }
+}
+
+impl fmt::Display for Arrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.brush.char(), self.from, self.to)
+    }
+}
+
+/// A highlighted square like `Rd4`, as found in `[%csl Rd4]`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Circle {
+    pub brush: Brush,
+    pub square: Square,
+}
+
+impl FromStr for Circle {
+    type Err = ParseShapeError;
+
+    fn from_str(circle: &str) -> Result<Circle, ParseShapeError> {
+        let mut chars = circle.chars();
+        Ok(Circle {
+            brush: chars.next().and_then(Brush::from_char).ok_or(ParseShapeError)?,
+            square: chars.as_str().parse().map_err(|_| ParseShapeError)?,
+        })
+    }
+}
+
+impl fmt::Display for Circle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.brush.char(), self.square)
+    }
+}
+
+fn join<T: ToString>(shapes: &[T]) -> Option<String> {
+    if shapes.is_empty() {
+        None
+    } else {
+        Some(shapes.iter().map(T::to_string).collect::<Vec<_>>().join(","))
+    }
+}
+
+/// Parses a clock like `1:02:03` or `0:00:09.5`.
+fn parse_clock(clock: &str) -> Option<Duration> {
+    let mut parts = clock.split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes = parts.next()?;
+    let seconds = parts.next()?;
+    if parts.next().is_some() || minutes.len() != 2 {
+        return None;
+    }
+    let minutes: u64 = minutes.parse().ok().filter(|m| *m < 60)?;
+
+    let (seconds, fraction) = match seconds.find('.') {
+        Some(idx) => (&seconds[..idx], &seconds[idx + 1..]),
+        None => (seconds, ""),
+    };
+    if seconds.len() != 2 || fraction.len() > 9 || !fraction.bytes().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    let seconds: u64 = seconds.parse().ok().filter(|s| *s < 60)?;
+    let nanos = format!("{:0<9}", fraction).parse().ok()?;
+
+    Some(Duration::new(hours * 3600 + minutes * 60 + seconds, nanos))
+}
+
+fn format_clock(clock: Duration) -> String {
+    let secs = clock.as_secs();
+    let mut formatted = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    if clock.subsec_nanos() > 0 {
+        let fraction = format!("{:09}", clock.subsec_nanos());
+        formatted.push('.');
+        formatted.push_str(fraction.trim_end_matches('0'));
+    }
+    formatted
+}
+
+/// Finds all commands in a comment, returning their name, argument and
+/// byte range.
+fn commands(comment: &str) -> Vec<(&str, &str, Range<usize>)> {
+    let mut commands = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = comment[pos..].find("[%").map(|idx| pos + idx) {
+        let end = match comment[start..].find(']') {
+            Some(idx) => start + idx + 1,
+            None => break,
+        };
+        let command = &comment[start + 2..end - 1];
+        let name_end = command.find(char::is_whitespace).unwrap_or(command.len());
+        commands.push((&command[..name_end], command[name_end..].trim(), start..end));
+        pos = end;
+    }
+    commands
+}
+
+impl Node {
+    /// Gets the argument of the first command with the given name, like
+    /// `0:03:00` for `[%clk 0:03:00]`.
+    pub fn command(&self, name: &str) -> Option<&str> {
+        self.comments.iter()
+            .flat_map(|comment| commands(comment))
+            .find(|&(n, _, _)| n == name)
+            .map(|(_, arg, _)| arg)
+    }
+
+    /// Parses the comma separated items of all commands with the given
+    /// name, skipping invalid items.
+    fn shapes<T: FromStr>(&self, name: &str) -> Vec<T> {
+        self.comments.iter()
+            .flat_map(|comment| commands(comment))
+            .filter(|&(n, _, _)| n == name)
+            .flat_map(|(_, arg, _)| arg.split(','))
+            .filter_map(|item| item.trim().parse().ok())
+            .collect()
+    }
+
+    /// Removes all commands with the given name. If `arg` is given, adds
+    /// a new command to the first comment. Empty comments are removed.
+    pub fn set_command(&mut self, name: &str, arg: Option<&str>) {
+        for comment in &mut self.comments {
+            while let Some(range) = commands(comment).into_iter().find(|&(n, _, _)| n == name).map(|(_, _, r)| r) {
+                let (mut start, mut end) = (range.start, range.end);
+                if comment[end..].starts_with(' ') {
+                    end += 1;
+                } else if comment[..start].ends_with(' ') {
+                    start -= 1;
+                }
+                comment.replace_range(start..end, "");
+            }
+        }
+        self.comments.retain(|comment| !comment.trim().is_empty());
+
+        if let Some(arg) = arg {
+            let command = format!("[%{} {}]", name, arg);
+            match self.comments.first_mut() {
+                Some(comment) => {
+                    comment.push(' ');
+                    comment.push_str(&command);
+                }
+                None => self.comments.push(command),
+            }
+        }
+    }
+
+    /// The remaining time on the clock after the move, from `[%clk ...]`.
+    pub fn clock(&self) -> Option<Duration> {
+        self.command("clk").and_then(parse_clock)
+    }
+
+    pub fn set_clock(&mut self, clock: Option<Duration>) {
+        self.set_command("clk", clock.map(format_clock).as_deref());
+    }
+
+    /// The engine evaluation after the move, from `[%eval ...]`.
+    pub fn eval(&self) -> Option<Eval> {
+        self.command("eval").and_then(|eval| eval.parse().ok())
+    }
+
+    pub fn set_eval(&mut self, eval: Option<Eval>) {
+        self.set_command("eval", eval.map(|e| e.to_string()).as_deref());
+    }
+
+    /// Arrows drawn on the board after the move, from `[%cal ...]`.
+    pub fn arrows(&self) -> Vec<Arrow> {
+        self.shapes("cal")
+    }
+
+    pub fn set_arrows(&mut self, arrows: &[Arrow]) {
+        self.set_command("cal", join(arrows).as_deref());
+    }
+
+    /// Squares highlighted after the move, from `[%csl ...]`.
+    pub fn circles(&self) -> Vec<Circle> {
+        self.shapes("csl")
+    }
+
+    pub fn set_circles(&mut self, circles: &[Circle]) {
+        self.set_command("csl", join(circles).as_deref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::Game;
+
+    #[test]
+    fn test_eval() {
+        for &(s, eval) in &[("-1.2", Eval::Centipawns(-120)), ("0.17", Eval::Centipawns(17)), ("-0.05", Eval::Centipawns(-5)),
+                            ("3.0", Eval::Centipawns(300)), ("#-3", Eval::Mate(-3)), ("#1", Eval::Mate(1))] {
+            assert_eq!(s.parse::<Eval>().expect("valid eval"), eval);
+            assert_eq!(eval.to_string(), s);
+        }
+        assert_eq!("0.17,20".parse::<Eval>().expect("valid eval"), Eval::Centipawns(17));
+        assert!("abc".parse::<Eval>().is_err());
+        assert!("#".parse::<Eval>().is_err());
+    }
+
+    #[test]
+    fn test_clock() {
+        for &(s, clock) in &[("0:03:00", Duration::from_secs(180)), ("1:02:03", Duration::from_secs(3723)),
+                             ("0:00:09.5", Duration::from_millis(9500))] {
+            assert_eq!(parse_clock(s), Some(clock));
+            assert_eq!(format_clock(clock), s);
+        }
+        assert_eq!(parse_clock("0:3:00"), None);
+        assert_eq!(parse_clock("0:60:00"), None);
+        assert_eq!(parse_clock("3:00"), None);
+    }
+
+    #[test]
+    fn test_node_commands() {
+        let mut game: Game = "1. e4 { [%eval 0.17] [%clk 0:03:00] } e5 { Solid [%clk 0:02:58] } *".parse().expect("valid pgn");
+        let e4 = game[game.root()].children()[0];
+        let e5 = game[e4].children()[0];
+
+        assert_eq!(game[e4].eval(), Some(Eval::Centipawns(17)));
+        assert_eq!(game[e4].clock(), Some(Duration::from_secs(180)));
+        assert_eq!(game[e5].eval(), None);
+        assert_eq!(game[e5].clock(), Some(Duration::from_secs(178)));
+
+        game[e4].set_eval(None);
+        game[e4].set_clock(Some(Duration::from_millis(179_500)));
+        game[e5].set_clock(None);
+        game[e5].set_eval(Some(Eval::Mate(-2)));
+        assert_eq!(game.to_string(), "1. e4 { [%clk 0:02:59.5] } 1... e5 { Solid [%eval #-2] } *\n");
+
+        game[e4].set_clock(None);
+        assert!(game[e4].comments.is_empty());
+    }
+    #[test]
+    fn test_shapes() {
+        let mut game: Game = "1. e4 { [%cal Ge2e4,Rd1h5] [%csl Rd4] [%cal Bxx9] } *".parse().expect("valid pgn");
+        let e4 = game[game.root()].children()[0];
+
+        assert_eq!(game[e4].arrows(), [
+            Arrow { brush: Brush::Green, from: Square::E2, to: Square::E4 },
+            Arrow { brush: Brush::Red, from: Square::D1, to: Square::H5 },
+        ]);
+        assert_eq!(game[e4].circles(), [Circle { brush: Brush::Red, square: Square::D4 }]);
+
+        game[e4].set_arrows(&[Arrow { brush: Brush::Yellow, from: Square::G1, to: Square::F3 }]);
+        game[e4].set_circles(&[]);
+        assert_eq!(game[e4].comments, ["[%cal Yg1f3]"]);
+
+        assert!("Ge2".parse::<Arrow>().is_err());
+        assert!("Xe2e4".parse::<Arrow>().is_err());
+        assert!("Gd".parse::<Circle>().is_err());
+    }
+}
@@ -0,0 +1,109 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::fen::Fen;
+use crate::position::{FromSetup, Position};
+use crate::types::CastlingMode;
+use super::Game;
+
+/// Positions are sampled at this interval.
+const INTERVAL: usize = 10;
+
+/// A fingerprint of the main line of a game, to detect duplicate games,
+/// for example across PGN collections.
+///
+/// It consists of Zobrist hashes of every 10th position and the final position,
+/// so games that differ only in headers, comments or variations have the
+/// same fingerprint. Games that transpose into each other between the
+/// sampled positions are also considered equal.
+///
+/// Fingerprints are meant for comparison within a single program run.
+/// They are not guaranteed to be stable across versions.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use shakmaty::Chess;
+/// use shakmaty::pgn::{Fingerprint, Game};
+///
+/// let games: Vec<Game> = vec![
+///     "[Event \"A\"]\n\n1. e4 e5 2. Nf3 *".parse().expect("valid pgn"),
+///     "[Event \"B\"]\n\n1. e4 { Best by test } e5 2. Nf3 *".parse().expect("valid pgn"),
+///     "[Event \"C\"]\n\n1. d4 d5 *".parse().expect("valid pgn"),
+/// ];
+///
+/// let mut seen = HashSet::new();
+/// let unique: Vec<&str> = games.iter()
+///     .filter(|game| seen.insert(Fingerprint::new::<Chess>(game).expect("legal moves")))
+///     .map(|game| game.headers.get("Event").unwrap())
+///     .collect();
+/// assert_eq!(unique, ["A", "C"]);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Fingerprint {
+    hashes: Vec<u64>,
+}
+
+impl Fingerprint {
+    /// Replays the main line of `game` from the position given in its `FEN`
+    /// tag, or the default starting position, and fingerprints it.
+    ///
+    /// Returns `None` if the starting position or any of the moves are not
+    /// legal.
+    pub fn new<P: Position + FromSetup + Default>(game: &Game) -> Option<Fingerprint> {
+        let mut pos: P = match game.headers.get("FEN") {
+            Some(fen) => fen.parse::<Fen>().ok()?.position(CastlingMode::Chess960).ok()?,
+            None => P::default(),
+        };
+
+        let mut hashes = vec![pos.zobrist_hash()];
+        let mut plies = 0;
+        for node in game.mainline() {
+            let m = game[node].san()?.san.to_move(&pos).ok()?;
+            pos.play_unchecked(&m);
+            plies += 1;
+            if plies % INTERVAL == 0 {
+                hashes.push(pos.zobrist_hash());
+            }
+        }
+        if plies % INTERVAL != 0 {
+            hashes.push(pos.zobrist_hash());
+        }
+
+        Some(Fingerprint { hashes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Chess;
+
+    fn fingerprint(pgn: &str) -> Option<Fingerprint> {
+        Fingerprint::new::<Chess>(&pgn.parse().expect("valid pgn"))
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let long = "1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 Ng8 5. Nf3 Nf6 6. Ng1 Ng8";
+        assert_eq!(fingerprint(long), fingerprint(&format!("[Event \"Other\"]\n\n{} (6... e5) 1-0", long)));
+        assert_eq!(fingerprint(long).expect("legal").hashes.len(), 3);
+        assert_ne!(fingerprint(long), fingerprint("1. Nf3 Nf6 2. Ng1 Ng8"));
+        assert_ne!(fingerprint("1. e4"), fingerprint("[FEN \"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Qkq - 0 1\"]\n\n1. e4"));
+        assert_eq!(fingerprint("1. e5"), None);
+    }
+}
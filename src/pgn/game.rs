@@ -0,0 +1,947 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::{Index, IndexMut, Range};
+use std::str::FromStr;
+
+use crate::fen::{self, Fen};
+use crate::position::{Chess, Outcome};
+use crate::san::{Nag, SanPlus};
+use crate::setup::Setup;
+use crate::variants::VariantPosition;
+use super::Headers;
+
+/// The part of a PGN that could not be parsed.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ParsePgnErrorKind {
+    InvalidTag,
+    InvalidComment,
+    InvalidVariation,
+    InvalidSan,
+    InvalidMovetext,
+}
+
+impl ParsePgnErrorKind {
+    fn desc(self) -> &'static str {
+        match self {
+            ParsePgnErrorKind::InvalidTag => "invalid tag pair in pgn",
+            ParsePgnErrorKind::InvalidComment => "unterminated comment in pgn",
+            ParsePgnErrorKind::InvalidVariation => "unbalanced variation in pgn",
+            ParsePgnErrorKind::InvalidSan => "invalid san in pgn",
+            ParsePgnErrorKind::InvalidMovetext => "invalid movetext in pgn",
+        }
+    }
+}
+
+/// Errors that can occur when parsing a PGN.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ParsePgnError {
+    kind: ParsePgnErrorKind,
+    span: Range<usize>,
+}
+
+impl ParsePgnError {
+    fn new(kind: ParsePgnErrorKind, span: Range<usize>) -> ParsePgnError {
+        ParsePgnError { kind, span }
+    }
+
+    /// The part of the PGN that could not be parsed.
+    pub fn kind(&self) -> ParsePgnErrorKind {
+        self.kind
+    }
+
+    /// Byte range of the offending token in the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl fmt::Display for ParsePgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.desc().fmt(f)
+    }
+}
+
+impl Error for ParsePgnError {
+    fn description(&self) -> &str {
+        self.kind.desc()
+    }
+}
+
+/// Error when setting up the starting position of a [`Game`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StartingPositionError {
+    /// The `Variant` tag names an unknown or disabled variant.
+    UnknownVariant,
+    /// The `FEN` tag is not syntactically valid.
+    InvalidFen,
+    /// The `FEN` tag describes an illegal position.
+    IllegalPosition,
+}
+
+impl StartingPositionError {
+    fn desc(&self) -> &str {
+        match *self {
+            StartingPositionError::UnknownVariant => "unknown variant in pgn",
+            StartingPositionError::InvalidFen => "invalid fen in pgn",
+            StartingPositionError::IllegalPosition => "illegal starting position in pgn",
+        }
+    }
+}
+
+impl fmt::Display for StartingPositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.desc().fmt(f)
+    }
+}
+
+impl Error for StartingPositionError {
+    fn description(&self) -> &str {
+        self.desc()
+    }
+}
+
+/// Identifies a node of a [`Game`]. Identifiers stay valid when the tree
+/// is modified.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct NodeId(usize);
+
+/// A node of a [`Game`] tree. Every node except the root carries the move
+/// that leads to it.
+///
+/// Comments are stored without the surrounding braces and whitespace.
+/// Rest-of-line comments (`;`) are read like brace comments.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct Node {
+    san: Option<SanPlus>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    /// Comments before the move, e.g. at the start of a variation.
+    pub starting_comments: Vec<String>,
+    /// NAGs like `$1`, following the move.
+    pub nags: Vec<Nag>,
+    /// Comments after the move. For the root these are comments before the
+    /// first move.
+    pub comments: Vec<String>,
+}
+
+impl Node {
+    /// The move leading to this node, or `None` for the root.
+    pub fn san(&self) -> Option<&SanPlus> {
+        self.san.as_ref()
+    }
+
+    /// The parent node, or `None` for the root.
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// The continuations from this node. The first is the main line, all
+    /// others are variations.
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+/// A game with tag pairs and a tree of moves, including recursive
+/// variations.
+///
+/// Moves are stored as [`SanPlus`] and are not validated against a
+/// position, so that games of any variant can be read and written.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::pgn::Game;
+///
+/// let game: Game = "[Event \"Casual\"]\n\n1. e4 e5 (1... c5 2. Nf3) 2. Nf3 *".parse()?;
+/// assert_eq!(game.headers.get("Event"), Some("Casual"));
+///
+/// let e4 = game[game.root()].children()[0];
+/// let alternatives: Vec<String> = game[e4].children().iter()
+///     .map(|id| game[*id].san().expect("move").to_string())
+///     .collect();
+/// assert_eq!(alternatives, ["e5", "c5"]);
+///
+/// assert_eq!(game.to_string(), "[Event \"Casual\"]\n\n1. e4 e5 (1... c5 2. Nf3) 2. Nf3 *\n");
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Game {
+    pub headers: Headers,
+    nodes: Vec<Node>,
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game {
+            headers: Headers::new(),
+            nodes: vec![Node::default()],
+        }
+    }
+}
+
+impl Game {
+    /// A game without tags and moves.
+    pub fn new() -> Game {
+        Game::default()
+    }
+
+    /// The root node, corresponding to the starting position.
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// Adds a move after `parent`. It becomes the main line if `parent` has
+    /// no other continuations, otherwise it is added as the last variation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` is not a node of this game.
+    pub fn add_variation(&mut self, parent: NodeId, san: SanPlus) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            san: Some(san),
+            parent: Some(parent),
+            ..Node::default()
+        });
+        self.nodes[parent.0].children.push(id);
+        id
+    }
+
+    /// Iterates over the main line, i.e. the first continuation of each
+    /// node, starting after the root.
+    pub fn mainline(&self) -> Mainline<'_> {
+        self.mainline_from(self.root())
+    }
+
+    /// Iterates over the main continuation of `node`, not including `node`
+    /// itself.
+    pub fn mainline_from(&self, node: NodeId) -> Mainline<'_> {
+        Mainline { game: self, node }
+    }
+
+    /// Tests if `node` is on the main line of the game.
+    pub fn is_mainline(&self, mut node: NodeId) -> bool {
+        while let Some(parent) = self[node].parent {
+            if self[parent].children.first() != Some(&node) {
+                return false;
+            }
+            node = parent;
+        }
+        true
+    }
+
+    /// All continuations of the parent of `node`, including `node` itself.
+    /// Empty for the root.
+    pub fn siblings(&self, node: NodeId) -> &[NodeId] {
+        self[node].parent.map_or(&[], |parent| self[parent].children())
+    }
+
+    fn sibling_index(&self, node: NodeId) -> Option<(NodeId, usize)> {
+        let parent = self[node].parent?;
+        let idx = self[parent].children.iter().position(|&c| c == node)?;
+        Some((parent, idx))
+    }
+
+    /// Moves a variation one step up among its siblings. Returns `false` if
+    /// it already is the main continuation or has been deleted.
+    pub fn promote(&mut self, node: NodeId) -> bool {
+        match self.sibling_index(node) {
+            Some((parent, idx)) if idx > 0 => {
+                self.nodes[parent.0].children.swap(idx, idx - 1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Makes a variation the main continuation of its parent. Returns
+    /// `false` if it already is or has been deleted.
+    pub fn promote_to_main(&mut self, node: NodeId) -> bool {
+        match self.sibling_index(node) {
+            Some((parent, idx)) if idx > 0 => {
+                self.nodes[parent.0].children[..=idx].rotate_right(1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves a variation one step down among its siblings. Returns `false`
+    /// if it already is the last or has been deleted.
+    pub fn demote(&mut self, node: NodeId) -> bool {
+        match self.sibling_index(node) {
+            Some((parent, idx)) if idx + 1 < self[parent].children.len() => {
+                self.nodes[parent.0].children.swap(idx, idx + 1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes a node with all its continuations from the tree. Returns
+    /// `false` for the root or if it has already been deleted.
+    ///
+    /// The removed nodes can still be indexed, but are no longer reachable
+    /// from the root.
+    pub fn delete(&mut self, node: NodeId) -> bool {
+        match self.sibling_index(node) {
+            Some((parent, idx)) => {
+                self.nodes[parent.0].children.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets up the starting position of the game, according to its
+    /// `Variant` and `FEN` tags. See [`Headers::variant()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StartingPositionError`] if the variant is not supported,
+    /// or if the `FEN` tag is invalid.
+    ///
+    /// # Examples
+    ///
+    /// Replaying a Crazyhouse game with drops:
+    ///
+    #[cfg_attr(feature = "crazyhouse", doc = "```")]
+    #[cfg_attr(not(feature = "crazyhouse"), doc = "```ignore")]
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::{Position, Setup};
+    /// use shakmaty::pgn::Game;
+    ///
+    /// let game: Game = "[Variant \"Crazyhouse\"]\n\n1. e4 d5 2. exd5 Qxd5 3. Nc3 Qa5 4. P@e6 *".parse()?;
+    ///
+    /// let mut pos = game.starting_position()?;
+    /// for node in game.mainline() {
+    ///     let m = game[node].san().expect("move").san.to_move(&pos)?;
+    ///     pos.play_unchecked(&m);
+    /// }
+    /// let pockets = pos.pockets().expect("crazyhouse");
+    /// assert_eq!((pockets.white.pawns, pockets.black.pawns), (0, 1));
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    pub fn starting_position(&self) -> Result<VariantPosition, StartingPositionError> {
+        let variant = self.headers.variant().ok_or(StartingPositionError::UnknownVariant)?;
+
+        #[allow(unused_mut)]
+        let mut pos = match self.headers.get("FEN") {
+            Some(fen) => {
+                let fen: Fen = fen.parse().map_err(|_| StartingPositionError::InvalidFen)?;
+                VariantPosition::from_setup(variant, &fen, self.headers.castling_mode())
+                    .map_err(|_| StartingPositionError::IllegalPosition)?
+            }
+            None => VariantPosition::new(variant),
+        };
+
+        #[cfg(feature = "antichess")]
+        {
+            if let VariantPosition::Antichess(ref mut pos) = pos {
+                if self.headers.variant_key() == "suicide" {
+                    pos.set_rules(crate::variants::AntichessRules::Suicide);
+                }
+            }
+        }
+
+        Ok(pos)
+    }
+
+    /// Starts the game from `setup`, by setting the `SetUp` and `FEN` tags,
+    /// or removing them if `setup` is the standard starting position.
+    ///
+    /// The `Variant` tag is not changed.
+    pub fn set_starting_position(&mut self, setup: &dyn Setup) {
+        self.headers.remove("SetUp");
+        self.headers.remove("FEN");
+        let fen = fen::fen(setup);
+        if fen != fen::fen(&Chess::default()) {
+            self.headers.insert("SetUp", "1");
+            self.headers.insert("FEN", fen);
+        }
+    }
+
+    /// Parses a single game in PGN format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParsePgnError`] if `pgn` is not syntactically valid.
+    pub fn from_ascii(pgn: &[u8]) -> Result<Game, ParsePgnError> {
+        let mut game = Game::new();
+        let (headers, mut pos) = parse_tags(pgn)?;
+        game.headers = headers;
+
+        // Movetext.
+        let mut current = game.root();
+        let mut stack = Vec::new();
+        let mut result = None;
+        let mut starting_comments = Vec::new();
+        let mut variation_start = false;
+        loop {
+            pos = skip_whitespace_and_escapes(pgn, pos);
+            let start = pos;
+            let ch = match pgn.get(pos) {
+                Some(&ch) => ch,
+                None => break,
+            };
+
+            if result.is_some() {
+                return Err(ParsePgnError::new(ParsePgnErrorKind::InvalidMovetext, start..pgn.len()));
+            }
+
+            match ch {
+                b'{' | b';' => {
+                    let (text, end) = if ch == b'{' {
+                        let end = pgn[pos..].iter().position(|&ch| ch == b'}')
+                            .ok_or_else(|| ParsePgnError::new(ParsePgnErrorKind::InvalidComment, start..pgn.len()))?;
+                        (&pgn[pos + 1..pos + end], pos + end + 1)
+                    } else {
+                        let end = line_end(pgn, pos);
+                        (&pgn[pos + 1..end], end)
+                    };
+                    let comment = String::from_utf8_lossy(text).trim().to_owned();
+                    if variation_start {
+                        starting_comments.push(comment);
+                    } else {
+                        game.nodes[current.0].comments.push(comment);
+                    }
+                    pos = end;
+                }
+                b'(' => {
+                    let parent = game[current].parent
+                        .ok_or_else(|| ParsePgnError::new(ParsePgnErrorKind::InvalidVariation, start..start + 1))?;
+                    stack.push(current);
+                    current = parent;
+                    variation_start = true;
+                    pos += 1;
+                }
+                b')' => {
+                    current = stack.pop()
+                        .ok_or_else(|| ParsePgnError::new(ParsePgnErrorKind::InvalidVariation, start..start + 1))?;
+                    pos += 1;
+                }
+                b'*' => {
+                    result = Some(None);
+                    pos += 1;
+                }
+                _ => {
+                    pos = token_end(pgn, pos);
+                    let token = &pgn[start..pos];
+                    match token {
+                        b"1-0" | b"0-1" | b"1/2-1/2" => result = Outcome::from_ascii(token).ok().map(Some),
+                        [b'$', ..] => {
+                            let nag = Nag::from_ascii(token)
+                                .map_err(|_| ParsePgnError::new(ParsePgnErrorKind::InvalidMovetext, start..pos))?;
+                            game.nodes[current.0].nags.push(nag);
+                        }
+                        _ => {
+                            // Skip move numbers like 12. or 12... that may
+                            // be immediately followed by a move.
+                            let digits = token.iter().take_while(|ch| ch.is_ascii_digit()).count();
+                            let dots = token[digits..].iter().take_while(|&&ch| ch == b'.').count();
+                            let san = if digits > 0 && (dots > 0 || digits == token.len()) {
+                                &token[digits + dots..]
+                            } else {
+                                token
+                            };
+                            if !san.is_empty() {
+                                let san_start = start + token.len() - san.len();
+                                let mut san = SanPlus::from_ascii(san)
+                                    .map_err(|_| ParsePgnError::new(ParsePgnErrorKind::InvalidSan, san_start..pos))?;
+                                // Annotations attached to the move are
+                                // stored like separate NAGs.
+                                let nag = san.nag.take();
+                                current = game.add_variation(current, san);
+                                game.nodes[current.0].nags.extend(nag);
+                                game.nodes[current.0].starting_comments.append(&mut starting_comments);
+                                variation_start = false;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if result.is_some() && !stack.is_empty() {
+                return Err(ParsePgnError::new(ParsePgnErrorKind::InvalidVariation, start..pos));
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(ParsePgnError::new(ParsePgnErrorKind::InvalidVariation, pgn.len()..pgn.len()));
+        }
+
+        if let Some(Some(outcome)) = result {
+            if game.headers.get("Result").is_none() {
+                game.headers.set_result(Some(outcome));
+            }
+        }
+
+        Ok(game)
+    }
+
+    /// The ply of the starting position, according to the `FEN` tag.
+    fn starting_ply(&self) -> u32 {
+        match self.headers.get("FEN").and_then(|fen| fen.parse::<Fen>().ok()) {
+            Some(fen) => (fen.fullmoves().get() - 1) * 2 + fen.turn().fold(0, 1),
+            None => 0,
+        }
+    }
+
+    fn write_line(&self, out: &mut Movetext, mut node: NodeId, mut ply: u32, mut force_number: bool) {
+        while let Some((&main, variations)) = self[node].children.split_first() {
+            force_number = out.write_move(self, main, ply, force_number);
+
+            for &variation in variations {
+                out.token("(");
+                let force_number = out.write_move(self, variation, ply, true);
+                self.write_line(out, variation, ply + 1, force_number);
+                out.token(")");
+            }
+            force_number |= !variations.is_empty();
+
+            node = main;
+            ply += 1;
+        }
+    }
+}
+
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Parses the tag pairs at the start of a game, returning them and the
+/// position where the movetext starts.
+pub(super) fn parse_tags(pgn: &[u8]) -> Result<(Headers, usize), ParsePgnError> {
+    let mut headers = Headers::new();
+    let mut pos = if pgn.starts_with(BOM) { BOM.len() } else { 0 };
+
+    loop {
+        pos = skip_whitespace_and_escapes(pgn, pos);
+        if pgn.get(pos) != Some(&b'[') {
+            break;
+        }
+        let (name, value, end) = parse_tag(pgn, pos)
+            .ok_or_else(|| ParsePgnError::new(ParsePgnErrorKind::InvalidTag, pos..line_end(pgn, pos)))?;
+        headers.insert(name, value);
+        pos = end;
+    }
+
+    Ok((headers, pos))
+}
+
+fn skip_whitespace_and_escapes(pgn: &[u8], mut pos: usize) -> usize {
+    while let Some(&ch) = pgn.get(pos) {
+        if ch == b'%' && (pos == 0 || pgn[pos - 1] == b'\n' || (pos == BOM.len() && pgn.starts_with(BOM))) {
+            pos = line_end(pgn, pos);
+        } else if ch.is_ascii_whitespace() {
+            pos += 1;
+        } else {
+            break;
+        }
+    }
+    pos
+}
+
+fn line_end(pgn: &[u8], pos: usize) -> usize {
+    pgn[pos..].iter().position(|&ch| ch == b'\n').map_or(pgn.len(), |end| pos + end)
+}
+
+fn token_end(pgn: &[u8], pos: usize) -> usize {
+    pgn[pos..].iter()
+        .position(|&ch| ch.is_ascii_whitespace() || b"{};()".contains(&ch) || (ch == b'$' && pgn[pos] != b'$'))
+        .map_or(pgn.len(), |end| pos + end)
+}
+
+/// Parses a tag pair like `[Event "Casual"]` at `pos`, returning the name,
+/// unescaped value and the position after the closing bracket.
+fn parse_tag(pgn: &[u8], mut pos: usize) -> Option<(String, String, usize)> {
+    pos += 1; // [
+    while pgn.get(pos).map_or(false, |ch| ch.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    let name_start = pos;
+    while pgn.get(pos).map_or(false, |&ch| ch.is_ascii_alphanumeric() || ch == b'_') {
+        pos += 1;
+    }
+    if name_start == pos {
+        return None;
+    }
+    let name = String::from_utf8_lossy(&pgn[name_start..pos]).into_owned();
+    while pgn.get(pos).map_or(false, |ch| ch.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    if pgn.get(pos) != Some(&b'"') {
+        return None;
+    }
+    pos += 1;
+    let mut value = Vec::new();
+    loop {
+        match *pgn.get(pos)? {
+            b'"' => break,
+            b'\\' if matches!(pgn.get(pos + 1), Some(b'"') | Some(b'\\')) => {
+                value.push(pgn[pos + 1]);
+                pos += 2;
+            }
+            b'\n' => return None,
+            ch => {
+                value.push(ch);
+                pos += 1;
+            }
+        }
+    }
+    pos += 1; // "
+    while pgn.get(pos).map_or(false, |ch| ch.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    if pgn.get(pos) != Some(&b']') {
+        return None;
+    }
+    Some((name, String::from_utf8_lossy(&value).into_owned(), pos + 1))
+}
+
+/// Movetext with lines wrapped at 80 characters, as recommended by the
+/// PGN export format.
+struct Movetext {
+    text: String,
+    line_len: usize,
+}
+
+impl Movetext {
+    fn token(&mut self, token: &str) {
+        if !self.text.is_empty() && !self.text.ends_with(&['(', '\n'][..]) && token != ")" {
+            if self.line_len + 1 + token.len() > 80 {
+                self.text.push('\n');
+                self.line_len = 0;
+            } else {
+                self.text.push(' ');
+                self.line_len += 1;
+            }
+        }
+        self.text.push_str(token);
+        self.line_len += token.len();
+    }
+
+    fn comment(&mut self, comment: &str) {
+        if comment.contains('}') {
+            // Can not be written as a brace comment.
+            self.token(&format!(";{}", comment));
+            self.text.push('\n');
+            self.line_len = 0;
+        } else if comment.is_empty() {
+            self.token("{}");
+        } else {
+            self.token(&format!("{{ {} }}", comment));
+        }
+    }
+
+    /// Writes a move with its annotations. Returns if the next move needs
+    /// a move number.
+    fn write_move(&mut self, game: &Game, node: NodeId, ply: u32, force_number: bool) -> bool {
+        let node = &game[node];
+        for comment in &node.starting_comments {
+            self.comment(comment);
+        }
+        if ply % 2 == 1 {
+            if force_number || !node.starting_comments.is_empty() {
+                self.token(&format!("{}...", ply / 2 + 1));
+            }
+        } else {
+            self.token(&format!("{}.", ply / 2 + 1));
+        }
+        if let Some(ref san) = node.san {
+            self.token(&san.to_string());
+        }
+        for nag in &node.nags {
+            self.token(&nag.to_string());
+        }
+        for comment in &node.comments {
+            self.comment(comment);
+        }
+        !node.comments.is_empty()
+    }
+}
+
+/// Iterator over the main line of a [`Game`].
+#[derive(Debug, Clone)]
+pub struct Mainline<'a> {
+    game: &'a Game,
+    node: NodeId,
+}
+
+impl<'a> Iterator for Mainline<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let next = *self.game[self.node].children.first()?;
+        self.node = next;
+        Some(next)
+    }
+}
+
+impl Index<NodeId> for Game {
+    type Output = Node;
+
+    fn index(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+}
+
+impl IndexMut<NodeId> for Game {
+    fn index_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.0]
+    }
+}
+
+impl FromStr for Game {
+    type Err = ParsePgnError;
+
+    fn from_str(pgn: &str) -> Result<Game, ParsePgnError> {
+        Game::from_ascii(pgn.as_bytes())
+    }
+}
+
+impl fmt::Display for Game {
+    /// Writes the game in PGN format. A `SetUp` tag is added if there is a
+    /// `FEN` tag.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.headers.get("FEN").is_some() && self.headers.get("SetUp").is_none() {
+            let mut headers = self.headers.clone();
+            let fen = headers.remove("FEN").expect("fen tag");
+            headers.insert("SetUp", "1");
+            headers.insert("FEN", fen);
+            write!(f, "{}", headers)?;
+        } else {
+            write!(f, "{}", self.headers)?;
+        }
+        if !self.headers.is_empty() {
+            writeln!(f)?;
+        }
+
+        let mut movetext = Movetext {
+            text: String::new(),
+            line_len: 0,
+        };
+        for comment in &self[self.root()].comments {
+            movetext.comment(comment);
+        }
+        self.write_line(&mut movetext, self.root(), self.starting_ply(), true);
+        movetext.token(Outcome::to_str_opt(self.headers.result()));
+        writeln!(f, "{}", movetext.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mainline(game: &Game) -> Vec<String> {
+        game.mainline().map(|id| game[id].san().expect("move").to_string()).collect()
+    }
+
+    #[test]
+    fn test_set_starting_position() {
+        let mut game = Game::new();
+        game.headers.insert("Event", "Endgame study");
+        let fen: Fen = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1".parse().expect("valid fen");
+        game.set_starting_position(&fen);
+        game.add_variation(game.root(), "Kd2".parse().expect("valid san"));
+        assert_eq!(game.to_string(), "[Event \"Endgame study\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n\n1. Kd2 *\n");
+
+        game.set_starting_position(&Chess::default());
+        assert_eq!(game.headers.get("SetUp"), None);
+        assert_eq!(game.headers.get("FEN"), None);
+
+        // Tag is added when only FEN is present.
+        let game: Game = "[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]\n\n1... Kd7 *".parse().expect("valid pgn");
+        assert_eq!(game.to_string(), "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]\n\n1... Kd7 *\n");
+    }
+
+    #[test]
+    #[cfg(feature = "variants")]
+    fn test_starting_position() {
+        use crate::position::Position;
+        use crate::variants::{AntichessRules, Variant};
+
+        let game: Game = "[Variant \"Three-check\"]\n\n1. e4 e5 2. Bc4 Nc6 3. Bxf7+ *".parse().expect("valid pgn");
+        let mut pos = game.starting_position().expect("three-check");
+        for node in game.mainline() {
+            let m = game[node].san().expect("move").san.to_move(&pos).expect("legal");
+            pos.play_unchecked(&m);
+        }
+        assert_eq!(pos.variant(), Variant::ThreeCheck);
+        assert_eq!(pos.remaining_checks().map(|checks| checks.white), Some(2));
+
+        let game: Game = "[Variant \"suicide\"]\n\n*".parse().expect("valid pgn");
+        match game.starting_position() {
+            Ok(VariantPosition::Antichess(pos)) => assert_eq!(pos.rules(), AntichessRules::Suicide),
+            _ => panic!("expected antichess"),
+        }
+
+        let game: Game = "[Variant \"Chess960\"]\n[FEN \"bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1\"]\n\n*".parse().expect("valid pgn");
+        let pos = game.starting_position().expect("chess960");
+        assert!(pos.castles().mode().is_chess960());
+
+        let game: Game = "[Variant \"Shogi\"]\n\n*".parse().expect("valid pgn");
+        assert_eq!(game.starting_position().err(), Some(StartingPositionError::UnknownVariant));
+
+        let game: Game = "[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n*".parse().expect("valid pgn");
+        assert_eq!(game.starting_position().err(), Some(StartingPositionError::IllegalPosition));
+    }
+
+    #[test]
+    fn test_nested_variations() {
+        let pgn = "1. e4 e5 (1... c5 2. Nf3 (2. Nc3 Nc6) 2... d6) (1... e6) 2. Nf3 Nc6 1-0\n";
+        let game: Game = pgn.parse().expect("valid pgn");
+        assert_eq!(mainline(&game), ["e4", "e5", "Nf3", "Nc6"]);
+        assert_eq!(game.headers.get("Result"), Some("1-0"));
+
+        let e4 = game[game.root()].children()[0];
+        assert_eq!(game[e4].children().len(), 3);
+        let c5 = game[e4].children()[1];
+        let nf3 = game[c5].children()[0];
+        assert_eq!(game[game[c5].children()[1]].san().expect("move").to_string(), "Nc3");
+        assert_eq!(game[nf3].parent(), Some(c5));
+
+        assert_eq!(game.to_string(), format!("[Result \"1-0\"]\n\n{}", pgn));
+    }
+
+    #[test]
+    fn test_move_numbers() {
+        let game: Game = "1.e4 e5 2.Nf3 3... Nc6 *".parse().expect("valid pgn");
+        assert_eq!(mainline(&game), ["e4", "e5", "Nf3", "Nc6"]);
+
+        let pgn = "[SetUp \"1\"]\n[FEN \"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1\"]\n\n1... e5 (1... c5) 2. Nf3 *\n";
+        let game: Game = pgn.parse().expect("valid pgn");
+        assert_eq!(mainline(&game), ["e5", "Nf3"]);
+        assert_eq!(game.to_string(), pgn);
+    }
+
+    #[test]
+    fn test_tags() {
+        let game: Game = "[White \"Fischer, \\\"Bobby\\\"\"]\n[Black \"Spassky\"]\n\n*".parse().expect("valid pgn");
+        assert_eq!(game.headers.get("White"), Some("Fischer, \"Bobby\""));
+        assert_eq!(game.to_string(), "[White \"Fischer, \\\"Bobby\\\"\"]\n[Black \"Spassky\"]\n\n*\n");
+    }
+
+    #[test]
+    fn test_line_wrapping() {
+        let mut game = Game::new();
+        let mut node = game.root();
+        for _ in 0..20 {
+            for san in &["Nf3", "Nf6", "Ng1", "Ng8"] {
+                node = game.add_variation(node, san.parse().expect("valid san"));
+            }
+        }
+        let pgn = game.to_string();
+        assert!(pgn.lines().all(|line| line.len() <= 80));
+        assert_eq!(mainline(&pgn.parse().expect("valid pgn")).len(), 80);
+    }
+
+    #[test]
+    fn test_comments_and_nags() {
+        let pgn = "{ Game comment } 1. e4 $1 { Best by test } e5 ( { Or } 1... c5 $5 ) 2. Nf3 ; Developing\n2... Nc6 *";
+        let mut game: Game = pgn.parse().expect("valid pgn");
+        let root = game.root();
+        assert_eq!(game[root].comments, ["Game comment"]);
+
+        let e4 = game[root].children()[0];
+        assert_eq!(game[e4].nags, [Nag::GOOD_MOVE]);
+        assert_eq!(game[e4].comments, ["Best by test"]);
+
+        let c5 = game[e4].children()[1];
+        assert_eq!(game[c5].starting_comments, ["Or"]);
+        assert_eq!(game[c5].nags, [Nag::SPECULATIVE_MOVE]);
+
+        let nf3 = game[game[e4].children()[0]].children()[0];
+        assert_eq!(game[nf3].comments, ["Developing"]);
+
+        let written = "{ Game comment } 1. e4 $1 { Best by test } 1... e5 ({ Or } 1... c5 $5) 2. Nf3\n{ Developing } 2... Nc6 *\n";
+        assert_eq!(game.to_string(), written);
+        assert_eq!(written.parse::<Game>().expect("valid pgn"), game);
+
+        game[nf3].comments = vec!["With } brace".to_owned()];
+        assert_eq!(game.to_string().parse::<Game>().expect("valid pgn"), game);
+    }
+
+    #[test]
+    fn test_move_annotations_round_trip() {
+        let game: Game = "1. e4! e5?! 2. Nf3 $14 *".parse().expect("valid pgn");
+        let e4 = game[game.root()].children()[0];
+        assert_eq!(game[e4].san().expect("move").nag, None);
+        assert_eq!(game[e4].nags, [Nag::GOOD_MOVE]);
+
+        let written = game.to_string();
+        assert_eq!(written, "1. e4 $1 e5 $6 2. Nf3 $14 *\n");
+        assert_eq!(written.parse::<Game>().expect("valid pgn"), game);
+    }
+
+    #[test]
+    fn test_edit_variations() {
+        let mut game: Game = "1. e4 e5 (1... c5 2. Nf3) (1... e6) 2. Nf3 *".parse().expect("valid pgn");
+        let e4 = game[game.root()].children()[0];
+        let (e5, c5, e6) = match *game[e4].children() {
+            [e5, c5, e6] => (e5, c5, e6),
+            _ => panic!("expected three variations"),
+        };
+        let nf3 = game[c5].children()[0];
+        assert!(game.is_mainline(e5));
+        assert!(!game.is_mainline(nf3));
+        assert_eq!(game.siblings(c5), [e5, c5, e6]);
+        assert!(game.siblings(game.root()).is_empty());
+
+        assert!(game.promote_to_main(e6));
+        assert_eq!(game.siblings(e5), [e6, e5, c5]);
+        assert!(!game.promote(e6));
+        assert!(game.demote(e6));
+        assert!(game.promote(c5));
+        assert_eq!(game.siblings(e5), [e5, c5, e6]);
+        assert!(!game.demote(e6));
+
+        assert!(game.promote_to_main(c5));
+        assert_eq!(mainline(&game), ["e4", "c5", "Nf3"]);
+        assert_eq!(game.mainline_from(e4).collect::<Vec<_>>(), [c5, nf3]);
+
+        assert!(game.delete(c5));
+        assert!(!game.delete(c5));
+        assert!(!game.delete(game.root()));
+        assert_eq!(game[nf3].san().expect("move").to_string(), "Nf3");
+        assert_eq!(game.to_string(), "1. e4 e5 (1... e6) 2. Nf3 *\n");
+    }
+
+    #[test]
+    fn test_errors() {
+        for &(pgn, kind, ref span) in &[
+            ("[Event \"Casual]\n\n*", ParsePgnErrorKind::InvalidTag, 0..15),
+            ("1. e4 {unterminated", ParsePgnErrorKind::InvalidComment, 6..19),
+            ("(1. e4) *", ParsePgnErrorKind::InvalidVariation, 0..1),
+            ("1. e4 (1. d4 *", ParsePgnErrorKind::InvalidVariation, 13..14),
+            ("1. e4 e5 )", ParsePgnErrorKind::InvalidVariation, 9..10),
+            ("1. e4 Zz5 *", ParsePgnErrorKind::InvalidSan, 6..9),
+            ("1. e4 1-0 e5", ParsePgnErrorKind::InvalidMovetext, 10..12),
+        ] {
+            let error = pgn.parse::<Game>().expect_err("invalid pgn");
+            assert_eq!(error.kind(), kind, "{}", pgn);
+            assert_eq!(error.span(), *span, "{}", pgn);
+        }
+    }
+}
@@ -0,0 +1,637 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::fmt;
+use std::slice;
+use std::str::FromStr;
+
+use crate::position::Outcome;
+use crate::types::CastlingMode;
+use crate::variants::Variant;
+use super::ParsePgnError;
+
+/// Error when parsing an invalid PGN date.
+#[derive(Clone, Debug)]
+pub struct ParseDateError;
+
+impl fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid pgn date".fmt(f)
+    }
+}
+
+impl Error for ParseDateError {
+    fn description(&self) -> &str {
+        "invalid pgn date"
+    }
+}
+
+/// A possibly partial date, like `1992.11.04` or `1992.??.??`.
+///
+/// Unknown components are `None`. Dates order chronologically, with unknown
+/// components sorting first.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct Date {
+    pub year: Option<u16>,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+}
+
+impl Date {
+    /// Parses a date in `YYYY.MM.DD` format, where each component may be
+    /// replaced with question marks.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseDateError`] if `date` is not syntactically valid or
+    /// the month or day is out of range.
+    pub fn from_ascii(date: &[u8]) -> Result<Date, ParseDateError> {
+        let mut parts = date.split(|ch| *ch == b'.');
+
+        let year = parse_component(parts.next().ok_or(ParseDateError)?, 4)?;
+        let month = parse_component(parts.next().ok_or(ParseDateError)?, 2)?;
+        let day = parse_component(parts.next().ok_or(ParseDateError)?, 2)?;
+
+        if parts.next().is_some() {
+            return Err(ParseDateError);
+        }
+
+        if month.map_or(false, |m| !(1..=12).contains(&m)) || day.map_or(false, |d| !(1..=31).contains(&d)) {
+            return Err(ParseDateError);
+        }
+
+        Ok(Date {
+            year: year.map(|y| y as u16),
+            month: month.map(|m| m as u8),
+            day: day.map(|d| d as u8),
+        })
+    }
+}
+
+fn parse_component(part: &[u8], width: usize) -> Result<Option<u32>, ParseDateError> {
+    if part.len() != width {
+        Err(ParseDateError)
+    } else if part.iter().all(|ch| *ch == b'?') {
+        Ok(None)
+    } else {
+        btoi::btou(part).map(Some).map_err(|_| ParseDateError)
+    }
+}
+
+impl FromStr for Date {
+    type Err = ParseDateError;
+
+    fn from_str(date: &str) -> Result<Date, ParseDateError> {
+        Date::from_ascii(date.as_bytes())
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.year {
+            Some(year) => write!(f, "{:04}", year)?,
+            None => f.write_str("????")?,
+        }
+        match self.month {
+            Some(month) => write!(f, ".{:02}", month)?,
+            None => f.write_str(".??")?,
+        }
+        match self.day {
+            Some(day) => write!(f, ".{:02}", day),
+            None => f.write_str(".??"),
+        }
+    }
+}
+
+/// Error when parsing an invalid PGN time control.
+#[derive(Clone, Debug)]
+pub struct ParseTimeControlError;
+
+impl fmt::Display for ParseTimeControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid pgn time control".fmt(f)
+    }
+}
+
+impl Error for ParseTimeControlError {
+    fn description(&self) -> &str {
+        "invalid pgn time control"
+    }
+}
+
+/// A period of a [`TimeControl`], like `40/7200` or `300+3`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct TimeControlPeriod {
+    /// Number of moves to be played in this period, or `None` for the rest
+    /// of the game.
+    pub moves: Option<u32>,
+    pub seconds: u32,
+    /// Increment in seconds per move.
+    pub increment: u32,
+}
+
+impl fmt::Display for TimeControlPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(moves) = self.moves {
+            write!(f, "{}/", moves)?;
+        }
+        write!(f, "{}", self.seconds)?;
+        if self.increment > 0 {
+            write!(f, "+{}", self.increment)?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of a `TimeControl` tag, as described in the PGN standard.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TimeControl {
+    /// `?`
+    Unknown,
+    /// `-`
+    Unlimited,
+    /// `*180`: Sandclock with the given number of seconds.
+    Sandclock { seconds: u32 },
+    /// One or more periods, separated by colons, like `40/7200:3600`.
+    Periods(Vec<TimeControlPeriod>),
+}
+
+impl TimeControl {
+    /// Parses a `TimeControl` tag value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseTimeControlError`] if `tc` is not syntactically valid.
+    pub fn from_ascii(tc: &[u8]) -> Result<TimeControl, ParseTimeControlError> {
+        match tc {
+            b"?" => Ok(TimeControl::Unknown),
+            b"-" => Ok(TimeControl::Unlimited),
+            [b'*', seconds @ ..] => Ok(TimeControl::Sandclock {
+                seconds: btoi::btou(seconds).map_err(|_| ParseTimeControlError)?,
+            }),
+            _ => tc.split(|ch| *ch == b':')
+                .map(parse_period)
+                .collect::<Result<_, _>>()
+                .map(TimeControl::Periods),
+        }
+    }
+}
+
+fn parse_period(period: &[u8]) -> Result<TimeControlPeriod, ParseTimeControlError> {
+    let (moves, rest) = match period.iter().position(|ch| *ch == b'/') {
+        Some(idx) => (Some(btoi::btou(&period[..idx]).map_err(|_| ParseTimeControlError)?), &period[idx + 1..]),
+        None => (None, period),
+    };
+
+    let (seconds, increment) = match rest.iter().position(|ch| *ch == b'+') {
+        Some(idx) => (&rest[..idx], btoi::btou(&rest[idx + 1..]).map_err(|_| ParseTimeControlError)?),
+        None => (rest, 0),
+    };
+
+    Ok(TimeControlPeriod {
+        moves,
+        seconds: btoi::btou(seconds).map_err(|_| ParseTimeControlError)?,
+        increment,
+    })
+}
+
+impl FromStr for TimeControl {
+    type Err = ParseTimeControlError;
+
+    fn from_str(tc: &str) -> Result<TimeControl, ParseTimeControlError> {
+        TimeControl::from_ascii(tc.as_bytes())
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TimeControl::Unknown => f.write_str("?"),
+            TimeControl::Unlimited => f.write_str("-"),
+            TimeControl::Sandclock { seconds } => write!(f, "*{}", seconds),
+            TimeControl::Periods(ref periods) => {
+                for (i, period) in periods.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(":")?;
+                    }
+                    write!(f, "{}", period)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Error when parsing an invalid PGN round.
+#[derive(Clone, Debug)]
+pub struct ParseRoundError;
+
+impl fmt::Display for ParseRoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid pgn round".fmt(f)
+    }
+}
+
+impl Error for ParseRoundError {
+    fn description(&self) -> &str {
+        "invalid pgn round"
+    }
+}
+
+/// The value of a `Round` tag.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Round {
+    /// `?`
+    Unknown,
+    /// `-`: Rounds are not applicable, e.g. in casual games.
+    Inappropriate,
+    /// Round numbers, like `[3, 1]` for `3.1`, the first game of the
+    /// third round.
+    Numbered(Vec<u32>),
+}
+
+impl Round {
+    /// Parses a `Round` tag value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseRoundError`] if `round` is not syntactically valid.
+    pub fn from_ascii(round: &[u8]) -> Result<Round, ParseRoundError> {
+        match round {
+            b"?" => Ok(Round::Unknown),
+            b"-" => Ok(Round::Inappropriate),
+            _ => round.split(|ch| *ch == b'.')
+                .map(|part| btoi::btou(part).map_err(|_| ParseRoundError))
+                .collect::<Result<_, _>>()
+                .map(Round::Numbered),
+        }
+    }
+}
+
+impl FromStr for Round {
+    type Err = ParseRoundError;
+
+    fn from_str(round: &str) -> Result<Round, ParseRoundError> {
+        Round::from_ascii(round.as_bytes())
+    }
+}
+
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Round::Unknown => f.write_str("?"),
+            Round::Inappropriate => f.write_str("-"),
+            Round::Numbered(ref numbers) => {
+                for (i, number) in numbers.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    write!(f, "{}", number)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The Seven Tag Roster, in canonical order, with values for unknown tags.
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// PGN tag pairs, in insertion order.
+///
+/// When written, the tags of the Seven Tag Roster come first, in their
+/// canonical order, followed by all other tags in insertion order.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Headers {
+    tags: Vec<(String, String)>,
+}
+
+impl Headers {
+    pub fn new() -> Headers {
+        Headers::default()
+    }
+
+    /// Parses only the tag pairs of a PGN game. The movetext is not
+    /// inspected at all, which makes this much faster than parsing the
+    /// whole [`Game`](super::Game), e.g. when indexing large databases.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParsePgnError`] if a tag pair is not syntactically valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::pgn::Headers;
+    ///
+    /// let headers = Headers::from_pgn(b"[White \"Carlsen\"]\n[Result \"1-0\"]\n\n1. e4 {not parsed 1-0")?;
+    /// assert_eq!(headers.get("White"), Some("Carlsen"));
+    /// assert_eq!(headers.get("Result"), Some("1-0"));
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    pub fn from_pgn(pgn: &[u8]) -> Result<Headers, ParsePgnError> {
+        super::game::parse_tags(pgn).map(|(headers, _)| headers)
+    }
+
+    /// Gets the raw value of a tag.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Sets the value of a tag, replacing any previous value but keeping
+    /// its position.
+    pub fn insert<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+        let name = name.into();
+        let value = value.into();
+        match self.tags.iter_mut().find(|(n, _)| *n == name) {
+            Some(tag) => tag.1 = value,
+            None => self.tags.push((name, value)),
+        }
+    }
+
+    /// Removes a tag and returns its value.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let idx = self.tags.iter().position(|(n, _)| n == name)?;
+        Some(self.tags.remove(idx).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, (String, String)> {
+        self.tags.iter()
+    }
+
+    /// Adds all missing tags of the Seven Tag Roster, with values for
+    /// unknown tags, e.g. `?` for `Event`.
+    pub fn fill_seven_tag_roster(&mut self) {
+        for &(name, unknown) in &SEVEN_TAG_ROSTER {
+            if self.get(name).is_none() {
+                self.insert(name, unknown);
+            }
+        }
+    }
+
+    /// The `Date` tag, or `None` if it is missing or invalid.
+    pub fn date(&self) -> Option<Date> {
+        self.get("Date")?.parse().ok()
+    }
+
+    pub fn set_date(&mut self, date: Date) {
+        self.insert("Date", date.to_string());
+    }
+
+    /// The `Round` tag, or `None` if it is missing or invalid.
+    pub fn round(&self) -> Option<Round> {
+        self.get("Round")?.parse().ok()
+    }
+
+    pub fn set_round(&mut self, round: Round) {
+        self.insert("Round", round.to_string());
+    }
+
+    /// The `Result` tag. `None` if it is missing or invalid, or if the game
+    /// is still in progress or was abandoned (`*`).
+    pub fn result(&self) -> Option<Outcome> {
+        self.get("Result")?.parse().ok()
+    }
+
+    /// Sets the `Result` tag, using `*` for `None`.
+    pub fn set_result(&mut self, outcome: Option<Outcome>) {
+        self.insert("Result", Outcome::to_str_opt(outcome));
+    }
+
+    /// The `TimeControl` tag, or `None` if it is missing or invalid.
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.get("TimeControl")?.parse().ok()
+    }
+
+    pub fn set_time_control(&mut self, time_control: &TimeControl) {
+        self.insert("TimeControl", time_control.to_string());
+    }
+
+    /// The `WhiteElo` tag, or `None` if it is missing, unknown or invalid.
+    pub fn white_elo(&self) -> Option<u16> {
+        self.elo("WhiteElo")
+    }
+
+    /// The `BlackElo` tag, or `None` if it is missing, unknown or invalid.
+    pub fn black_elo(&self) -> Option<u16> {
+        self.elo("BlackElo")
+    }
+
+    fn elo(&self, name: &str) -> Option<u16> {
+        btoi::btou(self.get(name)?.as_bytes()).ok()
+    }
+
+    /// The variant named in the `Variant` tag, or [`Variant::Chess`] if it
+    /// is missing. `None` if the variant is unknown or not enabled.
+    ///
+    /// Common spellings like `Three-check` or `King of the Hill` are
+    /// recognized, as well as FICS names like `wild/fr` or `suicide`.
+    pub fn variant(&self) -> Option<Variant> {
+        // Map aliases to the UCI name, which is already normalized the same
+        // way, so that the list of variants is kept in one place.
+        let key = self.variant_key();
+        Variant::from_uci(match key.as_str() {
+            "" | "standard" | "normal" | "fromposition" | "chess960" | "960" |
+            "fischerandom" | "fischerrandom" => "chess",
+            key if key.starts_with("wild/") => "chess",
+            "giveaway" | "suicide" => "antichess",
+            "koth" => "kingofthehill",
+            "threecheck" => "3check",
+            "zh" => "crazyhouse",
+            key => key,
+        })
+    }
+
+    /// [`CastlingMode::Chess960`] if the `Variant` tag names Chess960
+    /// (including FICS `wild/fr`), otherwise [`CastlingMode::Standard`].
+    pub fn castling_mode(&self) -> CastlingMode {
+        CastlingMode::from_chess960(matches!(
+            self.variant_key().as_str(),
+            "chess960" | "960" | "fischerandom" | "fischerrandom" | "wild/fr"
+        ))
+    }
+
+    /// The `Variant` tag in lowercase, without spaces, dashes and
+    /// underscores.
+    pub(super) fn variant_key(&self) -> String {
+        self.get("Variant")
+            .unwrap_or("")
+            .chars()
+            .filter(|ch| !matches!(ch, ' ' | '-' | '_'))
+            .map(|ch| ch.to_ascii_lowercase())
+            .collect()
+    }
+}
+
+impl fmt::Display for Headers {
+    /// Writes the tag pairs, one per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let roster = SEVEN_TAG_ROSTER.iter().filter_map(|&(name, _)| self.tags.iter().find(|(n, _)| n == name));
+        let others = self.tags.iter().filter(|(n, _)| SEVEN_TAG_ROSTER.iter().all(|&(name, _)| name != n));
+        for (name, value) in roster.chain(others) {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a Headers {
+    type Item = &'a (String, String);
+    type IntoIter = slice::Iter<'a, (String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        for date in &["1992.11.04", "????.??.??", "2020.??.??", "2020.03.??"] {
+            assert_eq!(date.parse::<Date>().expect("valid date").to_string(), *date);
+        }
+        assert!("2020.13.01".parse::<Date>().is_err());
+        assert!("2020.1.1".parse::<Date>().is_err());
+        assert!("2020.01.01.01".parse::<Date>().is_err());
+        assert!("????.??.??".parse::<Date>().unwrap() < "1900.01.01".parse::<Date>().unwrap());
+    }
+
+    #[test]
+    fn test_time_control() {
+        for tc in &["?", "-", "*180", "300+3", "40/7200:3600", "40/5400+30:1800+30"] {
+            assert_eq!(tc.parse::<TimeControl>().expect("valid time control").to_string(), *tc);
+        }
+        assert_eq!("40/7200:3600".parse::<TimeControl>().expect("valid time control"), TimeControl::Periods(vec![
+            TimeControlPeriod { moves: Some(40), seconds: 7200, increment: 0 },
+            TimeControlPeriod { moves: None, seconds: 3600, increment: 0 },
+        ]));
+        assert!("".parse::<TimeControl>().is_err());
+        assert!("300+".parse::<TimeControl>().is_err());
+        assert!("40/".parse::<TimeControl>().is_err());
+        assert!("40/7200:".parse::<TimeControl>().is_err());
+        assert!("*".parse::<TimeControl>().is_err());
+        assert!("+3".parse::<TimeControl>().is_err());
+
+        let mut headers = Headers::new();
+        headers.set_time_control(&TimeControl::Sandclock { seconds: 60 });
+        assert_eq!(headers.get("TimeControl"), Some("*60"));
+        assert_eq!(headers.time_control(), Some(TimeControl::Sandclock { seconds: 60 }));
+    }
+
+    #[test]
+    fn test_headers() {
+        let mut headers = Headers::new();
+        headers.insert("White", "Carlsen");
+        headers.insert("Black", "Caruana");
+        headers.insert("White", "Carlsen, Magnus");
+        headers.insert("WhiteElo", "?");
+        assert_eq!(headers.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(), ["White", "Black", "WhiteElo"]);
+        assert_eq!(headers.get("White"), Some("Carlsen, Magnus"));
+        assert_eq!(headers.white_elo(), None);
+        assert_eq!(headers.remove("Black"), Some("Caruana".to_owned()));
+        assert_eq!(headers.len(), 2);
+    }
+
+    #[test]
+    fn test_round() {
+        for round in &["?", "-", "3", "3.1", "1.2.3"] {
+            assert_eq!(round.parse::<Round>().expect("valid round").to_string(), *round);
+        }
+        assert_eq!("3.1".parse::<Round>().expect("valid round"), Round::Numbered(vec![3, 1]));
+        assert!("".parse::<Round>().is_err());
+        assert!("3.".parse::<Round>().is_err());
+        assert!("a".parse::<Round>().is_err());
+    }
+
+    #[test]
+    fn test_seven_tag_roster() {
+        let mut headers = Headers::new();
+        headers.insert("WhiteElo", "2800");
+        headers.insert("White", "Carlsen");
+        headers.set_result(Some(Outcome::Draw));
+        headers.set_round(Round::Numbered(vec![4]));
+        assert_eq!(headers.result(), Some(Outcome::Draw));
+        assert_eq!(headers.to_string(), "[Round \"4\"]\n[White \"Carlsen\"]\n[Result \"1/2-1/2\"]\n[WhiteElo \"2800\"]\n");
+
+        headers.fill_seven_tag_roster();
+        assert_eq!(headers.to_string(), "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"4\"]\n\
+                                         [White \"Carlsen\"]\n[Black \"?\"]\n[Result \"1/2-1/2\"]\n[WhiteElo \"2800\"]\n");
+        assert_eq!(headers.date(), Some(Date::default()));
+
+        headers.set_result(None);
+        assert_eq!(headers.get("Result"), Some("*"));
+        assert_eq!(headers.result(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "variants")]
+    fn test_variant() {
+        let mut headers = Headers::new();
+        assert_eq!(headers.variant(), Some(Variant::Chess));
+        assert_eq!(headers.castling_mode(), CastlingMode::Standard);
+
+        headers.insert("Variant", "Chess960");
+        assert_eq!(headers.variant(), Some(Variant::Chess));
+        assert_eq!(headers.castling_mode(), CastlingMode::Chess960);
+
+        headers.insert("Variant", "wild/fr");
+        assert_eq!(headers.castling_mode(), CastlingMode::Chess960);
+
+        headers.insert("Variant", "Three-check");
+        assert_eq!(headers.variant(), Some(Variant::ThreeCheck));
+
+        headers.insert("Variant", "King of the Hill");
+        assert_eq!(headers.variant(), Some(Variant::KingOfTheHill));
+
+        headers.insert("Variant", "suicide");
+        assert_eq!(headers.variant(), Some(Variant::Antichess));
+
+        headers.insert("Variant", "Capablanca");
+        assert_eq!(headers.variant(), None);
+    }
+
+    #[test]
+    fn test_from_pgn() {
+        let headers = Headers::from_pgn(b"\xef\xbb\xbf% exported\n[Event \"Rated \\\"Blitz\\\"\"]\n[Site \"?\"]\n\n1. e4 (").expect("valid tags");
+        assert_eq!(headers.get("Event"), Some("Rated \"Blitz\""));
+        assert_eq!(headers.len(), 2);
+        assert!(Headers::from_pgn(b"[Event \"Rated]\n").is_err());
+        assert!(Headers::from_pgn(b"1. e4 e5").expect("no tags").is_empty());
+    }
+}
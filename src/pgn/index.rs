@@ -0,0 +1,116 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::{self, BufRead, Seek, SeekFrom};
+
+use super::{RawGame, Reader};
+
+/// Byte offsets of the games in a PGN database, for random access.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use std::io::Cursor;
+/// use shakmaty::pgn::GameIndex;
+///
+/// let mut db = Cursor::new(&b"[Round \"1\"]\n\n1. e4 *\n\n[Round \"2\"]\n\n1. d4 *\n"[..]);
+///
+/// let index = GameIndex::build(&mut db)?;
+/// assert_eq!(index.offsets(), &[0, 22]);
+///
+/// let game = index.read_game(&mut db, 1)?.expect("second game");
+/// assert_eq!(game.headers()?.get("Round"), Some("2"));
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GameIndex {
+    offsets: Vec<u64>,
+}
+
+impl GameIndex {
+    /// Builds an index by streaming through all games, starting at the
+    /// current position of `inner`. The movetext is not parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error encountered while reading.
+    pub fn build<R: BufRead>(inner: R) -> io::Result<GameIndex> {
+        let mut index = GameIndex::default();
+        for game in Reader::new(inner) {
+            index.offsets.push(game?.offset());
+        }
+        Ok(index)
+    }
+
+    /// The number of indexed games.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Byte offsets of all games, in order.
+    pub fn offsets(&self) -> &[u64] {
+        &self.offsets
+    }
+
+    /// Seeks to the `n`-th game and reads it, or returns `None` if there
+    /// is no such game.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error encountered while seeking or reading.
+    pub fn read_game<R: BufRead + Seek>(&self, inner: &mut R, n: usize) -> io::Result<Option<RawGame>> {
+        let offset = match self.offsets.get(n) {
+            Some(&offset) => offset,
+            None => return Ok(None),
+        };
+        inner.seek(SeekFrom::Start(offset))?;
+        Reader::with_offset(inner, offset).next().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_random_access() {
+        let mut pgn = Vec::new();
+        for round in 1..=10 {
+            pgn.extend_from_slice(format!("[Round \"{}\"]\n\n1. e4 {{ Game {} }} *\n\n", round, round).as_bytes());
+        }
+        let mut db = Cursor::new(pgn);
+
+        let index = GameIndex::build(&mut db).expect("index");
+        assert_eq!(index.len(), 10);
+
+        for n in (0..10).rev() {
+            let raw = index.read_game(&mut db, n).expect("read").expect("game");
+            assert_eq!(raw.offset(), index.offsets()[n]);
+            let game = raw.game().expect("valid pgn");
+            assert_eq!(game.headers.get("Round"), Some((n + 1).to_string().as_str()));
+        }
+
+        assert_eq!(index.read_game(&mut db, 10).expect("read"), None);
+    }
+}
@@ -0,0 +1,72 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Portable Game Notation building blocks.
+//!
+//! # Examples
+//!
+//! Reading and writing a game with variations:
+//!
+//! ```
+//! # use std::error::Error;
+//! #
+//! use shakmaty::pgn::Game;
+//!
+//! let game: Game = "1. e4 e5 (1... c5) 2. Nf3 1-0".parse()?;
+//! assert_eq!(game.headers.get("Result"), Some("1-0"));
+//! assert_eq!(game.to_string(), "[Result \"1-0\"]\n\n1. e4 e5 (1... c5) 2. Nf3 1-0\n");
+//! #
+//! # Ok::<_, Box<dyn Error>>(())
+//! ```
+//!
+//! Reading typed values from tag pairs:
+//!
+//! ```
+//! use shakmaty::pgn::{Date, Headers, TimeControl};
+//!
+//! let mut headers = Headers::new();
+//! headers.insert("Date", "2020.??.??");
+//! headers.insert("WhiteElo", "2150");
+//! headers.insert("TimeControl", "300+3");
+//!
+//! assert_eq!(headers.date(), Some(Date { year: Some(2020), month: None, day: None }));
+//! assert_eq!(headers.white_elo(), Some(2150));
+//! assert_eq!(headers.black_elo(), None);
+//! assert_eq!(headers.time_control().map(|tc| tc.to_string()), Some("300+3".to_owned()));
+//! ```
+
+mod commands;
+mod fingerprint;
+mod game;
+mod headers;
+mod index;
+mod opening_tree;
+mod reader;
+mod replay;
+
+pub use self::commands::{Arrow, Brush, Circle, Eval, ParseEvalError, ParseShapeError};
+pub use self::fingerprint::Fingerprint;
+pub use self::game::{
+    Game, Mainline, Node, NodeId, ParsePgnError, ParsePgnErrorKind, StartingPositionError,
+};
+pub use self::headers::{
+    Date, Headers, ParseDateError, ParseRoundError, ParseTimeControlError, Round, TimeControl,
+    TimeControlPeriod,
+};
+pub use self::index::GameIndex;
+pub use self::opening_tree::{MoveStats, OpeningTree};
+pub use self::reader::{ImportError, RawGame, Reader};
+pub use self::replay::{Positions, ReplayError};
@@ -0,0 +1,175 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::position::{Outcome, Position};
+use crate::types::{Color, Move};
+use super::{Game, ReplayError};
+
+/// Statistics about a move in an [`OpeningTree`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MoveStats {
+    /// Number of games in which the move was played, including games
+    /// without a result.
+    pub games: u64,
+    /// Number of those games won by white.
+    pub white: u64,
+    /// Number of those games that were drawn.
+    pub draws: u64,
+    /// Number of those games won by black.
+    pub black: u64,
+}
+
+impl MoveStats {
+    fn add(&mut self, outcome: Option<Outcome>) {
+        self.games += 1;
+        match outcome {
+            Some(Outcome::Decisive { winner: Color::White }) => self.white += 1,
+            Some(Outcome::Decisive { winner: Color::Black }) => self.black += 1,
+            Some(Outcome::Draw) => self.draws += 1,
+            None => (),
+        }
+    }
+}
+
+/// Move statistics aggregated from many games, the core of an opening
+/// explorer.
+///
+/// Statistics are collected per position, not per move sequence, so
+/// transpositions are merged. Positions are identified by their
+/// [Zobrist hashes](crate::zobrist).
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::Chess;
+/// use shakmaty::pgn::{Game, OpeningTree};
+///
+/// let mut tree = OpeningTree::new(20);
+/// for pgn in &["1. e4 e5 2. Nf3 Nc6 1-0", "1. e4 c5 0-1", "1. Nf3 e5 2. e4 Nf6 1/2-1/2"] {
+///     tree.add_game(&pgn.parse::<Game>()?)?;
+/// }
+///
+/// let moves = tree.moves(&Chess::default());
+/// assert_eq!(moves.len(), 2);
+/// assert_eq!(moves[0].0.to_string(), "e2-e4");
+/// assert_eq!(moves[0].1.games, 2);
+///
+/// // 1. e4 e5 2. Nf3 and 1. Nf3 e5 2. e4 transpose.
+/// let game: Game = "1. e4 e5 2. Nf3".parse()?;
+/// let pos = game.positions().last().expect("moves")?;
+/// let moves = tree.moves(&pos);
+/// assert_eq!(moves.len(), 2);
+/// assert_eq!(moves[1].1.draws, 1);
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OpeningTree {
+    max_plies: usize,
+    positions: HashMap<u64, Vec<(Move, MoveStats)>>,
+}
+
+impl OpeningTree {
+    /// Creates an empty tree, which will include only the first `max_plies`
+    /// moves of each game.
+    pub fn new(max_plies: usize) -> OpeningTree {
+        OpeningTree {
+            max_plies,
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Replays the main line of `game` and adds its moves, counted
+    /// according to the `Result` tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplayError`] if the game can not be replayed up to
+    /// the maximum number of plies. Nothing is added in this case.
+    pub fn add_game(&mut self, game: &Game) -> Result<(), ReplayError> {
+        let mut pos = game.starting_position().map_err(ReplayError::StartingPosition)?;
+        let mut entries = Vec::new();
+        for node in game.mainline().take(self.max_plies) {
+            let m = game[node].san()
+                .and_then(|san| san.san.to_move(&pos).ok())
+                .ok_or(ReplayError::IllegalMove(node))?;
+            entries.push((pos.zobrist_hash(), m.clone()));
+            pos.play_unchecked(&m);
+        }
+
+        let outcome = game.headers.result();
+        for (key, m) in entries {
+            let moves = self.positions.entry(key).or_default();
+            match moves.iter_mut().find(|(other, _)| *other == m) {
+                Some((_, stats)) => stats.add(outcome),
+                None => {
+                    let mut stats = MoveStats::default();
+                    stats.add(outcome);
+                    moves.push((m, stats));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The moves played in `pos`, the most popular first.
+    pub fn moves<P: Position + ?Sized>(&self, pos: &P) -> Vec<(Move, MoveStats)> {
+        let mut moves = self.positions.get(&pos.zobrist_hash()).cloned().unwrap_or_default();
+        moves.sort_by_key(|(_, stats)| Reverse(stats.games));
+        moves
+    }
+
+    /// The number of distinct positions in the tree.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Chess;
+
+    #[test]
+    fn test_opening_tree() {
+        let mut tree = OpeningTree::new(2);
+        assert!(tree.is_empty());
+
+        for pgn in &["1. d4 d5 2. c4 1-0", "1. d4 Nf6 2. c4 0-1", "1. d4 d5 1/2-1/2", "1. d4 *"] {
+            tree.add_game(&pgn.parse().expect("valid pgn")).expect("legal moves");
+        }
+        assert_eq!(tree.len(), 2);
+
+        let moves = tree.moves(&Chess::default());
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].1, MoveStats { games: 4, white: 1, draws: 1, black: 1 });
+
+        // Broken games are not added at all.
+        let game = "1. e4 Ke7 2. Kf3 *".parse().expect("valid pgn");
+        let mut tree = OpeningTree::new(10);
+        assert!(tree.add_game(&game).is_err());
+        assert!(tree.is_empty());
+    }
+}
@@ -0,0 +1,350 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::mem;
+
+use super::{Game, Headers, ParsePgnError};
+
+/// Error when a game from a stream can not be parsed, with the position
+/// of the problem in the stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportError {
+    offset: u64,
+    line: Option<u64>,
+    error: ParsePgnError,
+}
+
+impl ImportError {
+    /// Byte offset of the offending token in the stream.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Line number (starting at 1) of the offending token in the stream,
+    /// or `None` if the stream was not read from the start.
+    pub fn line(&self) -> Option<u64> {
+        self.line
+    }
+
+    /// The underlying parser error. Its span is relative to the game.
+    pub fn error(&self) -> &ParsePgnError {
+        &self.error
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{} (line {})", self.error, line),
+            None => write!(f, "{} (byte {})", self.error, self.offset),
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn description(&self) -> &str {
+        #[allow(deprecated)]
+        self.error.description()
+    }
+}
+
+/// The unparsed text of a single game, as yielded by [`Reader`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RawGame {
+    offset: u64,
+    line: Option<u64>,
+    bytes: Vec<u8>,
+}
+
+impl RawGame {
+    /// Byte offset of the game in the stream.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Line number (starting at 1) of the first line of the game, or
+    /// `None` if the stream was not read from the start.
+    pub fn line(&self) -> Option<u64> {
+        self.line
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Parses only the tag pairs. See [`Headers::from_pgn()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParsePgnError`] if a tag pair is not syntactically valid.
+    pub fn headers(&self) -> Result<Headers, ParsePgnError> {
+        Headers::from_pgn(&self.bytes)
+    }
+
+    /// Parses the entire game.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParsePgnError`] if the game is not syntactically valid.
+    pub fn game(&self) -> Result<Game, ParsePgnError> {
+        Game::from_ascii(&self.bytes)
+    }
+
+    /// Parses the entire game, like [`RawGame::game()`], but reports errors
+    /// with their position in the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImportError`] if the game is not syntactically valid.
+    pub fn import(&self) -> Result<Game, ImportError> {
+        self.game().map_err(|error| {
+            let start = error.span().start.min(self.bytes.len());
+            let lines = self.bytes[..start].iter().filter(|&&ch| ch == b'\n').count() as u64;
+            ImportError {
+                offset: self.offset + start as u64,
+                line: self.line.map(|line| line + lines),
+                error,
+            }
+        })
+    }
+}
+
+/// Splits a stream of PGN games into individual [`RawGame`]s, reading only
+/// one game at a time.
+///
+/// A new game starts with the first tag pair following movetext. Games
+/// without tag pairs can therefore not be told apart from the previous
+/// game.
+///
+/// Broken games do not affect the following games, so that bulk imports
+/// can skip them and carry on. In particular, a tag pair after a blank line
+/// ends an unterminated comment.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::pgn::Reader;
+///
+/// let pgn = b"[White \"Alice\"]\n\n1. e4 e5 1-0\n\n[White \"Bob\"]\n\n1. d4 d5 0-1\n";
+///
+/// let mut whites = Vec::new();
+/// for raw in Reader::new(&pgn[..]) {
+///     let headers = raw?.headers()?;
+///     whites.push(headers.get("White").unwrap_or("?").to_owned());
+/// }
+/// assert_eq!(whites, ["Alice", "Bob"]);
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+///
+/// Skipping broken games:
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::pgn::Reader;
+///
+/// let pgn = b"[Round \"1\"]\n\n1. e4 *\n\n[Round \"2\"]\n\n1. e4 (1... e5 *\n\n[Round \"3\"]\n\n1. d4 *\n";
+///
+/// let mut games = Vec::new();
+/// let mut errors = Vec::new();
+/// for raw in Reader::new(&pgn[..]) {
+///     match raw?.import() {
+///         Ok(game) => games.push(game),
+///         Err(err) => errors.push(err.to_string()),
+///     }
+/// }
+/// assert_eq!(games.len(), 2);
+/// assert_eq!(errors, ["unbalanced variation in pgn (line 7)"]);
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Reader<R> {
+    inner: R,
+    offset: u64,
+    line: Option<u64>,
+    pending: Vec<u8>,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(inner: R) -> Reader<R> {
+        Reader {
+            inner,
+            offset: 0,
+            line: Some(1),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Starts reading at `offset`, used to report the offsets of games
+    /// after seeking. Line numbers are unknown.
+    pub(super) fn with_offset(inner: R, offset: u64) -> Reader<R> {
+        Reader {
+            offset,
+            line: None,
+            ..Reader::new(inner)
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_game(&mut self) -> io::Result<Option<RawGame>> {
+        let offset = self.offset;
+        let line = self.line;
+        let mut bytes = Vec::new();
+        let mut in_movetext = false;
+        let mut in_comment = false;
+        let mut after_blank = false;
+
+        loop {
+            let line = if self.pending.is_empty() {
+                let mut line = Vec::new();
+                if self.inner.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+                line
+            } else {
+                mem::take(&mut self.pending)
+            };
+
+            let content = if line.starts_with(b"\xef\xbb\xbf") { &line[3..] } else { &line[..] };
+            let content = &content[content.iter().take_while(|ch| ch.is_ascii_whitespace()).count()..];
+
+            if content.starts_with(b"[") && (!in_comment || after_blank) {
+                // A tag pair after a blank line also ends an unterminated
+                // comment, so that it does not swallow the following games.
+                // The broken game is reported when it is parsed.
+                if in_movetext {
+                    self.pending = line;
+                    break;
+                }
+            } else if !in_comment && (content.is_empty() || content.starts_with(b"%")) {
+                // Blank line or escaped line.
+            } else {
+                in_movetext = true;
+                for &ch in content {
+                    match ch {
+                        b'}' if in_comment => in_comment = false,
+                        b'{' if !in_comment => in_comment = true,
+                        b';' if !in_comment => break,
+                        _ => (),
+                    }
+                }
+            }
+
+            after_blank = content.is_empty();
+
+            self.offset += line.len() as u64;
+            if line.ends_with(b"\n") {
+                self.line = self.line.map(|n| n + 1);
+            }
+            bytes.extend_from_slice(&line);
+        }
+
+        Ok(if bytes.iter().all(|ch| ch.is_ascii_whitespace()) {
+            None
+        } else {
+            Some(RawGame { offset, line, bytes })
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = io::Result<RawGame>;
+
+    fn next(&mut self) -> Option<io::Result<RawGame>> {
+        self.read_game().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::ParsePgnErrorKind;
+
+    #[test]
+    fn test_split_games() {
+        let pgn = b"[Event \"A\"]\n\n1. e4 { [Not a tag]\n[Still a comment] } e5 *\n\n\
+                    [Event \"B\"]\n[Site \"?\"]\n\n1. d4 ; [Event \"C\"]\n\n\
+                    [Event \"D\"]\n*";
+
+        let games: Vec<RawGame> = Reader::new(&pgn[..]).collect::<Result<_, _>>().expect("read games");
+        assert_eq!(games.len(), 3);
+
+        for game in &games {
+            let start = game.offset() as usize;
+            assert_eq!(&pgn[start..start + game.as_bytes().len()], game.as_bytes());
+        }
+
+        assert_eq!(games[0].game().expect("valid pgn").headers.get("Event"), Some("A"));
+        assert_eq!(games[1].headers().expect("valid tags").get("Event"), Some("B"));
+        assert_eq!(games[2].offset() as usize, pgn.len() - 13);
+        assert_eq!(games[2].as_bytes(), b"[Event \"D\"]\n*");
+    }
+
+    #[test]
+    fn test_import_error() {
+        let pgn = b"[Event \"A\"]\n\n1. e4 e5 *\n\n[Event \"B]\n\n1. d4 *\n\n[Event \"C\"]\n\n1. c4\n2. Zz9 *\n";
+
+        let results: Vec<_> = Reader::new(&pgn[..]).map(|raw| raw.expect("read").import()).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+
+        let err = results[1].as_ref().expect_err("broken tag");
+        assert_eq!(err.error().kind(), ParsePgnErrorKind::InvalidTag);
+        assert_eq!(err.line(), Some(5));
+        assert_eq!(err.offset(), 25);
+
+        let err = results[2].as_ref().expect_err("broken san");
+        assert_eq!(err.error().kind(), ParsePgnErrorKind::InvalidSan);
+        assert_eq!(err.line(), Some(12));
+        assert_eq!(&pgn[err.offset() as usize..][..3], b"Zz9");
+    }
+
+    #[test]
+    fn test_unterminated_comment() {
+        let pgn = b"[Event \"A\"]\n\n1. e4 { unterminated\n\n[Event \"B\"]\n\n1. d4 { closed\n\n} d5 *\n";
+
+        let results: Vec<_> = Reader::new(&pgn[..]).map(|raw| raw.expect("read").import()).collect();
+        assert_eq!(results.len(), 2);
+
+        let err = results[0].as_ref().expect_err("unterminated comment");
+        assert_eq!(err.error().kind(), ParsePgnErrorKind::InvalidComment);
+        assert_eq!(err.line(), Some(3));
+
+        let game = results[1].as_ref().expect("valid pgn");
+        assert_eq!(game.headers.get("Event"), Some("B"));
+        assert_eq!(game.mainline().count(), 2);
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(Reader::new(&b""[..]).count(), 0);
+        assert_eq!(Reader::new(&b"\n\n  \n"[..]).count(), 0);
+    }
+}
@@ -0,0 +1,142 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::position::Position;
+use crate::variants::VariantPosition;
+use super::{Game, Mainline, NodeId, StartingPositionError};
+
+/// Error when replaying the moves of a [`Game`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReplayError {
+    /// The starting position could not be set up.
+    StartingPosition(StartingPositionError),
+    /// The move leading to the node is illegal or ambiguous.
+    IllegalMove(NodeId),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ReplayError::StartingPosition(ref err) => err.fmt(f),
+            ReplayError::IllegalMove(_) => "illegal move in pgn".fmt(f),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn description(&self) -> &str {
+        match *self {
+            ReplayError::StartingPosition(_) => "invalid starting position in pgn",
+            ReplayError::IllegalMove(_) => "illegal move in pgn",
+        }
+    }
+}
+
+/// Iterator over the positions after each move of the main line of a
+/// [`Game`], created by [`Game::positions()`].
+///
+/// Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Positions<'a> {
+    game: &'a Game,
+    mainline: Mainline<'a>,
+    pos: Option<Result<VariantPosition, StartingPositionError>>,
+}
+
+impl Game {
+    /// Replays the main line from [`Game::starting_position()`], yielding
+    /// the position after each move. Zip with [`Game::mainline()`] to get the
+    /// corresponding nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::fen;
+    /// use shakmaty::pgn::Game;
+    ///
+    /// let game: Game = "1. e4 e5 2. Nf3 *".parse()?;
+    ///
+    /// let fens = game.positions()
+    ///     .map(|pos| pos.map(|pos| fen::epd(&pos)))
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(fens, [
+    ///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq -",
+    ///     "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq -",
+    ///     "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq -",
+    /// ]);
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    pub fn positions(&self) -> Positions<'_> {
+        Positions {
+            game: self,
+            mainline: self.mainline(),
+            pos: Some(self.starting_position()),
+        }
+    }
+}
+
+impl<'a> Iterator for Positions<'a> {
+    type Item = Result<VariantPosition, ReplayError>;
+
+    fn next(&mut self) -> Option<Result<VariantPosition, ReplayError>> {
+        let mut pos = match self.pos.take()? {
+            Ok(pos) => pos,
+            Err(err) => return Some(Err(ReplayError::StartingPosition(err))),
+        };
+
+        let node = self.mainline.next()?;
+        match self.game[node].san().and_then(|san| san.san.to_move(&pos).ok()) {
+            Some(m) => {
+                pos.play_unchecked(&m);
+                self.pos = Some(Ok(pos.clone()));
+                Some(Ok(pos))
+            }
+            None => Some(Err(ReplayError::IllegalMove(node))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let game: Game = "1. e4 e5 (1... c5) 2. Ke3 Nc6 *".parse().expect("valid pgn");
+        let results: Vec<_> = game.positions().collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[..2].iter().all(|pos| pos.is_ok()));
+        let ke3 = game.mainline().nth(2).expect("third move");
+        match results[2] {
+            Err(ReplayError::IllegalMove(node)) => assert_eq!(node, ke3),
+            _ => panic!("expected illegal move"),
+        }
+
+        let game: Game = "[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n*".parse().expect("valid pgn");
+        let results: Vec<_> = game.positions().collect();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(ReplayError::StartingPosition(StartingPositionError::IllegalPosition))));
+
+        let game = Game::new();
+        assert_eq!(game.positions().count(), 0);
+    }
+}
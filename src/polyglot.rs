@@ -0,0 +1,310 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Reading and building [Polyglot](http://hgm.nubati.net/book_format.html)
+//! opening books.
+//!
+//! A book is a sequence of 16 byte entries, sorted by the
+//! [`polyglot_hash()`](crate::zobrist::polyglot_hash) of the position.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! #
+//! use shakmaty::{Chess, Position};
+//! use shakmaty::polyglot::{self, BookBuilder};
+//! use shakmaty::san::San;
+//! use shakmaty::zobrist;
+//!
+//! let pos = Chess::default();
+//! let mut builder = BookBuilder::new();
+//! for (san, games) in &[("e4", 3), ("d4", 2), ("e4", 1)] {
+//!     let m = san.parse::<San>()?.to_move(&pos)?;
+//!     builder.add(zobrist::polyglot_hash(&pos), &m, *games);
+//! }
+//!
+//! let mut book = Vec::new();
+//! builder.write(&mut book)?;
+//! assert_eq!(book.len(), 2 * 16);
+//!
+//! let entries = polyglot::read_entries(&book[..])?;
+//! let best = polyglot::find(&entries, zobrist::polyglot_hash(&pos))[0];
+//! assert_eq!(polyglot::decode_move(&pos, best.raw_move), Some("e4".parse::<San>()?.to_move(&pos)?));
+//! #
+//! # Ok::<_, Box<dyn Error>>(())
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use crate::movelist::MoveList;
+use crate::position::Position;
+use crate::types::{Move, Role};
+
+/// An entry of a Polyglot book.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Entry {
+    /// The Polyglot hash of the position.
+    pub key: u64,
+    /// The move, as encoded by [`encode_move()`].
+    pub raw_move: u16,
+    /// Relative weight of the move in the position.
+    pub weight: u16,
+    /// Reserved for learning, usually `0`.
+    pub learn: u32,
+}
+
+impl Entry {
+    /// Decodes an entry from its big-endian representation.
+    pub fn from_bytes(bytes: [u8; 16]) -> Entry {
+        let mut key = [0; 8];
+        key.copy_from_slice(&bytes[..8]);
+        let mut learn = [0; 4];
+        learn.copy_from_slice(&bytes[12..]);
+        Entry {
+            key: u64::from_be_bytes(key),
+            raw_move: u16::from_be_bytes([bytes[8], bytes[9]]),
+            weight: u16::from_be_bytes([bytes[10], bytes[11]]),
+            learn: u32::from_be_bytes(learn),
+        }
+    }
+
+    /// The big-endian representation of the entry.
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.raw_move.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+}
+
+/// Encodes a move as in Polyglot books, or returns `None` for drops.
+///
+/// Castling moves are encoded as the king moving to the square of the
+/// rook, so this works for Chess960, too.
+pub fn encode_move(m: &Move) -> Option<u16> {
+    let (from, to) = match *m {
+        Move::Castle { king, rook } => (king, rook),
+        Move::Put { .. } => return None,
+        _ => (m.from()?, m.to()),
+    };
+    let promotion = match m.promotion() {
+        None => 0,
+        Some(Role::Knight) => 1,
+        Some(Role::Bishop) => 2,
+        Some(Role::Rook) => 3,
+        Some(_) => 4,
+    };
+    Some(promotion << 12 | u16::from(from) << 6 | u16::from(to))
+}
+
+/// Decodes a move from a Polyglot book in the context of a position, or
+/// returns `None` if it is not legal.
+pub fn decode_move<P: Position>(pos: &P, raw_move: u16) -> Option<Move> {
+    let mut legals = MoveList::new();
+    pos.legal_moves(&mut legals);
+    legals.into_iter().find(|m| encode_move(m) == Some(raw_move))
+}
+
+/// Reads all entries of a book.
+///
+/// # Errors
+///
+/// Returns any I/O error encountered while reading, or an error of kind
+/// [`io::ErrorKind::InvalidData`] if the book ends with an incomplete
+/// entry.
+pub fn read_entries<R: Read>(mut r: R) -> io::Result<Vec<Entry>> {
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)?;
+    if bytes.len() % 16 != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "incomplete polyglot entry"));
+    }
+    Ok(bytes.chunks_exact(16).map(|chunk| {
+        let mut entry = [0; 16];
+        entry.copy_from_slice(chunk);
+        Entry::from_bytes(entry)
+    }).collect())
+}
+
+/// The entries for the position with the Polyglot hash `key`, given all
+/// entries of a book, sorted by key.
+pub fn find(entries: &[Entry], key: u64) -> &[Entry] {
+    let start = entries
+        .binary_search_by(|entry| if entry.key < key { Ordering::Less } else { Ordering::Greater })
+        .unwrap_or_else(|idx| idx);
+    let len = entries[start..].iter().take_while(|entry| entry.key == key).count();
+    &entries[start..start + len]
+}
+
+/// Aggregates weighted moves, e.g. harvested from games, into a book.
+#[derive(Clone, Debug, Default)]
+pub struct BookBuilder {
+    weights: HashMap<u64, Vec<(u16, u64)>>,
+}
+
+impl BookBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> BookBuilder {
+        BookBuilder::default()
+    }
+
+    /// Adds `weight` to the move `m` in the position with the Polyglot
+    /// hash `key`. Drops are ignored.
+    pub fn add(&mut self, key: u64, m: &Move, weight: u32) {
+        let raw_move = match encode_move(m) {
+            Some(raw_move) => raw_move,
+            None => return,
+        };
+        let moves = self.weights.entry(key).or_default();
+        match moves.iter_mut().find(|(other, _)| *other == raw_move) {
+            Some((_, total)) => *total += u64::from(weight),
+            None => moves.push((raw_move, u64::from(weight))),
+        }
+    }
+
+    /// The number of positions in the book. Each position can have
+    /// multiple entries, one for each move.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Checks if no moves have been added.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// The entries of the book, sorted by key and then by descending
+    /// weight.
+    ///
+    /// Weights are scaled down per position if they do not fit into 16
+    /// bits. Moves are kept with at least weight `1`.
+    pub fn entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        for (&key, moves) in &self.weights {
+            let max = moves.iter().map(|&(_, weight)| weight).max().unwrap_or(0);
+            for &(raw_move, weight) in moves {
+                let weight = if max > u64::from(u16::max_value()) {
+                    (weight * u64::from(u16::max_value()) / max).max(1)
+                } else {
+                    weight
+                };
+                entries.push(Entry {
+                    key,
+                    raw_move,
+                    weight: weight as u16,
+                    learn: 0,
+                });
+            }
+        }
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)).then(a.raw_move.cmp(&b.raw_move)));
+        entries
+    }
+
+    /// Writes the book in binary format.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error encountered while writing.
+    pub fn write<W: Write>(&self, mut w: W) -> io::Result<()> {
+        for entry in self.entries() {
+            w.write_all(&entry.to_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::Square;
+
+    #[test]
+    fn test_encode_move() {
+        let e4 = Move::Normal { role: Role::Pawn, from: Square::E2, capture: None, to: Square::E4, promotion: None };
+        assert_eq!(encode_move(&e4), Some(0x031c));
+
+        let castle = Move::Castle { king: Square::E1, rook: Square::H1 };
+        assert_eq!(encode_move(&castle), Some(0x0107));
+
+        let promotion = Move::Normal { role: Role::Pawn, from: Square::A7, capture: None, to: Square::A8, promotion: Some(Role::Queen) };
+        assert_eq!(encode_move(&promotion), Some(0x4c38));
+
+        assert_eq!(encode_move(&Move::Put { role: Role::Knight, to: Square::F3 }), None);
+    }
+
+    #[test]
+    fn test_builder() {
+        let e4 = Move::Normal { role: Role::Pawn, from: Square::E2, capture: None, to: Square::E4, promotion: None };
+        let d4 = Move::Normal { role: Role::Pawn, from: Square::D2, capture: None, to: Square::D4, promotion: None };
+
+        let mut builder = BookBuilder::new();
+        builder.add(2, &e4, 1);
+        builder.add(1, &e4, 100_000);
+        builder.add(1, &d4, 200_000);
+        builder.add(1, &e4, 100_000);
+        builder.add(1, &Move::Put { role: Role::Pawn, to: Square::E4 }, 1);
+        assert_eq!(builder.len(), 2);
+
+        let entries = builder.entries();
+        assert_eq!(entries.iter().map(|e| (e.key, e.weight)).collect::<Vec<_>>(), [(1, 65535), (1, 65535), (2, 1)]);
+        assert_eq!(entries[0].raw_move, encode_move(&e4).expect("encodable").min(encode_move(&d4).expect("encodable")));
+
+        let mut book = Vec::new();
+        builder.write(&mut book).expect("write");
+        assert_eq!(book.len(), 48);
+        let mut first = [0; 16];
+        first.copy_from_slice(&book[..16]);
+        assert_eq!(Entry::from_bytes(first), entries[0]);
+    }
+
+    #[test]
+    fn test_book_entry() {
+        use crate::position::Chess;
+        use crate::uci::Uci;
+        use crate::zobrist::polyglot_hash;
+
+        let mut pos = Chess::default();
+        let mut builder = BookBuilder::new();
+        assert!(builder.is_empty());
+        for uci in &["e2e4", "d7d5"] {
+            let m = uci.parse::<Uci>().expect("valid uci").to_move(&pos).expect("legal");
+            builder.add(polyglot_hash(&pos), &m, 2);
+            pos.play_unchecked(&m);
+        }
+
+        let mut book = Vec::new();
+        builder.write(&mut book).expect("write");
+        assert_eq!(book, [
+            0x46, 0x3b, 0x96, 0x18, 0x16, 0x91, 0xfc, 0x9c, 0x03, 0x1c, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, // start: e4
+            0x82, 0x3c, 0x9b, 0x50, 0xfd, 0x11, 0x41, 0x96, 0x0c, 0xe3, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, // 1. e4: d5
+        ]);
+
+        let entries = read_entries(&book[..]).expect("read");
+        assert_eq!(entries, builder.entries());
+        assert_eq!(find(&entries, 0x823c9b50fd114196).len(), 1);
+        assert!(find(&entries, 0).is_empty());
+
+        let start = find(&entries, polyglot_hash(&Chess::default()));
+        let e4 = decode_move(&Chess::default(), start[0].raw_move).expect("legal");
+        assert_eq!(e4, Move::Normal { role: Role::Pawn, from: Square::E2, capture: None, to: Square::E4, promotion: None });
+
+        assert!(read_entries(&book[..20]).is_err());
+    }
+}
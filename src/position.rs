@@ -17,20 +17,59 @@
 use std::fmt;
 use std::error::Error;
 use std::num::NonZeroU32;
+use std::str::FromStr;
 
 use bitflags::bitflags;
 
 use crate::attacks;
+use crate::zobrist;
 use crate::board::Board;
 use crate::bitboard::Bitboard;
 use crate::square::{Rank, Square};
-use crate::types::{Black, CastlingSide, CastlingMode, Color, Move, Piece, RemainingChecks, Role, White};
-use crate::material::{Material, MaterialSide};
+use crate::types::{Black, CastlingSide, CastlingMode, Color, Move, Piece, PromotionMode, RemainingChecks, Role, White};
+use crate::material::Material;
 use crate::setup::{Castles, EpSquare, Setup, SwapTurn};
-use crate::movelist::{ArrayVecExt, MoveList};
+use crate::movelist::{ArrayVecExt, MoveGen, MoveList};
+
+/// Error when parsing an invalid outcome.
+#[derive(Clone, Debug)]
+pub struct ParseOutcomeError;
+
+impl fmt::Display for ParseOutcomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid outcome".fmt(f)
+    }
+}
+
+impl Error for ParseOutcomeError {
+    fn description(&self) -> &str {
+        "invalid outcome"
+    }
+}
 
 /// Outcome of a game.
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+///
+/// Written as `1-0`, `0-1` or `1/2-1/2`. Games that are not over (or whose
+/// outcome is unknown, `*` in PGN) are represented as `None`, like in
+/// [`Position::outcome()`].
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::{Color, Outcome};
+///
+/// let outcome: Outcome = "0-1".parse()?;
+/// assert_eq!(outcome, Outcome::Decisive { winner: Color::Black });
+/// assert_eq!(Outcome::Draw.to_string(), "1/2-1/2");
+///
+/// assert!("*".parse::<Outcome>().is_err());
+/// assert_eq!(Outcome::from_ascii_opt(b"*")?, None);
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum Outcome {
     Decisive { winner: Color },
     Draw,
@@ -43,23 +82,64 @@ impl Outcome {
             Outcome::Draw => None,
         }
     }
+
+    /// Parses `1-0`, `0-1` or `1/2-1/2`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseOutcomeError`] for any other input.
+    pub fn from_ascii(outcome: &[u8]) -> Result<Outcome, ParseOutcomeError> {
+        match outcome {
+            b"1-0" => Ok(Outcome::Decisive { winner: White }),
+            b"0-1" => Ok(Outcome::Decisive { winner: Black }),
+            b"1/2-1/2" => Ok(Outcome::Draw),
+            _ => Err(ParseOutcomeError),
+        }
+    }
+
+    /// Like [`Outcome::from_ascii()`], but also accepts `*` for an unknown
+    /// outcome.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseOutcomeError`] for any other input.
+    pub fn from_ascii_opt(outcome: &[u8]) -> Result<Option<Outcome>, ParseOutcomeError> {
+        match outcome {
+            b"*" => Ok(None),
+            _ => Outcome::from_ascii(outcome).map(Some),
+        }
+    }
+
+    /// Writes an optional outcome, using `*` for `None`.
+    pub fn to_str_opt(outcome: Option<Outcome>) -> &'static str {
+        match outcome {
+            Some(Outcome::Decisive { winner: White }) => "1-0",
+            Some(Outcome::Decisive { winner: Black }) => "0-1",
+            Some(Outcome::Draw) => "1/2-1/2",
+            None => "*",
+        }
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = ParseOutcomeError;
+
+    fn from_str(outcome: &str) -> Result<Outcome, ParseOutcomeError> {
+        Outcome::from_ascii(outcome.as_bytes())
+    }
 }
 
 impl fmt::Display for Outcome {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", match *self {
-            Outcome::Decisive { winner: White } => "1-0",
-            Outcome::Decisive { winner: Black } => "0-1",
-            Outcome::Draw => "1/2-1/2",
-        })
+        f.write_str(Outcome::to_str_opt(Some(*self)))
     }
 }
 
 /// Error when trying to play an illegal move.
 #[derive(Debug)]
 pub struct PlayError<'a, P> {
-    m: &'a Move,
-    inner: P,
+    pub(crate) m: &'a Move,
+    pub(crate) inner: P,
 }
 
 impl<'a, P> PlayError<'a, P> {
@@ -92,8 +172,9 @@ bitflags! {
         /// A player has too many kings.
         const TOO_MANY_KINGS = 1 << 2;
 
-        /// There are pawns on the backrank. Only [`Horde`] allows players to
-        /// have pawns on their own backrank.
+        /// There are pawns on the backrank. Only
+        /// [`Horde`](crate::variants::Horde) allows players to have pawns on
+        /// their own backrank.
         const PAWNS_ON_BACKRANK = 1 << 3;
 
         /// Some castling rights are invalid.
@@ -147,7 +228,7 @@ impl<P> PositionError<P> {
         }
     }
 
-    fn strict(self) -> Result<P, Self> {
+    pub(crate) fn strict(self) -> Result<P, Self> {
         self.ignore(PositionErrorKinds::empty())
     }
 
@@ -166,6 +247,13 @@ impl<P> PositionError<P> {
         self.ignore(PositionErrorKinds::IMPOSSIBLE_MATERIAL)
     }
 
+    /// Get the position, even if one or both kings are missing, as is common
+    /// in problems and studies. A side without a king is never in check and
+    /// cannot castle, but otherwise moves normally.
+    pub fn ignore_missing_king(self) -> Result<P, Self> {
+        self.ignore(PositionErrorKinds::MISSING_KING)
+    }
+
     pub fn kinds(&self) -> PositionErrorKinds {
         self.errors
     }
@@ -203,6 +291,38 @@ pub trait FromSetup: Sized {
     /// is actually reachable with a series of legal moves from the starting
     /// position.
     fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Self, PositionError<Self>>;
+
+    /// Set up a position and also determine if the game is already over,
+    /// for example because the side to move is checkmated or stalemated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PositionError`] if the setup does not meet basic validity
+    /// requirements, just like [`FromSetup::from_setup()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::{CastlingMode, Chess, Color, FromSetup, Outcome};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let fen: Fen = "7k/5KQ1/8/8/8/8/8/8 b - - 0 1".parse()?;
+    /// let (_, outcome) = Chess::from_setup_with_outcome(&fen, CastlingMode::Standard)?;
+    /// assert_eq!(outcome, Some(Outcome::Decisive { winner: Color::White }));
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    fn from_setup_with_outcome(setup: &dyn Setup, mode: CastlingMode) -> Result<(Self, Option<Outcome>), PositionError<Self>>
+    where
+        Self: Position,
+    {
+        Self::from_setup(setup, mode).map(|pos| {
+            let outcome = pos.outcome();
+            (pos, outcome)
+        })
+    }
 }
 
 /// A legal chess or chess variant position. See [`Chess`] for a concrete
@@ -211,6 +331,40 @@ pub trait Position: Setup {
     /// Collects all legal moves in an existing buffer.
     fn legal_moves(&self, moves: &mut MoveList);
 
+    /// Collects legal moves in an existing buffer, like
+    /// [`Position::legal_moves()`], but only with the promotions selected
+    /// by `mode`.
+    ///
+    /// The positions in this crate pass `mode` down to move generation, so
+    /// that unwanted promotions are not generated in the first place. The
+    /// default implementation filters the result of
+    /// [`Position::legal_moves()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, CastlingMode, MoveList, Position, PromotionMode};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let pos: Chess = "8/P6k/8/8/8/8/8/K7 w - - 0 1".parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    ///
+    /// let mut moves = MoveList::new();
+    /// pos.legal_moves_with_promotions(PromotionMode::All, &mut moves);
+    /// assert_eq!(moves.len(), 4 + 3);
+    ///
+    /// moves.clear();
+    /// pos.legal_moves_with_promotions(PromotionMode::QueenOnly, &mut moves);
+    /// assert_eq!(moves.len(), 1 + 3);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
+        self.legal_moves(moves);
+        if mode != PromotionMode::All {
+            moves.retain(|m| m.promotion().map_or(true, |role| mode.allows(role)));
+        }
+    }
+
     /// Generates a subset of legal moves: All piece moves and drops of type
     /// `role` to the square `to`, excluding castling moves.
     fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
@@ -221,7 +375,7 @@ pub trait Position: Setup {
     /// Generates legal castling moves.
     fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
         self.legal_moves(moves);
-        moves.retain(|m| m.castling_side().map_or(false, |s| side == s));
+        moves.retain(|m| m.castling_side() == Some(side));
     }
 
     /// Generates en passant moves.
@@ -242,6 +396,72 @@ pub trait Position: Setup {
         moves.retain(|m| m.is_promotion());
     }
 
+    /// Generates legal moves that get the king out of check. Generates
+    /// nothing if the king is not in check.
+    ///
+    /// Useful if it is already known that the king is in check, for
+    /// example from [`Position::checkers()`].
+    fn evasion_moves(&self, moves: &mut MoveList) {
+        if self.checkers().is_empty() {
+            moves.clear();
+        } else {
+            self.legal_moves(moves);
+        }
+    }
+
+    /// Generates quiet moves, i.e. moves that are neither captures nor
+    /// promotions. Castling moves and drops are quiet.
+    fn quiet_moves(&self, moves: &mut MoveList) {
+        self.legal_moves(moves);
+        moves.retain(|m| !m.is_capture() && !m.is_promotion());
+    }
+
+    /// Generates legal moves from a square in `from` to a square in `to`,
+    /// for example all moves landing on e5, or all knight moves.
+    ///
+    /// Castling moves are represented as king moves to the rook square.
+    /// Drops have no origin square and are only included if `from` is
+    /// [`Bitboard::ALL`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Chess, MoveList, Position, Role, Setup, Square};
+    ///
+    /// let pos = Chess::default();
+    /// let mut moves = MoveList::new();
+    ///
+    /// pos.legal_moves_masked(pos.our(Role::Knight), Bitboard::ALL, &mut moves);
+    /// assert_eq!(moves.len(), 4);
+    ///
+    /// pos.legal_moves_masked(Bitboard::ALL, Bitboard::from_square(Square::E4), &mut moves);
+    /// assert_eq!(moves.len(), 1);
+    /// ```
+    fn legal_moves_masked(&self, from: Bitboard, to: Bitboard, moves: &mut MoveList) {
+        self.legal_moves(moves);
+        filter_masked(from, to, moves);
+    }
+
+    /// Counts the legal moves.
+    ///
+    /// Implementations may count moves without generating them, which is
+    /// faster than [`Position::legal_moves()`].
+    fn count_legal_moves(&self) -> usize {
+        let mut moves = MoveList::new();
+        self.legal_moves(&mut moves);
+        moves.len()
+    }
+
+    /// Tests if there are any legal moves.
+    ///
+    /// Implementations may return as soon as a legal move is found, which
+    /// is faster than generating all legal moves.
+    fn has_legal_moves(&self) -> bool {
+        let mut moves = MoveList::new();
+        self.legal_moves(&mut moves);
+        !moves.is_empty()
+    }
+
     /// Tests if a move is irreversible.
     ///
     /// In standard chess pawn moves, captures, moves that destroy castling
@@ -270,6 +490,73 @@ pub trait Position: Setup {
         self.board().attacks_to(square, attacker, occupied)
     }
 
+    /// Tests if the legal move `m` gives check, without playing it.
+    ///
+    /// Direct checks are detected with the attacks of the moved piece from
+    /// its destination, and discovered checks with the sliders that attack
+    /// the opponent king after the move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{CastlingMode, Chess, Move, Position, Role, Square};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let pos: Chess = "4k3/8/8/8/8/8/4N3/4RK2 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    ///
+    /// // Discovered check.
+    /// assert!(pos.gives_check(&Move::Normal {
+    ///     role: Role::Knight,
+    ///     from: Square::E2,
+    ///     capture: None,
+    ///     to: Square::C3,
+    ///     promotion: None,
+    /// }));
+    ///
+    /// assert!(!pos.gives_check(&Move::Normal {
+    ///     role: Role::King,
+    ///     from: Square::F1,
+    ///     capture: None,
+    ///     to: Square::F2,
+    ///     promotion: None,
+    /// }));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    fn gives_check(&self, m: &Move) -> bool {
+        let their_king = match self.board().king_of(!self.turn()) {
+            Some(king) => king,
+            None => return false,
+        };
+
+        let mut occupied = self.board().occupied();
+        let (role, to, vacated) = match *m {
+            Move::Normal { role, from, to, promotion, .. } =>
+                (promotion.unwrap_or(role), to, Bitboard::from_square(from)),
+            Move::EnPassant { from, to } => {
+                occupied.discard(Square::from_coords(to.file(), from.rank()));
+                (Role::Pawn, to, Bitboard::from_square(from))
+            }
+            Move::Castle { king, rook } => {
+                let side = CastlingSide::from_queen_side(rook < king);
+                let vacated = Bitboard::from_square(king) | Bitboard::from_square(rook);
+                occupied = (occupied & !vacated).with(Square::from_coords(side.king_to_file(), king.rank()));
+                (Role::Rook, Square::from_coords(side.rook_to_file(), rook.rank()), vacated)
+            }
+            Move::Put { role, to } => (role, to, Bitboard(0)),
+        };
+        occupied = (occupied & !vacated).with(to);
+
+        if attacks::attacks(to, role.of(self.turn()), occupied).contains(their_king) {
+            return true;
+        }
+
+        let sliders = (attacks::rook_attacks(their_king, occupied) & self.board().rooks_and_queens()) |
+                      (attacks::bishop_attacks(their_king, occupied) & self.board().bishops_and_queens());
+        (sliders & self.us() & !vacated).any()
+    }
+
     /// Castling paths and unmoved rooks.
     fn castles(&self) -> &Castles;
 
@@ -297,6 +584,11 @@ pub trait Position: Setup {
     /// positions, is there a position with the same material configuration
     /// where `color` can win with a series of legal moves. If not, then
     /// `color` has insufficient winning material.
+    ///
+    /// Each variant applies its own winning conditions. For example, in
+    /// Atomic chess a single knight can win by exploding an enemy piece
+    /// next to the opponent's king, and in Crazyhouse almost any material
+    /// is sufficient, because captured pieces can be dropped back.
     fn has_insufficient_material(&self, color: Color) -> bool;
 
     /// Tests special variant winning, losing and drawing conditions.
@@ -331,6 +623,36 @@ pub trait Position: Setup {
         Self::from_setup(&SwapTurn(self), mode)
     }
 
+    /// Iterates over legal moves, generating them lazily in stages. See
+    /// [`MoveGen`].
+    fn moves_iter(&self) -> MoveGen<'_, Self>
+    where
+        Self: Sized,
+    {
+        MoveGen::new(self)
+    }
+
+    /// Generates the legal moves of the piece on `square`, including
+    /// castling moves if it is our king.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, MoveList, Position, Square};
+    ///
+    /// let pos = Chess::default();
+    /// let mut moves = MoveList::new();
+    ///
+    /// pos.legal_moves_from(Square::G1, &mut moves);
+    /// assert_eq!(moves.len(), 2);
+    ///
+    /// pos.legal_moves_from(Square::E8, &mut moves);
+    /// assert!(moves.is_empty());
+    /// ```
+    fn legal_moves_from(&self, square: Square, moves: &mut MoveList) {
+        self.legal_moves_masked(Bitboard::from_square(square), Bitboard::ALL, moves);
+    }
+
     /// Generates legal moves.
     fn legals(&self) -> MoveList {
         let mut legals = MoveList::new();
@@ -338,19 +660,48 @@ pub trait Position: Setup {
         legals
     }
 
-    /// Tests a move for legality.
-    fn is_legal(&self, m: &Move) -> bool {
-        let mut moves = MoveList::new();
+    /// Generates the legal moves that could be confused with `m` in SAN:
+    /// All moves and drops of the same role to the same square, or the
+    /// castling moves to the same side. If `m` is legal, it is among them.
+    ///
+    /// This covers all kinds of moves with a single lookup, including
+    /// pawn pushes, pawn captures, en passant, castling and drops.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Move, MoveList, Position, Role, Square};
+    ///
+    /// let pos = Chess::default();
+    /// let m = Move::Normal {
+    ///     role: Role::Pawn,
+    ///     from: Square::E2,
+    ///     capture: None,
+    ///     to: Square::E4,
+    ///     promotion: None,
+    /// };
+    ///
+    /// let mut moves = MoveList::new();
+    /// pos.move_candidates(&m, &mut moves);
+    /// assert_eq!(moves.as_slice(), [m]);
+    /// ```
+    fn move_candidates(&self, m: &Move, moves: &mut MoveList) {
         match *m {
             Move::Normal { role, to, .. } | Move::Put { role, to } =>
-                self.san_candidates(role, to, &mut moves),
+                self.san_candidates(role, to, moves),
             Move::EnPassant { to, .. } =>
-                self.san_candidates(Role::Pawn, to, &mut moves),
+                self.san_candidates(Role::Pawn, to, moves),
             Move::Castle { king, rook } if king.file() < rook.file() =>
-                self.castling_moves(CastlingSide::KingSide, &mut moves),
+                self.castling_moves(CastlingSide::KingSide, moves),
             Move::Castle { .. } =>
-                self.castling_moves(CastlingSide::QueenSide, &mut moves),
+                self.castling_moves(CastlingSide::QueenSide, moves),
         }
+    }
+
+    /// Tests a move for legality.
+    fn is_legal(&self, m: &Move) -> bool {
+        let mut moves = MoveList::new();
+        self.move_candidates(m, &mut moves);
         moves.contains(m)
     }
 
@@ -361,6 +712,62 @@ pub trait Position: Setup {
         })
     }
 
+    /// Bitboard of our pieces that are absolutely pinned to our king by
+    /// an enemy slider.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, CastlingMode, Chess, Position, Square};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let pos: Chess = "4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1".parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    ///
+    /// assert_eq!(pos.pinned(), Bitboard::from_square(Square::E2));
+    /// assert!(pos.pin_ray(Square::E2).contains(Square::E8));
+    /// assert_eq!(pos.pin_ray(Square::E1), Bitboard::ALL);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    fn pinned(&self) -> Bitboard {
+        self.our(Role::King).first().map_or(Bitboard(0), |king| {
+            slider_blockers(self.board(), self.them(), king) & self.us()
+        })
+    }
+
+    /// Gets the line along which the piece on `square` is pinned, through
+    /// our king and the pinning piece. Returns [`Bitboard::ALL`] if the
+    /// piece is not pinned, so the result can always be used to mask its
+    /// targets.
+    fn pin_ray(&self, square: Square) -> Bitboard {
+        match self.our(Role::King).first() {
+            Some(king) if self.pinned().contains(square) => attacks::ray(king, square),
+            _ => Bitboard::ALL,
+        }
+    }
+
+    /// Bitboard of our pieces that are the only blocker between one of our
+    /// sliders and the enemy king. Moving such a piece off the line gives
+    /// discovered check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, CastlingMode, Chess, Position, Square};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let pos: Chess = "4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1".parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    ///
+    /// assert_eq!(pos.discovered_check_candidates(), Bitboard::from_square(Square::E4));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    fn discovered_check_candidates(&self) -> Bitboard {
+        self.their(Role::King).first().map_or(Bitboard(0), |king| {
+            slider_blockers(self.board(), self.us(), king) & self.us()
+        })
+    }
+
     /// Tests if the king is in check.
     fn is_check(&self) -> bool {
         self.checkers().any()
@@ -372,9 +779,7 @@ pub trait Position: Setup {
             return false;
         }
 
-        let mut legals = MoveList::new();
-        self.legal_moves(&mut legals);
-        legals.is_empty()
+        !self.has_legal_moves()
     }
 
     /// Tests for stalemate.
@@ -382,9 +787,7 @@ pub trait Position: Setup {
         if !self.checkers().is_empty() || self.is_variant_end() {
             false
         } else {
-            let mut legals = MoveList::new();
-            self.legal_moves(&mut legals);
-            legals.is_empty()
+            !self.has_legal_moves()
         }
     }
 
@@ -399,12 +802,38 @@ pub trait Position: Setup {
     /// [insufficient material](Position::is_insufficient_material) or
     /// [variant end](Position::is_variant_end).
     fn is_game_over(&self) -> bool {
-        let mut legals = MoveList::new();
-        self.legal_moves(&mut legals);
-        legals.is_empty() || self.is_insufficient_material()
+        !self.has_legal_moves() || self.is_insufficient_material()
+    }
+
+    /// The Zobrist hash of the position, a 64-bit key for transposition
+    /// tables and databases. See [`zobrist`](crate::zobrist) for details.
+    fn zobrist_hash(&self) -> u64 {
+        zobrist::hash(self)
     }
 
     /// The outcome of the game, or `None` if the game is not over.
+    ///
+    /// Variant end conditions take precedence. Otherwise the game is won
+    /// by [checkmate](Position::is_checkmate()), and drawn by
+    /// [stalemate](Position::is_stalemate()) or
+    /// [insufficient material](Position::is_insufficient_material()).
+    /// Draws by the [seventy-five-move rule](Position::is_seventyfive_moves())
+    /// and by repetition are not considered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{CastlingMode, Chess, Color, Outcome, Position};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let pos: Chess = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"
+    ///     .parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    /// assert_eq!(pos.outcome(), Some(Outcome::Decisive { winner: Color::Black }));
+    ///
+    /// assert_eq!(Chess::default().outcome(), None);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
     fn outcome(&self) -> Option<Outcome> {
         self.variant_outcome().or_else(|| {
             if self.is_checkmate() {
@@ -417,6 +846,104 @@ pub trait Position: Setup {
         })
     }
 
+    /// Tests if a draw can be claimed under the fifty-move rule, i.e. no
+    /// pawn has moved and no capture has been made in the last 50 moves
+    /// of each side, and the last move did not deliver checkmate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{CastlingMode, Chess, Position};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let pos: Chess = "8/5k2/8/8/8/8/3KR3/8 w - - 100 80"
+    ///     .parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    /// assert!(pos.is_fifty_moves());
+    /// assert!(!pos.is_seventyfive_moves());
+    ///
+    /// // Checkmate takes precedence.
+    /// let pos: Chess = "4R2k/6pp/8/8/8/8/8/6K1 b - - 150 99"
+    ///     .parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    /// assert!(!pos.is_fifty_moves());
+    /// assert!(!pos.is_seventyfive_moves());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    fn is_fifty_moves(&self) -> bool {
+        self.halfmoves() >= 100 && !self.is_checkmate()
+    }
+
+    /// Tests if the game is drawn under the seventy-five-move rule, i.e. no
+    /// pawn has moved and no capture has been made in the last 75 moves of
+    /// each side, and the last move did not deliver checkmate. Unlike the
+    /// [fifty-move rule](Position::is_fifty_moves()), this draw does not
+    /// have to be claimed.
+    fn is_seventyfive_moves(&self) -> bool {
+        self.halfmoves() >= 150 && !self.is_checkmate()
+    }
+
+    /// The outcome of the game if `color` runs out of time: A draw if the
+    /// opponent [has insufficient winning material](Position::has_insufficient_material),
+    /// otherwise a win for the opponent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{CastlingMode, Chess, Color, Outcome, Position};
+    /// use shakmaty::fen::Fen;
+    ///
+    /// let pos: Chess = "8/5k2/8/8/8/2B5/3K4/4r3 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    /// assert_eq!(pos.outcome_on_timeout(Color::White), Outcome::Decisive { winner: Color::Black });
+    /// assert_eq!(pos.outcome_on_timeout(Color::Black), Outcome::Draw);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    fn outcome_on_timeout(&self, color: Color) -> Outcome {
+        if self.has_insufficient_material(!color) {
+            Outcome::Draw
+        } else {
+            Outcome::Decisive { winner: !color }
+        }
+    }
+
+    /// Tests if the legal move `m` delivers checkmate.
+    ///
+    /// See [`find_mates_in_one()`](crate::find_mates_in_one) to collect all
+    /// such moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position, Role, Move, Square};
+    /// use shakmaty::fen::Fen;
+    /// use shakmaty::CastlingMode;
+    ///
+    /// let pos: Chess = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".parse::<Fen>()?
+    ///     .position(CastlingMode::Standard)?;
+    ///
+    /// assert!(pos.is_mate_in_one(&Move::Normal {
+    ///     role: Role::Rook,
+    ///     from: Square::A1,
+    ///     capture: None,
+    ///     to: Square::A8,
+    ///     promotion: None,
+    /// }));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    fn is_mate_in_one(&self, m: &Move) -> bool
+    where
+        Self: Sized + Clone,
+    {
+        if !self.gives_check(m) {
+            return false;
+        }
+        let mut child = self.clone();
+        child.play_unchecked(m);
+        child.is_checkmate()
+    }
+
     /// Plays a move.
     ///
     /// # Errors
@@ -439,6 +966,10 @@ pub trait Position: Setup {
 }
 
 /// A standard Chess position.
+///
+/// Positions compare and hash equal if they are equal for the purposes of
+/// repetitions, ignoring move counters. So they can be used as keys of a
+/// `HashMap`, e.g. for transposition tables.
 #[derive(Clone, Debug)]
 pub struct Chess {
     board: Board,
@@ -450,13 +981,7 @@ pub struct Chess {
 }
 
 impl Chess {
-    fn gives_check(&self, m: &Move) -> bool {
-        let mut pos = self.clone();
-        pos.play_unchecked(m);
-        pos.is_check()
-    }
-
-    fn from_setup_unchecked(setup: &dyn Setup, mode: CastlingMode) -> (Chess, PositionErrorKinds) {
+    pub(crate) fn from_setup_unchecked(setup: &dyn Setup, mode: CastlingMode) -> (Chess, PositionErrorKinds) {
         let mut errors = PositionErrorKinds::empty();
         let board = setup.board().clone();
         let turn = setup.turn();
@@ -511,6 +1036,7 @@ impl Setup for Chess {
     fn turn(&self) -> Color { self.turn }
     fn castling_rights(&self) -> Bitboard { self.castles.castling_rights() }
     fn ep_square(&self) -> Option<Square> { self.ep_square.and_then(|ep| relevant_ep(ep, self)) }
+    fn maybe_ep_square(&self) -> Option<Square> { self.ep_square.map(Square::from) }
     fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
     fn halfmoves(&self) -> u32 { self.halfmoves }
     fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
@@ -535,1124 +1061,262 @@ impl Position for Chess {
     }
 
     fn legal_moves(&self, moves: &mut MoveList) {
+        self.legal_moves_with_promotions(PromotionMode::All, moves);
+    }
+
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
         moves.clear();
 
-        let king = self.board().king_of(self.turn()).expect("king in standard chess");
+        let king = self.board().king_of(self.turn());
 
         let has_ep = gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
 
         let checkers = self.checkers();
         if checkers.is_empty() {
             let target = !self.us();
-            gen_non_king(self, target, moves);
-            gen_safe_king(self, king, target, moves);
-            gen_castling_moves(self, &self.castles, king, CastlingSide::KingSide, moves);
-            gen_castling_moves(self, &self.castles, king, CastlingSide::QueenSide, moves);
-        } else {
-            evasions(self, king, checkers, moves);
+            gen_non_king(self, target, mode, moves);
+            if let Some(king) = king {
+                gen_safe_king(self, king, target, moves);
+                gen_castling_moves(self, &self.castles, king, CastlingSide::KingSide, moves);
+                gen_castling_moves(self, &self.castles, king, CastlingSide::QueenSide, moves);
+            }
+        } else if let Some(king) = king {
+            evasions(self, king, checkers, mode, moves);
         }
 
-        let blockers = slider_blockers(self.board(), self.them(), king);
-        if blockers.any() || has_ep {
-            moves.swap_retain(|m| is_safe(self, king, m, blockers));
+        if let Some(king) = king {
+            let blockers = slider_blockers(self.board(), self.them(), king);
+            if blockers.any() || has_ep {
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
+            }
         }
     }
 
-    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
-        moves.clear();
-        let king = self.board().king_of(self.turn()).expect("king in standard chess");
-        gen_castling_moves(self, &self.castles, king, side, moves);
-    }
+    fn count_legal_moves(&self) -> usize {
+        let king = match self.board().king_of(self.turn()) {
+            Some(king) if self.checkers().is_empty() => king,
+            _ => return self.legals().len(),
+        };
 
-    fn en_passant_moves(&self, moves: &mut MoveList) {
-        moves.clear();
+        let occupied = self.board().occupied();
+        let target = !self.us();
+        let pinned = slider_blockers(self.board(), self.them(), king) & self.us();
+        let seventh = Bitboard::relative_rank(self.turn(), Rank::Seventh);
 
-        if gen_en_passant(self.board(), self.turn(), self.ep_square, moves) {
-            let king = self.board().king_of(self.turn()).expect("king in standard chess");
-            let blockers = slider_blockers(self.board(), self.them(), king);
-            moves.swap_retain(|m| is_safe(self, king, m, blockers));
+        let mut count = 0;
+
+        for to in attacks::king_attacks(king) & target {
+            if self.king_attackers(to, !self.turn(), occupied).is_empty() {
+                count += 1;
+            }
         }
-    }
 
-    fn promotion_moves(&self, moves: &mut MoveList) {
-        moves.clear();
+        for from in self.us() & !self.board().pawns() & !self.board().kings() {
+            let mut targets = self.board().attacks_from(from) & target;
+            if pinned.contains(from) {
+                targets &= attacks::ray(king, from);
+            }
+            count += targets.count();
+        }
 
-        let king = self.board().king_of(self.turn()).expect("king in standard chess");
-        let checkers = self.checkers();
+        // Pushes of pawns that are not pinned can be counted all at once.
+        let pawns = self.our(Role::Pawn) & !pinned;
+        let single_moves = attacks::single_pushes(self.turn(), pawns, occupied);
+        let double_moves = attacks::double_pushes(self.turn(), pawns, occupied);
+        count += (single_moves & !Bitboard::BACKRANKS).count() +
+                 (single_moves & Bitboard::BACKRANKS).count() * 4 +
+                 double_moves.count();
 
-        if checkers.is_empty() {
-            gen_pawn_moves(self, Bitboard::BACKRANKS, moves);
-        } else {
-            evasions(self, king, checkers, moves);
-            moves.retain(|m| m.is_promotion());
+        for from in pawns {
+            let captures = (attacks::pawn_attacks(self.turn(), from) & self.them()).count();
+            count += if seventh.contains(from) { captures * 4 } else { captures };
         }
 
-        let blockers = slider_blockers(self.board(), self.them(), king);
-        if blockers.any() {
-            moves.swap_retain(|m| is_safe(self, king, m, blockers));
+        for from in self.our(Role::Pawn) & pinned {
+            let pushes = attacks::pawn_pushes(self.turn(), from, occupied);
+            let captures = attacks::pawn_attacks(self.turn(), from) & self.them();
+            let targets = (pushes | captures) & attacks::ray(king, from);
+            count += if seventh.contains(from) { targets.count() * 4 } else { targets.count() };
         }
-    }
-
-    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
-        moves.clear();
 
-        let king = self.board().king_of(self.turn()).expect("king in standard chess");
-        let checkers = self.checkers();
+        let mut moves = MoveList::new();
+        self.en_passant_moves(&mut moves);
+        count += moves.len();
+        self.castling_moves(CastlingSide::KingSide, &mut moves);
+        count += moves.len();
+        self.castling_moves(CastlingSide::QueenSide, &mut moves);
+        count += moves.len();
 
-        if checkers.is_empty() {
-            let piece_from = match role {
-                Role::Pawn | Role::King => Bitboard(0),
-                Role::Knight => attacks::knight_attacks(to),
-                Role::Bishop => attacks::bishop_attacks(to, self.board().occupied()),
-                Role::Rook => attacks::rook_attacks(to, self.board().occupied()),
-                Role::Queen => attacks::queen_attacks(to, self.board().occupied()),
-            };
+        count
+    }
 
-            if !self.us().contains(to) {
-                match role {
-                    Role::Pawn => gen_pawn_moves(self, Bitboard::from_square(to), moves),
-                    Role::King => gen_safe_king(self, king, Bitboard::from_square(to), moves),
-                    _ => {}
+    fn has_legal_moves(&self) -> bool {
+        if let Some(king) = self.board().king_of(self.turn()) {
+            // Safe king moves.
+            let occupied = self.board().occupied() ^ Bitboard::from_square(king);
+            for to in attacks::king_attacks(king) & !self.us() {
+                if self.king_attackers(to, !self.turn(), occupied).is_empty() {
+                    return true;
                 }
+            }
 
-                for from in piece_from & self.our(role) {
-                    moves.push(Move::Normal {
-                        role,
-                        from,
-                        capture: self.board().role_at(to),
-                        to,
-                        promotion: None,
-                    });
+            // If not in check, any pseudo-legal move of a piece that is not
+            // pinned is legal. En passant is left to the full move
+            // generation.
+            if self.checkers().is_empty() {
+                let blockers = slider_blockers(self.board(), self.them(), king);
+                for from in self.us() & !self.board().kings() & !blockers {
+                    let targets = if self.board().pawns().contains(from) {
+                        attacks::pawn_pushes(self.turn(), from, self.board().occupied()) |
+                        (attacks::pawn_attacks(self.turn(), from) & self.them())
+                    } else {
+                        self.board().attacks_from(from) & !self.us()
+                    };
+                    if targets.any() {
+                        return true;
+                    }
                 }
             }
-        } else {
-            evasions(self, king, checkers, moves);
-            filter_san_candidates(role, to, moves);
         }
 
-        let has_ep =
-            role == Role::Pawn &&
-            Some(EpSquare(to)) == self.ep_square &&
-            gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
+        let mut moves = MoveList::new();
+        self.legal_moves(&mut moves);
+        !moves.is_empty()
+    }
 
-        let blockers = slider_blockers(self.board(), self.them(), king);
-        if blockers.any() || has_ep {
-            moves.swap_retain(|m| is_safe(self, king, m, blockers));
-        }
-    }
-
-    fn has_insufficient_material(&self, color: Color) -> bool {
-        // Pawns, rooks and queens are never insufficient material.
-        if (self.board.by_color(color) & (self.board.pawns() | self.board.rooks_and_queens())).any() {
-            return false;
-        }
-
-        // Knights are only insufficient material if:
-        // (1) We do not have any other pieces, including more than one knight.
-        // (2) The opponent does not have pawns, knights, bishops or rooks.
-        //     These would allow self mate.
-        if (self.board.by_color(color) & self.board.knights()).any() {
-            return self.board.by_color(color).count() <= 2 &&
-                (self.board.by_color(!color) & !self.board.kings() & !self.board().queens()).is_empty();
-        }
-
-        // Bishops are only insufficient material if:
-        // (1) We do not have any other pieces, including bishops on the
-        //     opposite color.
-        // (2) The opponent does not have bishops on the opposite color,
-        //      pawns or knights. These would allow self mate.
-        if (self.board.by_color(color) & self.board.bishops()).any() {
-            let same_color =
-                (self.board().bishops() & Bitboard::DARK_SQUARES).is_empty() ||
-                (self.board().bishops() & Bitboard::LIGHT_SQUARES).is_empty();
-            return same_color && self.board().knights().is_empty() && self.board().pawns().is_empty();
-        }
-
-        true
-    }
-
-    fn is_variant_end(&self) -> bool { false }
-    fn variant_outcome(&self) -> Option<Outcome> { None }
-}
-
-/// An Atomic Chess position.
-#[derive(Clone, Debug)]
-pub struct Atomic {
-    board: Board,
-    turn: Color,
-    castles: Castles,
-    ep_square: Option<EpSquare>,
-    halfmoves: u32,
-    fullmoves: NonZeroU32,
-}
-
-impl Default for Atomic {
-    fn default() -> Atomic {
-        Atomic {
-            board: Board::default(),
-            turn: White,
-            castles: Castles::default(),
-            ep_square: None,
-            halfmoves: 0,
-            fullmoves: NonZeroU32::new(1).unwrap(),
-        }
-    }
-}
-
-impl Setup for Atomic {
-    fn board(&self) -> &Board { &self.board }
-    fn pockets(&self) -> Option<&Material> { None }
-    fn turn(&self) -> Color { self.turn }
-    fn castling_rights(&self) -> Bitboard { self.castles.castling_rights() }
-    fn ep_square(&self) -> Option<Square> { self.ep_square.and_then(|ep| relevant_ep(ep, self)) }
-    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
-    fn halfmoves(&self) -> u32 { self.halfmoves }
-    fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
-}
-
-impl FromSetup for Atomic {
-    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Atomic, PositionError<Atomic>> {
-        let mut errors = PositionErrorKinds::empty();
-        let board = setup.board().clone();
-        let turn = setup.turn();
-
-        let castles = match Castles::from_setup(&board, setup.castling_rights(), mode) {
-            Ok(castles) => castles,
-            Err(castles) => {
-                errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS;
-                castles
-            }
-        };
-
-        let ep_square = match EpSquare::from_setup(&board, turn, setup.ep_square()) {
-            Ok(ep_square) => ep_square,
-            Err(()) => {
-                errors |= PositionErrorKinds::INVALID_EP_SQUARE;
-                None
-            }
-        };
-
-        let pos = Atomic {
-            board,
-            turn,
-            castles,
-            ep_square,
-            halfmoves: setup.halfmoves(),
-            fullmoves: setup.fullmoves(),
-        };
-
-        errors |= validate(&pos) - PositionErrorKinds::IMPOSSIBLE_CHECK;
-
-        if (pos.them() & pos.board().kings()).any() {
-            // Our king just exploded. Game over, but valid position.
-            errors.remove(PositionErrorKinds::MISSING_KING);
-        }
-
-        PositionError { errors, pos }.strict()
-    }
-}
-
-impl Position for Atomic {
-    fn castles(&self) -> &Castles {
-        &self.castles
-    }
-
-    fn play_unchecked(&mut self, m: &Move) {
-        do_move(&mut self.board, &mut self.turn, &mut self.castles,
-                &mut self.ep_square, &mut self.halfmoves,
-                &mut self.fullmoves, m);
-
-        match *m {
-            Move::Normal { capture: Some(_), to, .. } | Move::EnPassant { to, .. } => {
-                self.board.remove_piece_at(to);
-
-                let explosion_radius = attacks::king_attacks(to) &
-                                       self.board().occupied() &
-                                       !self.board.pawns();
-
-                if (explosion_radius & self.board().kings() & self.us()).any() {
-                    self.castles.discard_side(self.turn());
-                }
-
-                for explosion in explosion_radius {
-                    self.board.remove_piece_at(explosion);
-                    self.castles.discard_rook(explosion);
-                }
-            },
-            _ => ()
-        }
-    }
-
-    fn legal_moves(&self, moves: &mut MoveList) {
-        moves.clear();
-
-        gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
-        gen_non_king(self, !self.us(), moves);
-        KingTag::gen_moves(self, !self.board().occupied(), moves);
-        if let Some(king) = self.board().king_of(self.turn()) {
-            gen_castling_moves(self, &self.castles, king, CastlingSide::KingSide, moves);
-            gen_castling_moves(self, &self.castles, king, CastlingSide::QueenSide, moves);
-        }
-
-        // Atomic move generation could be implemented more efficiently.
-        // For simplicity we filter all pseudo legal moves.
-        moves.swap_retain(|m| {
-            let mut after = self.clone();
-            after.play_unchecked(m);
-            if let Some(our_king) = after.board().king_of(self.turn()) {
-                (after.board.kings() & after.board().by_color(!self.turn())).is_empty() ||
-                after.king_attackers(our_king, !self.turn(), after.board.occupied()).is_empty()
-            } else {
-                false
-            }
-        });
-    }
-
-    fn king_attackers(&self, square: Square, attacker: Color, occupied: Bitboard) -> Bitboard {
-        if (attacks::king_attacks(square) & self.board().kings() & self.board().by_color(attacker)).any() {
-            Bitboard(0)
-        } else {
-            self.board().attacks_to(square, attacker, occupied)
-        }
-    }
-
-    fn is_variant_end(&self) -> bool {
-        self.variant_outcome().is_some()
-    }
-
-    fn has_insufficient_material(&self, color: Color) -> bool {
-        // Remaining material does not matter if the opponents king is already
-        // exploded.
-        if (self.board.by_color(!color) & self.board.kings()).is_empty() {
-            return false;
-        }
-
-        // Bare king can not mate.
-        if (self.board.by_color(color) & !self.board.kings()).is_empty() {
-            return true;
-        }
-
-        // As long as the opponent king is not alone there is always a chance
-        // their own piece explodes next to it.
-        if (self.board.by_color(!color) & !self.board.kings()).any() {
-            // Unless there are only bishops that cannot explode each other.
-            if self.board().occupied() == self.board().kings() | self.board().bishops() {
-                if (self.board().bishops() & self.board().white() & Bitboard::DARK_SQUARES).is_empty() {
-                    return (self.board().bishops() & self.board().black() & Bitboard::LIGHT_SQUARES).is_empty();
-                }
-                if (self.board().bishops() & self.board().white() & Bitboard::LIGHT_SQUARES).is_empty() {
-                    return (self.board().bishops() & self.board().black() & Bitboard::DARK_SQUARES).is_empty();
-                }
-            }
-
-            return false;
-        }
-
-        // Queen or pawn (future queen) can give mate against bare king.
-        if self.board().queens().any() || self.board.pawns().any() {
-            return false;
-        }
-
-        // Single knight, bishop or rook can not mate against bare king.
-        if (self.board().knights() | self.board().bishops() | self.board().rooks()).count() == 1 {
-            return true;
-        }
-
-        // Two knights can not mate against bare king.
-        if self.board().occupied() == self.board().kings() | self.board().knights() {
-            return self.board().knights().count() <= 2;
-        }
-
-        false
-    }
-
-    fn variant_outcome(&self) -> Option<Outcome> {
-        for &color in &[White, Black] {
-            if (self.board().by_color(color) & self.board().kings()).is_empty() {
-                return Some(Outcome::Decisive { winner: !color });
-            }
-        }
-        None
-    }
-}
-
-/// An Antichess position. Antichess is also known as Giveaway, but players
-/// start without castling rights.
-#[derive(Clone, Debug)]
-pub struct Antichess {
-    board: Board,
-    turn: Color,
-    castles: Castles,
-    ep_square: Option<EpSquare>,
-    halfmoves: u32,
-    fullmoves: NonZeroU32,
-}
-
-impl Default for Antichess {
-    fn default() -> Antichess {
-        Antichess {
-            board: Board::default(),
-            turn: White,
-            castles: Castles::empty(CastlingMode::Standard),
-            ep_square: None,
-            halfmoves: 0,
-            fullmoves: NonZeroU32::new(1).unwrap(),
-        }
-    }
-}
-
-impl Setup for Antichess {
-    fn board(&self) -> &Board { &self.board }
-    fn pockets(&self) -> Option<&Material> { None }
-    fn turn(&self) -> Color { self.turn }
-    fn castling_rights(&self) -> Bitboard { Bitboard(0) }
-    fn ep_square(&self) -> Option<Square> { self.ep_square.and_then(|ep| relevant_ep(ep, self)) }
-    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
-    fn halfmoves(&self) -> u32 { self.halfmoves }
-    fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
-}
-
-impl FromSetup for Antichess {
-    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Antichess, PositionError<Antichess>> {
-        let mut errors = PositionErrorKinds::empty();
-        let board = setup.board().clone();
-        let turn = setup.turn();
-
-        let ep_square = match EpSquare::from_setup(&board, turn, setup.ep_square()) {
-            Ok(ep_square) => ep_square,
-            Err(()) => {
-                errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS;
-                None
-            }
-        };
-
-        let pos = Antichess {
-            board,
-            turn,
-            castles: Castles::empty(mode),
-            ep_square,
-            halfmoves: setup.halfmoves(),
-            fullmoves: setup.fullmoves(),
-        };
-
-        if setup.castling_rights().any() {
-            errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS
-        }
-
-        errors |= validate(&pos)
-            - PositionErrorKinds::MISSING_KING
-            - PositionErrorKinds::TOO_MANY_KINGS
-            - PositionErrorKinds::OPPOSITE_CHECK
-            - PositionErrorKinds::IMPOSSIBLE_CHECK;
-
-        PositionError { errors, pos }.strict()
-    }
-}
-
-impl Position for Antichess {
-    fn play_unchecked(&mut self, m: &Move) {
-        do_move(&mut self.board, &mut self.turn, &mut self.castles,
-                &mut self.ep_square, &mut self.halfmoves,
-                &mut self.fullmoves, m);
-    }
-
-    fn castles(&self) -> &Castles {
-        &self.castles
-    }
-
-    fn en_passant_moves(&self, moves: &mut MoveList) {
-        moves.clear();
-        gen_en_passant(self.board(), self.turn, self.ep_square, moves);
-    }
-
-    fn capture_moves(&self, moves: &mut MoveList) {
-        self.en_passant_moves(moves); // clears move list
-        let them = self.them();
-        gen_non_king(self, them, moves);
-        add_king_promotions(moves);
-        KingTag::gen_moves(self, them, moves);
-    }
-
-    fn legal_moves(&self, moves: &mut MoveList) {
-        self.capture_moves(moves); // clears move list
-
-        if moves.is_empty() {
-            // No compulsory captures. Generate everything else.
-            gen_non_king(self, !self.board().occupied(), moves);
-            add_king_promotions(moves);
-            KingTag::gen_moves(self, !self.board().occupied(), moves);
-        }
-    }
-
-    fn king_attackers(&self, _square: Square, _attacker: Color, _occupied: Bitboard) -> Bitboard {
-        Bitboard(0)
-    }
-
-    fn is_variant_end(&self) -> bool {
-        self.board().white().is_empty() || self.board().black().is_empty()
-    }
-
-    fn has_insufficient_material(&self, color: Color) -> bool {
-        // In a position with only bishops, check if all our bishops can be
-        // captured.
-        if self.board.occupied() == self.board.bishops() {
-            let we_some_on_light = (self.board.by_color(color) & Bitboard::LIGHT_SQUARES).any();
-            let we_some_on_dark = (self.board.by_color(color) & Bitboard::DARK_SQUARES).any();
-            let they_all_on_dark = (self.board.by_color(!color) & Bitboard::LIGHT_SQUARES).is_empty();
-            let they_all_on_light = (self.board.by_color(!color) & Bitboard::DARK_SQUARES).is_empty();
-            (we_some_on_light && they_all_on_dark) || (we_some_on_dark && they_all_on_light)
-        } else {
-            false
-        }
-    }
-
-    fn variant_outcome(&self) -> Option<Outcome> {
-        if self.us().is_empty() || self.is_stalemate() {
-            Some(Outcome::Decisive { winner: self.turn() })
-        } else {
-            None
-        }
-    }
-}
-
-/// A King of the Hill position.
-#[derive(Clone, Debug, Default)]
-pub struct KingOfTheHill {
-    chess: Chess,
-}
-
-impl Setup for KingOfTheHill {
-    fn board(&self) -> &Board { self.chess.board() }
-    fn pockets(&self) -> Option<&Material> { None }
-    fn turn(&self) -> Color { self.chess.turn() }
-    fn castling_rights(&self) -> Bitboard { self.chess.castling_rights() }
-    fn ep_square(&self) -> Option<Square> { self.chess.ep_square() }
-    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
-    fn halfmoves(&self) -> u32 { self.chess.halfmoves() }
-    fn fullmoves(&self) -> NonZeroU32 { self.chess.fullmoves() }
-}
-
-impl FromSetup for KingOfTheHill {
-    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<KingOfTheHill, PositionError<KingOfTheHill>> {
-        let (chess, errors) = Chess::from_setup_unchecked(setup, mode);
-        PositionError {
-            errors,
-            pos: KingOfTheHill { chess },
-        }.strict()
-    }
-}
-
-impl Position for KingOfTheHill {
-    fn play_unchecked(&mut self, m: &Move) {
-        self.chess.play_unchecked(m);
-    }
-
-    fn castles(&self) -> &Castles {
-        self.chess.castles()
-    }
-
-    fn legal_moves(&self, moves: &mut MoveList) {
-        if self.is_variant_end() {
-            moves.clear();
-        } else {
-            self.chess.legal_moves(moves);
-        }
-    }
-
-    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
-        if self.is_variant_end() {
-            moves.clear();
-        } else {
-            self.chess.castling_moves(side, moves);
-        }
-    }
-
-    fn en_passant_moves(&self, moves: &mut MoveList) {
-        if self.is_variant_end() {
-            moves.clear();
-        } else {
-            self.chess.en_passant_moves(moves);
-        }
-    }
-
-    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
-        if self.is_variant_end() {
-            moves.clear();
-        } else {
-            self.chess.san_candidates(role, to, moves);
-        }
-    }
-
-    fn has_insufficient_material(&self, _color: Color) -> bool {
-        // Even a lone king can walk onto the hill.
-        false
-    }
-
-    fn is_variant_end(&self) -> bool {
-        (self.chess.board().kings() & Bitboard::CENTER).any()
-    }
-
-    fn variant_outcome(&self) -> Option<Outcome> {
-        for &color in &[White, Black] {
-            if (self.board().by_color(color) & self.board().kings() & Bitboard::CENTER).any() {
-                return Some(Outcome::Decisive { winner: color });
-            }
-        }
-        None
-    }
-}
-
-/// A Three-Check position.
-#[derive(Clone, Debug, Default)]
-pub struct ThreeCheck {
-    chess: Chess,
-    remaining_checks: RemainingChecks,
-}
-
-impl Setup for ThreeCheck {
-    fn board(&self) -> &Board { self.chess.board() }
-    fn pockets(&self) -> Option<&Material> { None }
-    fn turn(&self) -> Color { self.chess.turn() }
-    fn castling_rights(&self) -> Bitboard { self.chess.castling_rights() }
-    fn ep_square(&self) -> Option<Square> { self.chess.ep_square() }
-    fn remaining_checks(&self) -> Option<&RemainingChecks> { Some(&self.remaining_checks) }
-    fn halfmoves(&self) -> u32 { self.chess.halfmoves() }
-    fn fullmoves(&self) -> NonZeroU32 { self.chess.fullmoves }
-}
-
-impl FromSetup for ThreeCheck {
-    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<ThreeCheck, PositionError<ThreeCheck>> {
-        let (chess, mut errors) = Chess::from_setup_unchecked(setup, mode);
-
-        let remaining_checks = setup.remaining_checks().cloned().unwrap_or_default();
-        if remaining_checks.white == 0 && remaining_checks.black == 0 {
-            errors |= PositionErrorKinds::VARIANT
-        }
-
-        PositionError {
-            errors,
-            pos: ThreeCheck { chess, remaining_checks },
-        }.strict()
-    }
-}
-
-impl Position for ThreeCheck {
-    fn play_unchecked(&mut self, m: &Move) {
-        let turn = self.chess.turn();
-        self.chess.play_unchecked(m);
-        if self.is_check() {
-            self.remaining_checks.decrement(turn);
-        }
-    }
-
-    fn castles(&self) -> &Castles {
-        self.chess.castles()
-    }
-
-    fn legal_moves(&self, moves: &mut MoveList) {
-        if self.is_variant_end() {
-            moves.clear();
-        } else {
-            self.chess.legal_moves(moves);
-        }
-    }
-
-    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
-        if self.is_variant_end() {
-            moves.clear();
-        } else {
-            self.chess.castling_moves(side, moves);
+    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
+        moves.clear();
+        if let Some(king) = self.board().king_of(self.turn()) {
+            gen_castling_moves(self, &self.castles, king, side, moves);
         }
     }
 
     fn en_passant_moves(&self, moves: &mut MoveList) {
-        if self.is_variant_end() {
-            moves.clear();
-        } else {
-            self.chess.en_passant_moves(moves);
-        }
-    }
-
-    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
-        if self.is_variant_end() {
-            moves.clear();
-        } else {
-            self.chess.san_candidates(role, to, moves);
-        }
-    }
-
-    fn has_insufficient_material(&self, color: Color) -> bool {
-        // Any remaining piece can give check.
-        (self.board().by_color(color) & !self.board().kings()).is_empty()
-    }
-
-    fn is_irreversible(&self, m: &Move) -> bool {
-        self.chess.is_irreversible(m) || self.chess.gives_check(m)
-    }
-
-    fn is_variant_end(&self) -> bool {
-        self.remaining_checks.white == 0 || self.remaining_checks.black == 0
-    }
-
-    fn variant_outcome(&self) -> Option<Outcome> {
-        if self.remaining_checks.white == 0 && self.remaining_checks.black == 0 {
-            Some(Outcome::Draw)
-        } else if self.remaining_checks.white == 0 {
-            Some(Outcome::Decisive { winner: White })
-        } else if self.remaining_checks.black == 0 {
-            Some(Outcome::Decisive { winner: Black })
-        } else {
-            None
-        }
-    }
-}
-
-/// A Crazyhouse position.
-#[derive(Clone, Debug, Default)]
-pub struct Crazyhouse {
-    chess: Chess,
-    pockets: Material,
-}
-
-impl Crazyhouse {
-    fn our_pocket(&self) -> &MaterialSide {
-        self.pockets.by_color(self.turn())
-    }
-
-    fn our_pocket_mut(&mut self) -> &mut MaterialSide {
-        let turn = self.turn();
-        self.pockets.by_color_mut(turn)
-    }
-
-    fn legal_put_squares(&self) -> Bitboard {
-        let checkers = self.checkers();
-
-        if checkers.is_empty() {
-            !self.board().occupied()
-        } else if let Some(checker) = checkers.single_square() {
-            let king = self.board().king_of(self.turn()).expect("king in crazyhouse");
-            attacks::between(checker, king)
-        } else {
-            Bitboard(0)
-        }
-    }
-}
-
-impl Setup for Crazyhouse {
-    fn board(&self) -> &Board { self.chess.board() }
-    fn pockets(&self) -> Option<&Material> { Some(&self.pockets) }
-    fn turn(&self) -> Color { self.chess.turn() }
-    fn castling_rights(&self) -> Bitboard { self.chess.castling_rights() }
-    fn ep_square(&self) -> Option<Square> { self.chess.ep_square() }
-    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
-    fn halfmoves(&self) -> u32 { self.chess.halfmoves() }
-    fn fullmoves(&self) -> NonZeroU32 { self.chess.fullmoves() }
-}
-
-impl FromSetup for Crazyhouse {
-    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Crazyhouse, PositionError<Crazyhouse>> {
-        let (chess, mut errors) = Chess::from_setup_unchecked(setup, mode);
-
-        let pockets = setup.pockets().cloned().unwrap_or_default();
-        if pockets.count().saturating_add(chess.board().occupied().count()) > 64 {
-            errors |= PositionErrorKinds::VARIANT;
-        } else if pockets.white.kings > 0 || pockets.black.kings > 0 {
-            errors |= PositionErrorKinds::TOO_MANY_KINGS;
-        }
-
-        if pockets.count().saturating_add(chess.board().occupied().count()) <= 32 &&
-           usize::from(pockets.white.pawns.saturating_add(pockets.black.pawns)).saturating_add(chess.board().pawns().count()) <= 16
-        {
-            errors -= PositionErrorKinds::IMPOSSIBLE_MATERIAL;
-        }
-
-        PositionError {
-            errors,
-            pos: Crazyhouse { chess, pockets },
-        }.strict()
-    }
-}
-
-impl Position for Crazyhouse {
-    fn play_unchecked(&mut self, m: &Move) {
-        match *m {
-            Move::Normal { capture: Some(capture), to, .. } => {
-                let capture = if self.board().promoted().contains(to) {
-                    Role::Pawn
-                } else {
-                    capture
-                };
-
-                *self.our_pocket_mut().by_role_mut(capture) += 1;
-            }
-            Move::EnPassant { .. } => {
-                self.our_pocket_mut().pawns += 1;
-            }
-            Move::Put { role, .. } => {
-                *self.our_pocket_mut().by_role_mut(role) -= 1;
-            }
-            _ => {}
-        }
-
-        self.chess.play_unchecked(m);
-    }
-
-    fn castles(&self) -> &Castles {
-        self.chess.castles()
-    }
-
-    fn legal_moves(&self, moves: &mut MoveList) {
-        self.chess.legal_moves(moves);
-
-        let pocket = self.our_pocket();
-        let targets = self.legal_put_squares();
-
-        for to in targets {
-            for &role in &[Role::Knight, Role::Bishop, Role::Rook, Role::Queen] {
-                if pocket.by_role(role) > 0 {
-                    moves.push(Move::Put { role, to });
-                }
-            }
-        }
+        moves.clear();
 
-        if pocket.pawns > 0 {
-            for to in targets & !Bitboard::BACKRANKS {
-                moves.push(Move::Put { role: Role::Pawn, to });
+        if gen_en_passant(self.board(), self.turn(), self.ep_square, moves) {
+            if let Some(king) = self.board().king_of(self.turn()) {
+                let blockers = slider_blockers(self.board(), self.them(), king);
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
             }
         }
     }
 
-    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
-        self.chess.castling_moves(side, moves);
-    }
-
-    fn en_passant_moves(&self, moves: &mut MoveList) {
-        self.chess.en_passant_moves(moves);
-    }
-
-    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
-        self.chess.san_candidates(role, to, moves);
-
-        if self.our_pocket().by_role(role) > 0 && self.legal_put_squares().contains(to) &&
-           (role != Role::Pawn || !Bitboard::BACKRANKS.contains(to))
-        {
-            moves.push(Move::Put { role, to });
-        }
-    }
-
-    fn is_irreversible(&self, m: &Move) -> bool {
-        match *m {
-            Move::Castle { .. } => true,
-            Move::Normal { role, from, to, .. } =>
-                self.castling_rights().contains(from) ||
-                self.castling_rights().contains(to) ||
-                (role == Role::King && self.chess.castles.has_side(self.turn())),
-            _ => false,
-        }
-    }
-
-    fn has_insufficient_material(&self, _color: Color) -> bool {
-        // In practise no material can leave the game, but this is simple
-        // to implement anyway. Bishops can be captured and put onto a
-        // different color complex.
-        self.board().occupied().count() + self.pockets.count() <= 3 &&
-        self.board().promoted().is_empty() &&
-        self.board().pawns().is_empty() &&
-        self.board().rooks_and_queens().is_empty() &&
-        self.pockets.white.pawns == 0 &&
-        self.pockets.black.pawns == 0 &&
-        self.pockets.white.rooks == 0 &&
-        self.pockets.black.rooks == 0 &&
-        self.pockets.white.queens == 0 &&
-        self.pockets.black.queens == 0
-    }
-
-    fn is_variant_end(&self) -> bool { false }
-    fn variant_outcome(&self) -> Option<Outcome> { None }
-}
-
-/// A Racing Kings position.
-#[derive(Clone, Debug)]
-pub struct RacingKings {
-    board: Board,
-    turn: Color,
-    castles: Castles,
-    halfmoves: u32,
-    fullmoves: NonZeroU32,
-}
-
-impl Default for RacingKings {
-    fn default() -> RacingKings {
-        RacingKings {
-            board: Board::racing_kings(),
-            turn: White,
-            castles: Castles::empty(CastlingMode::Standard),
-            halfmoves: 0,
-            fullmoves: NonZeroU32::new(1).unwrap(),
-        }
-    }
-}
-
-impl Setup for RacingKings {
-    fn board(&self) -> &Board { &self.board }
-    fn pockets(&self) -> Option<&Material> { None }
-    fn turn(&self) -> Color { self.turn }
-    fn castling_rights(&self) -> Bitboard { Bitboard(0) }
-    fn ep_square(&self) -> Option<Square> { None }
-    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
-    fn halfmoves(&self) -> u32 { self.halfmoves }
-    fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
-}
-
-impl FromSetup for RacingKings {
-    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<RacingKings, PositionError<RacingKings>> {
-        let mut errors = PositionErrorKinds::empty();
-
-        if setup.castling_rights().any() {
-            errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS;
-        }
-
-        let board = setup.board().clone();
-        if board.pawns().any() {
-            errors |= PositionErrorKinds::VARIANT;
-        }
-        if setup.ep_square().is_some() {
-            errors |= PositionErrorKinds::INVALID_EP_SQUARE;
-        }
-
-        let pos = RacingKings {
-            board,
-            turn: setup.turn(),
-            castles: Castles::empty(mode),
-            halfmoves: setup.halfmoves(),
-            fullmoves: setup.fullmoves(),
-        };
-
-        if pos.is_check() {
-            errors |= PositionErrorKinds::IMPOSSIBLE_CHECK;
-        }
-
-        if pos.turn().is_black() &&
-           (pos.board().white() & pos.board().kings() & Rank::Eighth).any() &&
-           (pos.board().black() & pos.board().kings() & Rank::Eighth).any()
-        {
-            errors |= PositionErrorKinds::VARIANT;
-        }
-
-        errors |= validate(&pos);
-
-        PositionError { errors, pos }.strict()
-    }
-}
-
-impl Position for RacingKings {
-    fn play_unchecked(&mut self, m: &Move) {
-        do_move(&mut self.board, &mut self.turn, &mut self.castles,
-                &mut None, &mut self.halfmoves,
-                &mut self.fullmoves, m);
-    }
-
-    fn legal_moves(&self, moves: &mut MoveList) {
+    fn capture_moves(&self, moves: &mut MoveList) {
         moves.clear();
 
-        if self.is_variant_end() {
-            return;
-        }
-
-        // Generate all legal moves (no castling, no ep).
-        let target = !self.us();
-        gen_non_king(self, target, moves);
-        let king = self.board().king_of(self.turn()).expect("king in racingkings");
-        gen_safe_king(self, king, target, moves);
-
-        let blockers = slider_blockers(self.board(), self.them(), king);
-        if blockers.any() {
-            moves.swap_retain(|m| is_safe(self, king, m, blockers));
-        }
-
-        // Do not allow giving check. This could be implemented more
-        // efficiently.
-        moves.swap_retain(|m| {
-            let mut after = self.clone();
-            after.play_unchecked(m);
-            !after.is_check()
-        });
-    }
-
-    fn castles(&self) -> &Castles {
-        &self.castles
-    }
-
-    fn has_insufficient_material(&self, _color: Color) -> bool {
-        // Even a lone king can win the race.
-        false
-    }
-
-    fn is_variant_end(&self) -> bool {
-        let in_goal = self.board().kings() & Rank::Eighth;
-        if in_goal.is_empty() {
-            return false;
-        }
+        let king = self.board().king_of(self.turn());
 
-        if self.turn().is_white() || (in_goal & self.board().black()).any() {
-            return true;
-        }
+        let has_ep = gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
 
-        // White has reached the backrank. Check if black can catch up.
-        let black_king = self.board().king_of(Black).expect("king in racingkings");
-        for target in attacks::king_attacks(black_king) & Rank::Eighth & !self.board().black() {
-            if self.king_attackers(target, White, self.board().occupied()).is_empty() {
-                return false;
+        let checkers = self.checkers();
+        if checkers.is_empty() {
+            let target = self.them();
+            gen_non_king(self, target, PromotionMode::All, moves);
+            if let Some(king) = king {
+                gen_safe_king(self, king, target, moves);
             }
+        } else if let Some(king) = king {
+            evasions(self, king, checkers, PromotionMode::All, moves);
+            moves.retain(|m| m.is_capture());
         }
 
-        true
-    }
-
-    fn variant_outcome(&self) -> Option<Outcome> {
-        if self.is_variant_end() {
-            let in_goal = self.board().kings() & Rank::Eighth;
-            if (in_goal & self.board().white()).any() && (in_goal & self.board().black()).any() {
-                Some(Outcome::Draw)
-            } else if (in_goal & self.board().white()).any() {
-                Some(Outcome::Decisive { winner: White })
-            } else {
-                Some(Outcome::Decisive { winner: Black })
+        if let Some(king) = king {
+            let blockers = slider_blockers(self.board(), self.them(), king);
+            if blockers.any() || has_ep {
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
             }
-        } else {
-            None
-        }
-    }
-}
-
-/// A Horde position.
-#[derive(Clone, Debug)]
-pub struct Horde {
-    board: Board,
-    turn: Color,
-    castles: Castles,
-    ep_square: Option<EpSquare>,
-    halfmoves: u32,
-    fullmoves: NonZeroU32,
-}
-
-impl Default for Horde {
-    fn default() -> Horde {
-        let mut castles = Castles::default();
-        castles.discard_side(White);
-
-        Horde {
-            board: Board::horde(),
-            turn: White,
-            castles,
-            ep_square: None,
-            halfmoves: 0,
-            fullmoves: NonZeroU32::new(1).unwrap(),
         }
     }
-}
 
-impl Setup for Horde {
-    fn board(&self) -> &Board { &self.board }
-    fn pockets(&self) -> Option<&Material> { None }
-    fn turn(&self) -> Color { self.turn }
-    fn castling_rights(&self) -> Bitboard { self.castles.castling_rights() }
-    fn ep_square(&self) -> Option<Square> { self.ep_square.and_then(|ep| relevant_ep(ep, self)) }
-    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
-    fn halfmoves(&self) -> u32 { self.halfmoves }
-    fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
-}
+    fn promotion_moves(&self, moves: &mut MoveList) {
+        moves.clear();
 
-impl FromSetup for Horde {
-    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Horde, PositionError<Horde>> {
-        let mut errors = PositionErrorKinds::empty();
-        let board = setup.board().clone();
-        let turn = setup.turn();
+        let king = self.board().king_of(self.turn());
+        let checkers = self.checkers();
 
-        let castles = match Castles::from_setup(&board, setup.castling_rights(), mode) {
-            Ok(castles) => castles,
-            Err(castles) => {
-                errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS;
-                castles
-            }
-        };
+        if checkers.is_empty() {
+            gen_pawn_moves(self, Bitboard::BACKRANKS, PromotionMode::All, moves);
+        } else if let Some(king) = king {
+            evasions(self, king, checkers, PromotionMode::All, moves);
+            moves.retain(|m| m.is_promotion());
+        }
 
-        let ep_square = match EpSquare::from_setup(&board, turn, setup.ep_square()) {
-            Ok(ep_square) => ep_square,
-            Err(()) => {
-                errors |= PositionErrorKinds::INVALID_EP_SQUARE;
-                None
+        if let Some(king) = king {
+            let blockers = slider_blockers(self.board(), self.them(), king);
+            if blockers.any() {
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
             }
-        };
+        }
+    }
 
-        let pos = Horde {
-            board,
-            turn,
-            castles,
-            ep_square,
-            halfmoves: setup.halfmoves(),
-            fullmoves: setup.fullmoves(),
-        };
+    fn evasion_moves(&self, moves: &mut MoveList) {
+        moves.clear();
 
-        errors |= validate(&pos)
-            - PositionErrorKinds::PAWNS_ON_BACKRANK
-            - PositionErrorKinds::MISSING_KING
-            - PositionErrorKinds::IMPOSSIBLE_MATERIAL;
+        let checkers = self.checkers();
+        if let (Some(king), true) = (self.board().king_of(self.turn()), checkers.any()) {
+            let has_ep = gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
+            evasions(self, king, checkers, PromotionMode::All, moves);
 
-        if (pos.board().kings() & pos.board.white()).is_empty() {
-            if pos.board().white().count() > 36 || pos.board().black().count() > 16 || (pos.board().black() & pos.board().pawns()).count() > 8 {
-                errors |= PositionErrorKinds::IMPOSSIBLE_MATERIAL;
+            let blockers = slider_blockers(self.board(), self.them(), king);
+            if blockers.any() || has_ep {
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
             }
-        } else if pos.board().black().count() > 36 || pos.board().white().count() > 16 || (pos.board().white() & pos.board().pawns()).count() > 8 {
-            errors |= PositionErrorKinds::IMPOSSIBLE_MATERIAL;
         }
+    }
 
-        if (pos.board().pawns() & pos.board().white() & Rank::Eighth).any() ||
-           (pos.board().pawns() & pos.board().black() & Rank::First).any()
-        {
-            errors |= PositionErrorKinds::PAWNS_ON_BACKRANK;
-        }
+    fn quiet_moves(&self, moves: &mut MoveList) {
+        moves.clear();
 
-        if (pos.board().kings() & !pos.board().promoted()).is_empty() {
-            errors |= PositionErrorKinds::MISSING_KING;
-        }
+        let king = self.board().king_of(self.turn());
+        let checkers = self.checkers();
 
-        if (pos.board().kings() & pos.board().white()).any() &&
-           (pos.board().kings() & pos.board().black()).any()
-        {
-            errors |= PositionErrorKinds::VARIANT;
+        if checkers.is_empty() {
+            let target = !self.board().occupied();
+            gen_non_king(self, target, PromotionMode::All, moves);
+            if let Some(king) = king {
+                gen_safe_king(self, king, target, moves);
+                gen_castling_moves(self, &self.castles, king, CastlingSide::KingSide, moves);
+                gen_castling_moves(self, &self.castles, king, CastlingSide::QueenSide, moves);
+            }
+        } else if let Some(king) = king {
+            evasions(self, king, checkers, PromotionMode::All, moves);
         }
 
-        PositionError { errors, pos }.strict()
-    }
-}
+        moves.retain(|m| !m.is_capture() && !m.is_promotion());
 
-impl Position for Horde {
-    fn play_unchecked(&mut self, m: &Move) {
-        do_move(&mut self.board, &mut self.turn, &mut self.castles,
-                &mut self.ep_square, &mut self.halfmoves,
-                &mut self.fullmoves, m);
+        if let Some(king) = king {
+            let blockers = slider_blockers(self.board(), self.them(), king);
+            if blockers.any() {
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
+            }
+        }
     }
 
-    fn legal_moves(&self, moves: &mut MoveList) {
+    fn legal_moves_masked(&self, from: Bitboard, to: Bitboard, moves: &mut MoveList) {
         moves.clear();
 
         let king = self.board().king_of(self.turn());
+
         let has_ep = gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
 
         let checkers = self.checkers();
         if checkers.is_empty() {
-            let target = !self.us();
-            gen_non_king(self, target, moves);
-            if let Some(king) = king {
+            let target = !self.us() & to;
+            if (from & self.us() & !self.board().kings()).any() {
+                gen_non_king(self, target, PromotionMode::All, moves);
+            }
+            if let Some(king) = king.filter(|&king| from.contains(king)) {
                 gen_safe_king(self, king, target, moves);
                 gen_castling_moves(self, &self.castles, king, CastlingSide::KingSide, moves);
                 gen_castling_moves(self, &self.castles, king, CastlingSide::QueenSide, moves);
             }
-        } else {
-            evasions(self, king.expect("king in check"), checkers, moves);
+        } else if let Some(king) = king {
+            evasions(self, king, checkers, PromotionMode::All, moves);
         }
 
+        filter_masked(from, to, moves);
+
         if let Some(king) = king {
             let blockers = slider_blockers(self.board(), self.them(), king);
             if blockers.any() || has_ep {
@@ -1661,39 +1325,95 @@ impl Position for Horde {
         }
     }
 
-    fn castles(&self) -> &Castles {
-        &self.castles
-    }
+    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
+        moves.clear();
+
+        let king = self.board().king_of(self.turn());
+        let checkers = self.checkers();
+
+        if checkers.is_empty() {
+            let piece_from = match role {
+                Role::Pawn | Role::King => Bitboard(0),
+                Role::Knight => attacks::knight_attacks(to),
+                Role::Bishop => attacks::bishop_attacks(to, self.board().occupied()),
+                Role::Rook => attacks::rook_attacks(to, self.board().occupied()),
+                Role::Queen => attacks::queen_attacks(to, self.board().occupied()),
+            };
+
+            if !self.us().contains(to) {
+                match role {
+                    Role::Pawn => gen_pawn_moves(self, Bitboard::from_square(to), PromotionMode::All, moves),
+                    Role::King => {
+                        if let Some(king) = king {
+                            gen_safe_king(self, king, Bitboard::from_square(to), moves);
+                        }
+                    }
+                    _ => {}
+                }
+
+                for from in piece_from & self.our(role) {
+                    moves.push(Move::Normal {
+                        role,
+                        from,
+                        capture: self.board().role_at(to),
+                        to,
+                        promotion: None,
+                    });
+                }
+            }
+        } else if let Some(king) = king {
+            evasions(self, king, checkers, PromotionMode::All, moves);
+            filter_san_candidates(role, to, moves);
+        }
+
+        let has_ep =
+            role == Role::Pawn &&
+            Some(EpSquare(to)) == self.ep_square &&
+            gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
 
-    fn is_variant_end(&self) -> bool {
-        self.board().white().is_empty() || self.board().black().is_empty()
+        if let Some(king) = king {
+            let blockers = slider_blockers(self.board(), self.them(), king);
+            if blockers.any() || has_ep {
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
+            }
+        }
     }
 
     fn has_insufficient_material(&self, color: Color) -> bool {
-        // The side with the king can always win by capturing the horde.
-        if (self.board.by_color(color) & self.board.kings()).any() {
+        // Pawns, rooks and queens are never insufficient material.
+        if (self.board.by_color(color) & (self.board.pawns() | self.board.rooks_and_queens())).any() {
             return false;
         }
 
-        // TODO: Detect when the horde can not mate. Note that it does not have
-        // a king.
-        false
-    }
+        // Knights are only insufficient material if:
+        // (1) We do not have any other pieces, including more than one knight.
+        // (2) The opponent does not have pawns, knights, bishops or rooks.
+        //     These would allow self mate.
+        if (self.board.by_color(color) & self.board.knights()).any() {
+            return self.board.by_color(color).count() <= 2 &&
+                (self.board.by_color(!color) & !self.board.kings() & !self.board().queens()).is_empty();
+        }
 
-    fn variant_outcome(&self) -> Option<Outcome> {
-        if self.board().occupied().is_empty() {
-            Some(Outcome::Draw)
-        } else if self.board().white().is_empty() {
-            Some(Outcome::Decisive { winner: Black })
-        } else if self.board().black().is_empty() {
-            Some(Outcome::Decisive { winner: White })
-        } else {
-            None
+        // Bishops are only insufficient material if:
+        // (1) We do not have any other pieces, including bishops on the
+        //     opposite color.
+        // (2) The opponent does not have bishops on the opposite color,
+        //      pawns or knights. These would allow self mate.
+        if (self.board.by_color(color) & self.board.bishops()).any() {
+            let same_color =
+                (self.board().bishops() & Bitboard::DARK_SQUARES).is_empty() ||
+                (self.board().bishops() & Bitboard::LIGHT_SQUARES).is_empty();
+            return same_color && self.board().knights().is_empty() && self.board().pawns().is_empty();
         }
+
+        true
     }
+
+    fn is_variant_end(&self) -> bool { false }
+    fn variant_outcome(&self) -> Option<Outcome> { None }
 }
 
-fn do_move(board: &mut Board,
+pub(crate) fn do_move(board: &mut Board,
            turn: &mut Color,
            castles: &mut Castles,
            ep_square: &mut Option<EpSquare>,
@@ -1757,7 +1477,7 @@ fn do_move(board: &mut Board,
     *turn = !color;
 }
 
-fn validate<P: Position>(pos: &P) -> PositionErrorKinds {
+pub(crate) fn validate<P: Position>(pos: &P) -> PositionErrorKinds {
     let mut errors = PositionErrorKinds::empty();
 
     if pos.board().occupied().is_empty() {
@@ -1818,15 +1538,15 @@ fn validate<P: Position>(pos: &P) -> PositionErrorKinds {
     errors
 }
 
-fn gen_non_king<P: Position>(pos: &P, target: Bitboard, moves: &mut MoveList) {
-    gen_pawn_moves(pos, target, moves);
+pub(crate) fn gen_non_king<P: Position>(pos: &P, target: Bitboard, promotions: PromotionMode, moves: &mut MoveList) {
+    gen_pawn_moves(pos, target, promotions, moves);
     KnightTag::gen_moves(pos, target, moves);
     BishopTag::gen_moves(pos, target, moves);
     RookTag::gen_moves(pos, target, moves);
     QueenTag::gen_moves(pos, target, moves);
 }
 
-fn gen_safe_king<P: Position>(pos: &P, king: Square, target: Bitboard, moves: &mut MoveList) {
+pub(crate) fn gen_safe_king<P: Position>(pos: &P, king: Square, target: Bitboard, moves: &mut MoveList) {
     for to in attacks::king_attacks(king) & target {
         if pos.board().attacks_to(to, !pos.turn(), pos.board().occupied()).is_empty() {
             moves.push(Move::Normal {
@@ -1840,7 +1560,7 @@ fn gen_safe_king<P: Position>(pos: &P, king: Square, target: Bitboard, moves: &m
     }
 }
 
-fn evasions<P: Position>(pos: &P, king: Square, checkers: Bitboard, moves: &mut MoveList) {
+pub(crate) fn evasions<P: Position>(pos: &P, king: Square, checkers: Bitboard, promotions: PromotionMode, moves: &mut MoveList) {
     let sliders = checkers & pos.board().sliders();
 
     let mut attacked = Bitboard(0);
@@ -1852,11 +1572,11 @@ fn evasions<P: Position>(pos: &P, king: Square, checkers: Bitboard, moves: &mut
 
     if let Some(checker) = checkers.single_square() {
         let target = attacks::between(king, checker).with(checker);
-        gen_non_king(pos, target, moves);
+        gen_non_king(pos, target, promotions, moves);
     }
 }
 
-fn gen_castling_moves<P: Position>(pos: &P, castles: &Castles, king: Square, side: CastlingSide, moves: &mut MoveList) {
+pub(crate) fn gen_castling_moves<P: Position>(pos: &P, castles: &Castles, king: Square, side: CastlingSide, moves: &mut MoveList) {
     if let Some(rook) = castles.rook(pos.turn(), side) {
         let path = castles.path(pos.turn(), side);
         if (path & pos.board().occupied()).any() {
@@ -1879,7 +1599,7 @@ fn gen_castling_moves<P: Position>(pos: &P, castles: &Castles, king: Square, sid
     }
 }
 
-trait Stepper {
+pub(crate) trait Stepper {
     const ROLE: Role;
 
     fn attacks(from: Square) -> Bitboard;
@@ -1899,7 +1619,7 @@ trait Stepper {
     }
 }
 
-trait Slider {
+pub(crate) trait Slider {
     const ROLE: Role;
     fn attacks(from: Square, occupied: Bitboard) -> Bitboard;
 
@@ -1918,11 +1638,12 @@ trait Slider {
     }
 }
 
-enum KnightTag { }
-enum BishopTag { }
-enum RookTag { }
-enum QueenTag { }
-enum KingTag { }
+pub(crate) enum KnightTag { }
+pub(crate) enum BishopTag { }
+pub(crate) enum RookTag { }
+pub(crate) enum QueenTag { }
+#[cfg(any(feature = "atomic", feature = "antichess"))]
+pub(crate) enum KingTag { }
 
 impl Stepper for KnightTag {
     const ROLE: Role = Role::Knight;
@@ -1931,6 +1652,7 @@ impl Stepper for KnightTag {
     }
 }
 
+#[cfg(any(feature = "atomic", feature = "antichess"))]
 impl Stepper for KingTag {
     const ROLE: Role = Role::King;
     fn attacks(from: Square) -> Bitboard {
@@ -1959,7 +1681,7 @@ impl Slider for QueenTag {
     }
 }
 
-fn gen_pawn_moves<P: Position>(pos: &P, target: Bitboard, moves: &mut MoveList) {
+pub(crate) fn gen_pawn_moves<P: Position>(pos: &P, target: Bitboard, promotions: PromotionMode, moves: &mut MoveList) {
     let seventh = pos.our(Role::Pawn) & Bitboard::relative_rank(pos.turn(), Rank::Seventh);
 
     for from in pos.our(Role::Pawn) & !seventh {
@@ -1976,16 +1698,15 @@ fn gen_pawn_moves<P: Position>(pos: &P, target: Bitboard, moves: &mut MoveList)
 
     for from in seventh {
         for to in attacks::pawn_attacks(pos.turn(), from) & pos.them() & target {
-            push_promotions(moves, from, to, pos.board().role_at(to));
+            push_promotions(moves, promotions, from, to, pos.board().role_at(to));
         }
     }
 
-    let single_moves = pos.our(Role::Pawn).relative_shift(pos.turn(), 8) &
-                       !pos.board().occupied();
+    let single_moves = attacks::single_pushes(pos.turn(), pos.our(Role::Pawn), pos.board().occupied());
 
-    let double_moves = single_moves.relative_shift(pos.turn(), 8) &
-                       Bitboard::relative_rank(pos.turn(), Rank::Fourth).with(Bitboard::relative_rank(pos.turn(), Rank::Third)) &
-                       !pos.board().occupied();
+    // Double pushes from the first rank are only possible in variants like
+    // Horde, all others reject such pawns during validation.
+    let double_moves = attacks::double_pushes(pos.turn(), pos.our(Role::Pawn), pos.board().occupied());
 
     for to in single_moves & target & !Bitboard::BACKRANKS {
         if let Some(from) = to.offset(pos.turn().fold(-8, 8)) {
@@ -2001,7 +1722,7 @@ fn gen_pawn_moves<P: Position>(pos: &P, target: Bitboard, moves: &mut MoveList)
 
     for to in single_moves & target & Bitboard::BACKRANKS {
         if let Some(from) = to.offset(pos.turn().fold(-8, 8)) {
-            push_promotions(moves, from, to, None);
+            push_promotions(moves, promotions, from, to, None);
         }
     }
 
@@ -2018,32 +1739,15 @@ fn gen_pawn_moves<P: Position>(pos: &P, target: Bitboard, moves: &mut MoveList)
     }
 }
 
-fn push_promotions(moves: &mut MoveList, from: Square, to: Square, capture: Option<Role>) {
-    moves.push(Move::Normal { role: Role::Pawn, from, capture, to, promotion: Some(Role::Queen) });
-    moves.push(Move::Normal { role: Role::Pawn, from, capture, to, promotion: Some(Role::Rook) });
-    moves.push(Move::Normal { role: Role::Pawn, from, capture, to, promotion: Some(Role::Bishop) });
-    moves.push(Move::Normal { role: Role::Pawn, from, capture, to, promotion: Some(Role::Knight) });
-}
-
-fn add_king_promotions(moves: &mut MoveList) {
-    let mut king_promotions = MoveList::new();
-
-    for m in &moves[..] {
-        if let Move::Normal { role, from, capture, to, promotion: Some(Role::Queen) } = *m {
-            king_promotions.push(Move::Normal {
-                role,
-                from,
-                capture,
-                to,
-                promotion: Some(Role::King),
-            });
+pub(crate) fn push_promotions(moves: &mut MoveList, promotions: PromotionMode, from: Square, to: Square, capture: Option<Role>) {
+    for &role in &[Role::Queen, Role::Rook, Role::Bishop, Role::Knight] {
+        if promotions.allows(role) {
+            moves.push(Move::Normal { role: Role::Pawn, from, capture, to, promotion: Some(role) });
         }
     }
-
-    moves.extend(king_promotions);
 }
 
-fn relevant_ep<P: Position>(EpSquare(ep_square): EpSquare, pos: &P) -> Option<Square> {
+pub(crate) fn relevant_ep<P: Position>(EpSquare(ep_square): EpSquare, pos: &P) -> Option<Square> {
     let mut moves = MoveList::new();
     pos.en_passant_moves(&mut moves);
     if moves.is_empty() {
@@ -2053,7 +1757,7 @@ fn relevant_ep<P: Position>(EpSquare(ep_square): EpSquare, pos: &P) -> Option<Sq
     }
 }
 
-fn gen_en_passant(board: &Board, turn: Color, ep_square: Option<EpSquare>, moves: &mut MoveList) -> bool {
+pub(crate) fn gen_en_passant(board: &Board, turn: Color, ep_square: Option<EpSquare>, moves: &mut MoveList) -> bool {
     let mut found = false;
 
     if let Some(EpSquare(to)) = ep_square {
@@ -2066,7 +1770,7 @@ fn gen_en_passant(board: &Board, turn: Color, ep_square: Option<EpSquare>, moves
     found
 }
 
-fn slider_blockers(board: &Board, enemy: Bitboard, king: Square) -> Bitboard {
+pub(crate) fn slider_blockers(board: &Board, enemy: Bitboard, king: Square) -> Bitboard {
     let snipers = (attacks::rook_attacks(king, Bitboard(0)) & board.rooks_and_queens()) |
                   (attacks::bishop_attacks(king, Bitboard(0)) & board.bishops_and_queens());
 
@@ -2083,7 +1787,7 @@ fn slider_blockers(board: &Board, enemy: Bitboard, king: Square) -> Bitboard {
     blockers
 }
 
-fn is_safe<P: Position>(pos: &P, king: Square, m: &Move, blockers: Bitboard) -> bool {
+pub(crate) fn is_safe<P: Position>(pos: &P, king: Square, m: &Move, blockers: Bitboard) -> bool {
     match *m {
         Move::Normal { from, to, .. } =>
             !blockers.contains(from) || attacks::aligned(from, to, king),
@@ -2100,7 +1804,13 @@ fn is_safe<P: Position>(pos: &P, king: Square, m: &Move, blockers: Bitboard) ->
     }
 }
 
-fn filter_san_candidates(role: Role, to: Square, moves: &mut MoveList) {
+fn filter_masked(from: Bitboard, to: Bitboard, moves: &mut MoveList) {
+    moves.retain(|m| {
+        m.from().map_or(from == Bitboard::ALL, |sq| from.contains(sq)) && to.contains(m.to())
+    });
+}
+
+pub(crate) fn filter_san_candidates(role: Role, to: Square, moves: &mut MoveList) {
     moves.retain(|m| match *m {
         Move::Normal { role: r, to: t, .. } | Move::Put { role: r, to: t } =>
             to == t && role == r,
@@ -2110,7 +1820,7 @@ fn filter_san_candidates(role: Role, to: Square, moves: &mut MoveList) {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::fen::{fen, Fen};
 
@@ -2151,6 +1861,35 @@ mod tests {
         assert_eq!(moves.len(), 1);
     }
 
+    #[test]
+    fn test_move_candidates() {
+        let fen = "r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1";
+        let pos: Chess = fen.parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+
+        for m in pos.legals() {
+            let mut moves = MoveList::new();
+            pos.move_candidates(&m, &mut moves);
+            assert!(moves.contains(&m), "{} not among its candidates", m);
+            assert!(moves.iter().all(|c| c.to() == m.to() && c.role() == m.role()));
+        }
+
+        let ep = Move::EnPassant { from: Square::E5, to: Square::D6 };
+        let mut moves = MoveList::new();
+        pos.move_candidates(&ep, &mut moves);
+        assert_eq!(moves.as_slice(), [ep]);
+
+        let castle = Move::Castle { king: Square::E1, rook: Square::A1 };
+        let mut moves = MoveList::new();
+        pos.move_candidates(&castle, &mut moves);
+        assert_eq!(moves.as_slice(), [castle]);
+
+        let illegal = Move::Castle { king: Square::E1, rook: Square::H8 };
+        assert!(!pos.is_legal(&illegal));
+    }
+
     #[test]
     fn test_promotion() {
         let fen = "3r3K/6PP/8/8/8/2k5/8/8 w - - 0 1";
@@ -2165,7 +1904,7 @@ mod tests {
         assert!(moves.iter().all(|m| m.is_promotion()));
     }
 
-    fn assert_insufficient_material<P>(fen: &str, white: bool, black: bool)
+    pub(crate) fn assert_insufficient_material<P>(fen: &str, white: bool, black: bool)
     where
         P: Position + FromSetup,
     {
@@ -2180,8 +1919,6 @@ mod tests {
 
     #[test]
     fn test_insufficient_material() {
-        let false_negative = false;
-
         assert_insufficient_material::<Chess>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", true, true);
         assert_insufficient_material::<Chess>("8/3k4/8/8/2N5/8/3K4/8 b - - 0 1", true, true);
         assert_insufficient_material::<Chess>("8/4rk2/8/8/8/8/3K4/8 w - - 0 1", true, false);
@@ -2192,92 +1929,379 @@ mod tests {
         assert_insufficient_material::<Chess>("5K2/8/8/1B6/8/k7/6b1/8 w - - 0 39", true, true);
         assert_insufficient_material::<Chess>("8/8/8/4k3/5b2/3K4/8/2B5 w - - 0 33", true, true);
         assert_insufficient_material::<Chess>("3b4/8/8/6b1/8/8/R7/K1k5 w - - 0 1", false, true);
+    }
+
+    #[test]
+    fn test_outcome() {
+        for &(fen, outcome) in &[
+            ("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3", Some(Outcome::Decisive { winner: Black })),
+            ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", Some(Outcome::Draw)),
+            ("8/5k2/8/8/8/2B5/3K4/8 w - - 0 1", Some(Outcome::Draw)),
+            ("8/5k2/8/8/2R5/8/3K4/8 w - - 0 1", None),
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            assert_eq!(pos.outcome(), outcome);
+            assert_eq!(pos.is_game_over(), outcome.is_some());
+            assert_eq!(pos.is_checkmate(), outcome.and_then(Outcome::winner).is_some());
+        }
+    }
+
+    #[test]
+    fn test_has_and_count_legal_moves() {
+        for fen in &[
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "4k3/8/8/b7/8/2P5/3K4/8 w - - 0 1",
+            "4k3/8/8/8/r1P1K3/8/8/8 w - - 0 1",
+            "2r1k3/8/8/8/8/8/2P5/2K5 w - - 0 1",
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+            "7k/5Q2/6K1/8/8/8/8/r7 b - - 0 1",
+            "k7/1r6/2Q5/8/8/8/8/K7 b - - 0 1",
+            "k7/1r6/2Q5/8/8/8/8/K5R1 b - - 0 1",
+            "8/8/8/8/1k6/8/2q5/K1R5 w - - 0 1",
+            "8/8/8/1k6/3Pp3/8/8/4K2Q b - d3 0 1",
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+            assert_eq!(pos.has_legal_moves(), !pos.legals().is_empty(), "{}", fen);
+            assert_eq!(pos.count_legal_moves(), pos.legals().len(), "{}", fen);
+        }
+
+        let mut pos = Chess::default();
+        for i in 0..200 {
+            let legals = pos.legals();
+            assert_eq!(pos.has_legal_moves(), !legals.is_empty());
+            assert_eq!(pos.count_legal_moves(), legals.len());
+            if legals.is_empty() {
+                break;
+            }
+            pos.play_unchecked(&legals[(i * 13) % legals.len()]);
+        }
+    }
+
+    #[test]
+    fn test_capture_moves() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "8/8/8/8/K2Pp2r/8/8/7k b - d3 0 1",
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+            "4k3/8/8/8/8/8/3q4/4K1N1 w - - 0 1",
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            let mut captures = MoveList::new();
+            pos.capture_moves(&mut captures);
+            let mut legals = pos.legals();
+            legals.retain(|m| m.is_capture());
+            assert_eq!(captures.len(), legals.len(), "{}", fen);
+            assert!(captures.iter().all(|m| legals.contains(m)));
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_with_promotions() {
+        for fen in &[
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "r3k3/1P6/8/8/8/8/8/K7 w - - 0 1", // promotion to capture the checker
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            let mut moves = MoveList::new();
+            pos.legal_moves_with_promotions(PromotionMode::All, &mut moves);
+            assert_eq!(moves, pos.legals(), "{}", fen);
+
+            pos.legal_moves_with_promotions(PromotionMode::QueenOnly, &mut moves);
+            let mut legals = pos.legals();
+            legals.retain(|m| m.promotion().map_or(true, |role| role == Role::Queen));
+            assert_eq!(moves.len(), legals.len(), "{}", fen);
+            assert!(moves.iter().all(|m| legals.contains(m)));
+        }
+    }
+
+    #[test]
+    fn test_evasion_moves() {
+        for &(fen, in_check) in &[
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", false),
+            ("4k3/8/8/8/8/8/3q4/4K1N1 w - - 0 1", true),
+            ("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3", true),
+            ("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1", true),
+            ("4k3/8/8/8/8/4r3/8/R3K2R w KQ - 0 1", true),
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            let mut evasions = MoveList::new();
+            pos.evasion_moves(&mut evasions);
+            if in_check {
+                let legals = pos.legals();
+                assert_eq!(evasions.len(), legals.len(), "{}", fen);
+                assert!(evasions.iter().all(|m| legals.contains(m)));
+            } else {
+                assert!(evasions.is_empty());
+            }
+        }
+    }
 
-        assert_insufficient_material::<Atomic>("8/3k4/8/8/2N5/8/3K4/8 b - - 0 1", true, true);
-        assert_insufficient_material::<Atomic>("8/4rk2/8/8/8/8/3K4/8 w - - 0 1", true, true);
-        assert_insufficient_material::<Atomic>("8/4qk2/8/8/8/8/3K4/8 w - - 0 1", true, false);
-        assert_insufficient_material::<Atomic>("8/1k6/8/2n5/8/3NK3/8/8 b - - 0 1", false, false);
-        assert_insufficient_material::<Atomic>("8/4bk2/8/8/8/8/3KB3/8 w - - 0 1", true, true);
-        assert_insufficient_material::<Atomic>("4b3/5k2/8/8/8/8/3KB3/8 w - - 0 1", false, false);
-        assert_insufficient_material::<Atomic>("3Q4/5kKB/8/8/8/8/8/8 b - - 0 1", false, true);
-        assert_insufficient_material::<Atomic>("8/5k2/8/8/8/8/5K2/4bb2 w - - 0 1", true, false);
-        assert_insufficient_material::<Atomic>("8/5k2/8/8/8/8/5K2/4nb2 w - - 0 1", true, false);
+    #[test]
+    fn test_quiet_moves() {
+        for fen in &[
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "8/8/8/8/K2Pp2r/8/8/7k b - d3 0 1",
+            "4k3/8/8/8/8/8/3q4/4K1N1 w - - 0 1",
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            let mut quiet = MoveList::new();
+            pos.quiet_moves(&mut quiet);
+            let mut legals = pos.legals();
+            legals.retain(|m| !m.is_capture() && !m.is_promotion());
+            assert_eq!(quiet.len(), legals.len(), "{}", fen);
+            assert!(quiet.iter().all(|m| legals.contains(m)));
+        }
+    }
 
-        assert_insufficient_material::<Antichess>("8/4bk2/8/8/8/8/3KB3/8 w - - 0 1", false, false);
-        assert_insufficient_material::<Antichess>("4b3/5k2/8/8/8/8/3KB3/8 w - - 0 1", false, false);
-        assert_insufficient_material::<Antichess>("8/8/8/6b1/8/3B4/4B3/5B2 w - - 0 1", true, true);
-        assert_insufficient_material::<Antichess>("8/8/5b2/8/8/3B4/3B4/8 w - - 0 1", true, false);
-        assert_insufficient_material::<Antichess>("8/5p2/5P2/8/3B4/1bB5/8/8 b - - 0 1", false_negative, false_negative);
+    #[test]
+    fn test_legal_moves_from() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let pos: Chess = fen.parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
 
-        assert_insufficient_material::<KingOfTheHill>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", false, false);
+        let mut total = 0;
+        let mut moves = MoveList::new();
+        for sq in Bitboard::ALL {
+            pos.legal_moves_from(sq, &mut moves);
+            assert!(moves.iter().all(|m| m.from() == Some(sq)));
+            total += moves.len();
+        }
+        assert_eq!(total, 48);
 
-        assert_insufficient_material::<RacingKings>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", false, false);
+        pos.legal_moves_from(Square::E1, &mut moves);
+        assert_eq!(moves.iter().filter(|m| m.is_castle()).count(), 2);
+    }
 
-        assert_insufficient_material::<ThreeCheck>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", true, true);
-        assert_insufficient_material::<ThreeCheck>("8/5k2/8/8/8/8/3K2N1/8 w - - 0 1", false, true);
+    #[test]
+    fn test_pinned() {
+        let fen = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+        let pos: Chess = fen.parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        assert!(pos.pinned().is_empty());
 
-        assert_insufficient_material::<Crazyhouse>("8/5k2/8/8/8/8/3K2N1/8 w - - 0 1", true, true);
-        assert_insufficient_material::<Crazyhouse>("8/5k2/8/8/8/5B2/3KB3/8 w - - 0 1", false, false);
-        assert_insufficient_material::<Crazyhouse>("8/8/8/8/3k4/3N~4/3K4/8 w - - 0 1", false, false);
+        let fen = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+        let pos: Chess = fen.parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        assert_eq!(pos.pinned(), Bitboard::from_square(Square::B5));
+        assert_eq!(pos.pin_ray(Square::B5), Bitboard::rank(Rank::Fifth));
+        assert_eq!(pos.pin_ray(Square::B4), Bitboard::ALL);
 
-        assert_insufficient_material::<Horde>("8/5k2/8/8/8/4NN2/8/8 w - - 0 1", false_negative, false);
+        for m in pos.legals() {
+            assert!(pos.pin_ray(m.from().expect("no drops")).contains(m.to()));
+        }
     }
 
     #[test]
-    fn test_exploded_king_loses_castling_rights() {
-        let pos: Atomic = "rnb1kbnr/pppppppp/8/4q3/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1".parse::<Fen>()
+    fn test_discovered_check_candidates() {
+        let fen = "3k4/8/3P4/8/3N4/8/8/Q2RK3 w - - 0 1";
+        let pos: Chess = fen.parse::<Fen>()
             .expect("valid fen")
-            .position(CastlingMode::Chess960)
+            .position(CastlingMode::Standard)
             .expect("valid position");
+        assert!(pos.discovered_check_candidates().is_empty());
 
-        let pos = pos.play(&Move::Normal {
-            role: Role::Queen,
-            from: Square::E5,
-            to: Square::E2,
-            capture: Some(Role::Pawn),
-            promotion: None,
-        }).expect("Qxe2# is legal");
+        let fen = "7k/8/8/8/3N4/8/1Q6/4K3 w - - 0 1";
+        let pos: Chess = fen.parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        assert_eq!(pos.discovered_check_candidates(), Bitboard::from_square(Square::D4));
+
+        for m in pos.legals() {
+            let from = m.from().expect("no drops");
+            if pos.discovered_check_candidates().contains(from) {
+                assert!(pos.gives_check(&m), "{}", m);
+            }
+        }
+    }
 
-        assert_eq!(pos.castling_rights(), Bitboard::from(Square::A8) | Bitboard::from(Square::H8));
-        assert_eq!(pos.castles().rook(Color::White, CastlingSide::QueenSide), None);
-        assert_eq!(pos.castles().rook(Color::White, CastlingSide::KingSide), None);
-        assert_eq!(pos.castles().rook(Color::Black, CastlingSide::QueenSide), Some(Square::A8));
-        assert_eq!(pos.castles().rook(Color::Black, CastlingSide::KingSide), Some(Square::H8));
+    #[test]
+    fn test_legal_moves_masked() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/8/8/8/K2Pp2r/8/8/7k b - d3 0 1",
+            "4k3/8/8/8/8/8/3q4/4K1N1 w - - 0 1",
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            let masks = [
+                (Bitboard::ALL, Bitboard::ALL),
+                (pos.our(Role::King), Bitboard::ALL),
+                (pos.our(Role::Pawn), Bitboard::BACKRANKS),
+                (Bitboard::ALL, Bitboard::CENTER),
+                (Bitboard::LIGHT_SQUARES, Bitboard::DARK_SQUARES),
+            ];
+
+            for &(from, to) in &masks {
+                let mut masked = MoveList::new();
+                pos.legal_moves_masked(from, to, &mut masked);
+                let mut legals = pos.legals();
+                legals.retain(|m| from.contains(m.from().expect("no drops")) && to.contains(m.to()));
+                assert_eq!(masked.len(), legals.len(), "{}", fen);
+                assert!(masked.iter().all(|m| legals.contains(m)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_gives_check() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "8/8/8/8/K2Pp2r/8/8/7k b - d3 0 1",
+            "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+            "4k3/2P5/8/8/8/8/8/K7 w - - 0 1",
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            for m in pos.legals() {
+                let mut child = pos.clone();
+                child.play_unchecked(&m);
+                assert_eq!(pos.gives_check(&m), child.is_check(), "{} {:?}", fen, m);
+            }
+        }
     }
 
     #[test]
-    fn test_racing_kings_end() {
-        // Both players reached the backrank.
-        let pos: RacingKings = "kr3NK1/1q2R3/8/8/8/5n2/2N5/1rb2B1R w - - 11 14".parse::<Fen>()
+    fn test_is_irreversible() {
+        let pos: Chess = "r3k2r/8/8/8/8/8/4P3/R3K1NR w KQkq - 0 1".parse::<Fen>()
             .expect("valid fen")
-            .position(CastlingMode::Chess960)
+            .position(CastlingMode::Standard)
             .expect("valid position");
-        assert!(pos.is_variant_end());
-        assert_eq!(pos.variant_outcome(), Some(Outcome::Draw));
 
-        // White to move is lost because black reached the backrank.
-        let pos: RacingKings = "1k6/6K1/8/8/8/8/8/8 w - - 0 1".parse::<Fen>()
+        let normal = |role, from, to| Move::Normal { role, from, capture: None, to, promotion: None };
+        assert!(pos.is_irreversible(&normal(Role::Pawn, Square::E2, Square::E3)));
+        assert!(pos.is_irreversible(&normal(Role::King, Square::E1, Square::F1)));
+        assert!(pos.is_irreversible(&normal(Role::Rook, Square::A1, Square::B1)));
+        assert!(pos.is_irreversible(&Move::Castle { king: Square::E1, rook: Square::A1 }));
+        assert!(pos.is_irreversible(&Move::Normal { role: Role::Rook, from: Square::A1, capture: Some(Role::Rook), to: Square::A8, promotion: None }));
+        assert!(!pos.is_irreversible(&normal(Role::Knight, Square::G1, Square::F3)));
+
+        // Giving up the right to capture en passant.
+        let pos: Chess = "4k3/8/8/8/3pP3/8/8/4K1N1 b - e3 0 1".parse::<Fen>()
             .expect("valid fen")
-            .position(CastlingMode::Chess960)
+            .position(CastlingMode::Standard)
             .expect("valid position");
-        assert!(pos.is_variant_end());
-        assert_eq!(pos.variant_outcome(), Some(Outcome::Decisive { winner: Color::Black }));
+        assert!(pos.is_irreversible(&normal(Role::King, Square::E8, Square::D8)));
+    }
+
+    #[test]
+    fn test_move_rules() {
+        for &(fen, fifty, seventyfive) in &[
+            ("8/5k2/8/8/8/8/3KR3/8 w - - 99 80", false, false),
+            ("8/5k2/8/8/8/8/3KR3/8 w - - 100 80", true, false),
+            ("8/5k2/8/8/8/8/3KR3/8 w - - 150 80", true, true),
+            ("4R2k/6pp/8/8/8/8/8/6K1 b - - 100 99", false, false),
+            ("7k/5Q2/6K1/8/8/8/8/8 b - - 150 99", true, true),
+        ] {
+            let pos: Chess = fen.parse::<Fen>()
+                .expect("valid fen")
+                .position(CastlingMode::Standard)
+                .expect("valid position");
+
+            assert_eq!(pos.is_fifty_moves(), fifty);
+            assert_eq!(pos.is_seventyfive_moves(), seventyfive);
+        }
+    }
 
-        // Black is given a chance to catch up.
-        let pos: RacingKings = "1K6/7k/8/8/8/8/8/8 b - - 0 1".parse::<Fen>()
+    #[test]
+    fn test_outcome_on_timeout() {
+        let pos: Chess = "8/4bk2/8/8/8/8/3KN3/8 w - - 0 1".parse::<Fen>()
             .expect("valid fen")
-            .position(CastlingMode::Chess960)
+            .position(CastlingMode::Standard)
             .expect("valid position");
-        assert!(!pos.is_variant_end());
-        assert_eq!(pos.variant_outcome(), None);
 
-        // Black near backrank but cannot move there.
-        let pos: RacingKings = "2KR4/k7/2Q5/4q3/8/8/8/2N5 b - - 0 1".parse::<Fen>()
+        // King and minor piece can not mate a lone king, but can help mate
+        // a king that is obstructed by its own pieces.
+        assert_eq!(pos.outcome_on_timeout(White), Outcome::Decisive { winner: Black });
+        assert_eq!(pos.outcome_on_timeout(Black), Outcome::Decisive { winner: White });
+
+        let pos: Chess = "8/5k2/8/8/8/8/3KR3/8 w - - 0 1".parse::<Fen>()
             .expect("valid fen")
-            .position(CastlingMode::Chess960)
+            .position(CastlingMode::Standard)
             .expect("valid position");
-        assert!(pos.is_variant_end());
-        assert_eq!(pos.variant_outcome(), Some(Outcome::Decisive { winner: Color::White }));
+        assert_eq!(pos.outcome_on_timeout(White), Outcome::Draw);
+        assert_eq!(pos.outcome_on_timeout(Black), Outcome::Decisive { winner: White });
+    }
+
+    #[test]
+    fn test_missing_king() {
+        let fen: Fen = "8/8/8/8/8/8/4P3/8 w - - 0 1".parse().expect("valid fen");
+        let res = fen.position::<Chess>(CastlingMode::Standard);
+        assert_eq!(res.expect_err("no kings").kinds(), PositionErrorKinds::MISSING_KING);
+        let pos = fen.position::<Chess>(CastlingMode::Standard)
+            .or_else(PositionError::ignore_missing_king)
+            .expect("kings can be missing");
+        assert!(pos.checkers().is_empty());
+        let mut moves = MoveList::new();
+        pos.legal_moves(&mut moves);
+        assert_eq!(moves.len(), 2);
+
+        let pos: Chess = "r3k2r/8/8/8/8/8/8/R7 w kq - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .or_else(PositionError::ignore_missing_king)
+            .expect("white king can be missing");
+        let mut moves = MoveList::new();
+        let pos = pos.play(&Move::Normal {
+            role: Role::Rook,
+            from: Square::A1,
+            capture: Some(Role::Rook),
+            to: Square::A8,
+            promotion: None,
+        }).expect("Rxa8+ is legal");
+        assert!(pos.is_check());
+        assert!(!pos.is_checkmate());
+        pos.castling_moves(CastlingSide::KingSide, &mut moves);
+        assert!(moves.is_empty());
     }
 
     #[test]
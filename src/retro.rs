@@ -0,0 +1,312 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Retrograde move generation for standard chess.
+//!
+//! Unmoves are represented as the [`Move`] that would be played in the
+//! predecessor position, so an uncapture is a capture and an unpromotion is
+//! a promotion.
+//!
+//! Castling rights of the predecessor can not be known, so predecessors keep
+//! the castling rights of the given position, and uncastling is not
+//! generated. The halfmove clock is not used to restrict unmoves.
+//!
+//! # Examples
+//!
+//! ```
+//! use shakmaty::{CastlingMode, Chess, Color, Position, Role, Setup, Square};
+//! use shakmaty::fen::Fen;
+//! use shakmaty::retro;
+//!
+//! let pos: Chess = "8/8/8/8/8/8/2k5/K7 w - - 0 1"
+//!     .parse::<Fen>()?
+//!     .position(CastlingMode::Standard)?;
+//!
+//! let unmoves = retro::unmoves(&pos);
+//! assert!(unmoves.iter().all(|m| m.role() == Role::King));
+//! assert!(unmoves.iter().any(|m| m.is_capture()));
+//!
+//! // The black king may have captured a queen on c2.
+//! let m = unmoves.iter()
+//!     .find(|m| m.from() == Some(Square::D3) && m.capture() == Some(Role::Queen))
+//!     .expect("uncapture");
+//! let mut predecessor = retro::unplay(&pos, m).expect("valid unmove");
+//! assert_eq!(predecessor.board().piece_at(Square::C2), Some(Role::Queen.of(Color::White)));
+//! predecessor.play_unchecked(m);
+//! assert_eq!(predecessor, pos);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::num::NonZeroU32;
+use std::vec;
+
+use crate::attacks;
+use crate::bitboard::Bitboard;
+use crate::fen::Fen;
+use crate::position::{Chess, Position};
+use crate::setup::Setup;
+use crate::square::{Rank, Square};
+use crate::types::{Move, Role};
+
+/// Roles that can be uncaptured on `sq`.
+fn uncaptures(sq: Square) -> &'static [Option<Role>] {
+    if Bitboard::BACKRANKS.contains(sq) {
+        &[None, Some(Role::Knight), Some(Role::Bishop), Some(Role::Rook), Some(Role::Queen)]
+    } else {
+        &[None, Some(Role::Pawn), Some(Role::Knight), Some(Role::Bishop), Some(Role::Rook), Some(Role::Queen)]
+    }
+}
+
+/// Generates all moves that lead to `pos` from a legal predecessor
+/// position, as returned by [`unplay()`].
+pub fn unmoves(pos: &Chess) -> Vec<Move> {
+    let mut moves = candidates(pos);
+    moves.retain(|m| unplay(pos, m).is_some());
+    moves
+}
+
+/// Iterates over all legal predecessors of `pos`, together with the move
+/// that leads from the predecessor to `pos`.
+///
+/// There is a predecessor for each choice of uncaptured piece.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{CastlingMode, Chess, Position};
+/// use shakmaty::fen::Fen;
+/// use shakmaty::retro;
+///
+/// let pos: Chess = "4k3/8/8/8/8/8/8/4K2R b - - 0 1"
+///     .parse::<Fen>()?
+///     .position(CastlingMode::Standard)?;
+///
+/// for (m, mut predecessor) in retro::predecessors(&pos) {
+///     assert!(predecessor.is_legal(&m));
+///     predecessor.play_unchecked(&m);
+///     assert_eq!(predecessor, pos);
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn predecessors(pos: &Chess) -> Predecessors<'_> {
+    Predecessors {
+        pos,
+        candidates: candidates(pos).into_iter(),
+    }
+}
+
+/// Iterator returned by [`predecessors()`].
+#[derive(Debug)]
+pub struct Predecessors<'a> {
+    pos: &'a Chess,
+    candidates: vec::IntoIter<Move>,
+}
+
+impl<'a> Iterator for Predecessors<'a> {
+    type Item = (Move, Chess);
+
+    fn next(&mut self) -> Option<(Move, Chess)> {
+        let pos = self.pos;
+        self.candidates.by_ref().find_map(|m| unplay(pos, &m).map(|predecessor| (m, predecessor)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.candidates.size_hint().1)
+    }
+}
+
+// Unmoves that are pseudo-legal, but not yet checked for a legal
+// predecessor.
+fn candidates(pos: &Chess) -> Vec<Move> {
+    let board = pos.board();
+    let them = !pos.turn();
+    let empty = !board.occupied();
+    let forward: i32 = them.fold(8, -8);
+
+    let mut candidates = Vec::new();
+
+    for to in board.by_color(them) {
+        let role = board.role_at(to).expect("piece on occupied square");
+
+        if role == Role::Pawn {
+            if let Some(from) = to.offset(-forward).filter(|&from| (empty & !Bitboard::BACKRANKS).contains(from)) {
+                candidates.push(Move::Normal { role, from, capture: None, to, promotion: None });
+                if to.rank() == them.fold(Rank::Fourth, Rank::Fifth) {
+                    if let Some(from) = from.offset(-forward).filter(|&from| empty.contains(from)) {
+                        candidates.push(Move::Normal { role, from, capture: None, to, promotion: None });
+                    }
+                }
+            }
+
+            for from in attacks::pawn_attacks(!them, to) & empty & !Bitboard::BACKRANKS {
+                for &capture in &uncaptures(to)[1..] {
+                    candidates.push(Move::Normal { role, from, capture, to, promotion: None });
+                }
+                if to.rank() == them.fold(Rank::Sixth, Rank::Third) {
+                    candidates.push(Move::EnPassant { from, to });
+                }
+            }
+        } else {
+            let origins = attacks::attacks(to, role.of(them), board.occupied()) & empty;
+            for from in origins {
+                for &capture in uncaptures(to) {
+                    candidates.push(Move::Normal { role, from, capture, to, promotion: None });
+                }
+            }
+
+            if role != Role::King && to.rank() == (!them).backrank() {
+                let promotion = Some(role);
+                if let Some(from) = to.offset(-forward).filter(|&from| empty.contains(from)) {
+                    candidates.push(Move::Normal { role: Role::Pawn, from, capture: None, to, promotion });
+                }
+                for from in attacks::pawn_attacks(!them, to) & empty {
+                    for &capture in &uncaptures(to)[1..] {
+                        candidates.push(Move::Normal { role: Role::Pawn, from, capture, to, promotion });
+                    }
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Takes back `m`, returning the predecessor position from which playing
+/// `m` leads to `pos`.
+///
+/// Returns `None` if there is no such legal predecessor, or if `m` is a
+/// castling move or a drop.
+pub fn unplay(pos: &Chess, m: &Move) -> Option<Chess> {
+    let them = !pos.turn();
+    let mut board = pos.board().clone();
+    let mut ep_square = None;
+
+    match *m {
+        Move::Normal { role, from, capture, to, promotion } => {
+            if board.piece_at(to) != Some(promotion.unwrap_or(role).of(them)) || board.occupied().contains(from) {
+                return None;
+            }
+            board.discard_piece_at(to);
+            board.set_piece_at(from, role.of(them), false);
+            if let Some(capture) = capture {
+                board.set_piece_at(to, capture.of(pos.turn()), false);
+            }
+        }
+        Move::EnPassant { from, to } => {
+            let captured = Square::from_coords(to.file(), from.rank());
+            if board.piece_at(to) != Some(Role::Pawn.of(them)) || (board.occupied() & (Bitboard::from_square(from) | Bitboard::from_square(captured))).any() {
+                return None;
+            }
+            board.discard_piece_at(to);
+            board.set_piece_at(from, Role::Pawn.of(them), false);
+            board.set_piece_at(captured, Role::Pawn.of(pos.turn()), false);
+            ep_square = Some(to);
+        }
+        Move::Castle { .. } | Move::Put { .. } => return None,
+    }
+
+    let fullmoves = if them.is_black() {
+        NonZeroU32::new(pos.fullmoves().get() - 1).unwrap_or(pos.fullmoves())
+    } else {
+        pos.fullmoves()
+    };
+
+    let setup = Fen {
+        board,
+        pockets: None,
+        turn: them,
+        castling_rights: pos.castling_rights(),
+        ep_square,
+        remaining_checks: None,
+        halfmoves: if m.is_zeroing() { 0 } else { pos.halfmoves().saturating_sub(1) },
+        fullmoves,
+    };
+
+    let predecessor: Chess = setup.position(pos.castles().mode()).ok()?;
+    if !predecessor.is_legal(m) {
+        return None;
+    }
+
+    let mut after = predecessor.clone();
+    after.play_unchecked(m);
+    if after == *pos {
+        Some(predecessor)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movelist::MoveList;
+    use crate::types::CastlingMode;
+
+    fn position(fen: &str) -> Chess {
+        fen.parse::<Fen>().expect("valid fen").position(CastlingMode::Standard).expect("legal position")
+    }
+
+    #[test]
+    fn test_unmoves() {
+        // Only the double push can explain the en passant square.
+        let pos = position("rnbqkbnr/ppp1pppp/8/8/2Pp4/8/PP1PPPPP/RNBQKBNR b KQkq c3 0 3");
+        assert_eq!(unmoves(&pos), [Move::Normal { role: Role::Pawn, from: Square::C2, capture: None, to: Square::C4, promotion: None }]);
+
+        // Unpromotions and en passant uncaptures.
+        let pos = position("1Q6/8/3P4/8/8/8/k7/2K5 b - - 0 1");
+        let moves = unmoves(&pos);
+        assert!(moves.contains(&Move::Normal { role: Role::Pawn, from: Square::B7, capture: None, to: Square::B8, promotion: Some(Role::Queen) }));
+        assert!(moves.contains(&Move::Normal { role: Role::Pawn, from: Square::A7, capture: Some(Role::Rook), to: Square::B8, promotion: Some(Role::Queen) }));
+        assert!(moves.contains(&Move::EnPassant { from: Square::E5, to: Square::D6 }));
+        assert!(moves.contains(&Move::EnPassant { from: Square::C5, to: Square::D6 }));
+
+        // White can not have been in check before the move of black.
+        let pos = position("k7/8/8/8/8/8/8/K6r w - - 0 1");
+        let moves = unmoves(&pos);
+        assert!(!moves.is_empty());
+        assert!(moves.iter().all(|m| m.to() == Square::H1 && m.from().map(Square::rank) != Some(Rank::First)));
+        assert!(moves.iter().any(|m| m.is_promotion()));
+    }
+
+    #[test]
+    fn test_unmoves_of_played_moves() {
+        let mut pos = position("r3k2r/p1pp1pb1/bn2pnp1/2qPN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let mut moves = MoveList::new();
+
+        for i in 0..40 {
+            pos.legal_moves(&mut moves);
+            moves.retain(|m| !m.is_castle());
+            if moves.is_empty() {
+                break;
+            }
+            let m = moves[(i * 7) % moves.len()].clone();
+            let before = pos.clone();
+            pos.play_unchecked(&m);
+
+            if before.castling_rights() == pos.castling_rights() {
+                assert!(unmoves(&pos).contains(&m), "missing unmove {:?}", m);
+                assert_eq!(unplay(&pos, &m), Some(before));
+            }
+            let mut count = 0;
+            for (unmove, mut predecessor) in predecessors(&pos) {
+                predecessor.play_unchecked(&unmove);
+                assert_eq!(predecessor, pos);
+                count += 1;
+            }
+            assert_eq!(count, unmoves(&pos).len());
+        }
+    }
+}
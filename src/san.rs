@@ -74,13 +74,29 @@ use crate::types::{CastlingSide, Move, Role};
 use crate::position::{Outcome, Position};
 use crate::movelist::MoveList;
 
+use std::cmp::min;
 use std::fmt;
+use std::fmt::Write as _;
+use std::ops::Range;
 use std::str::FromStr;
 use std::error::Error;
 
 /// Error when parsing a syntactially invalid SAN.
-#[derive(Clone, Debug)]
-pub struct ParseSanError;
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseSanError {
+    span: Range<usize>,
+}
+
+impl ParseSanError {
+    fn new(span: Range<usize>) -> ParseSanError {
+        ParseSanError { span }
+    }
+
+    /// Byte range of the offending token in the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
 
 impl fmt::Display for ParseSanError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -124,6 +140,28 @@ impl Error for SanError {
     }
 }
 
+/// How much of the origin square of piece moves to include in a [`San`].
+///
+/// Pawn moves are not affected: Pawn captures always include the origin
+/// file and pawn pushes never include any part of the origin square.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum DisambiguationPolicy {
+    /// Only as much as needed to distinguish the move from other legal
+    /// moves, like `Nf3` or `Nbd2`.
+    Minimal,
+    /// Always include the origin file, like `Ngf3`, and also the origin
+    /// rank if it is needed, like `Ra1a2`.
+    File,
+    /// Always include the origin square, like `Ng1f3`.
+    Square,
+}
+
+impl Default for DisambiguationPolicy {
+    fn default() -> DisambiguationPolicy {
+        DisambiguationPolicy::Minimal
+    }
+}
+
 /// A move in Standard Algebraic Notation.
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub enum San {
@@ -140,17 +178,39 @@ pub enum San {
     Null,
 }
 
+/// Splits a trailing move annotation like `!?` or `$14` from a SAN.
+fn split_nag(san: &[u8]) -> Result<(&[u8], Option<Nag>), ParseSanError> {
+    let glyph_start = if let Some(dollar) = san.iter().rposition(|&ch| ch == b'$') {
+        dollar
+    } else {
+        san.len() - san.iter().rev().take_while(|&&ch| ch == b'!' || ch == b'?').count()
+    };
+
+    if glyph_start == san.len() {
+        Ok((san, None))
+    } else {
+        let nag = Nag::from_ascii(&san[glyph_start..]).map_err(|_| ParseSanError::new(glyph_start..san.len()))?;
+        Ok((&san[..glyph_start], Some(nag)))
+    }
+}
+
 impl San {
-    /// Parses a SAN. Ignores a possible check or checkmate suffix.
+    /// Parses a SAN. Ignores a possible check or checkmate suffix and
+    /// move annotation (see [`SanPlus`]).
     ///
     /// # Errors
     ///
     /// Returns [`ParseSanError`] if `san` is not syntactically valid.
-    pub fn from_ascii(mut san: &[u8]) -> Result<San, ParseSanError> {
+    pub fn from_ascii(san: &[u8]) -> Result<San, ParseSanError> {
+        let (mut san, _) = split_nag(san)?;
+
         if san.ends_with(b"#") || san.ends_with(b"+") {
             san = &san[0..(san.len() - 1)];
         }
 
+        // Span of the byte at idx, or the empty span at the end of the input.
+        let at = |idx: usize| ParseSanError::new(idx..min(idx + 1, san.len()));
+
         if san == b"--" {
             Ok(San::Null)
         } else if san == b"O-O" {
@@ -160,63 +220,76 @@ impl San {
         } else if san.len() == 3 && san[0] == b'@' {
             Ok(San::Put {
                 role: Role::Pawn,
-                to: Square::from_ascii(&san[1..]).map_err(|_| ParseSanError)?,
+                to: Square::from_ascii(&san[1..]).map_err(|_| ParseSanError::new(1..3))?,
             })
         } else if san.len() == 4 && san[1] == b'@' {
             Ok(San::Put {
-                role: Role::from_char(char::from(san[0])).ok_or(ParseSanError)?,
-                to: Square::from_ascii(&san[2..]).map_err(|_| ParseSanError)?,
+                role: Role::from_char(char::from(san[0])).ok_or_else(|| at(0))?,
+                to: Square::from_ascii(&san[2..]).map_err(|_| ParseSanError::new(2..4))?,
             })
         } else {
-            let mut chars = san.iter().cloned();
-
-            let (role, next) = {
-                let ch = chars.next().ok_or(ParseSanError)?;
-                if ch >= b'a' {
-                    (Role::Pawn, ch)
-                } else {
-                    (Role::from_char(char::from(ch)).ok_or(ParseSanError)?, chars.next().ok_or(ParseSanError)?)
+            let file_at = |idx: usize| san.get(idx).and_then(|&ch| File::from_char(char::from(ch)));
+            let rank_at = |idx: usize| san.get(idx).and_then(|&ch| Rank::from_char(char::from(ch)));
+            let square_at = |idx: usize| Ok(Square::from_coords(
+                file_at(idx).ok_or_else(|| at(idx))?,
+                rank_at(idx + 1).ok_or_else(|| at(idx + 1))?,
+            ));
+
+            let mut idx = 0;
+
+            let role = match san.first() {
+                Some(&ch) if ch >= b'a' => Role::Pawn,
+                Some(&ch) => {
+                    idx += 1;
+                    Role::from_char(char::from(ch)).ok_or_else(|| at(0))?
                 }
+                None => return Err(at(0)),
             };
 
-            let (file, next) = if let Some(file) = File::from_char(char::from(next)) {
-                (Some(file), chars.next().ok_or(ParseSanError)?)
-            } else {
-                (None, next)
-            };
+            if idx >= san.len() {
+                return Err(at(idx));
+            }
 
-            let (rank, next) = if let Some(rank) = Rank::from_char(char::from(next)) {
-                (Some(rank), chars.next())
-            } else {
-                (None, Some(next))
-            };
+            let file = file_at(idx);
+            if file.is_some() {
+                idx += 1;
+                if idx >= san.len() {
+                    return Err(at(idx));
+                }
+            }
+
+            let rank = rank_at(idx);
+            if rank.is_some() {
+                idx += 1;
+            }
 
-            // This section is safe, because coordinates are already validated
-            // by file_from_char or rank_from_char.
-            let (capture, file, rank, to, next) = if let Some(next) = next {
-                if next == b'x' {
-                    let to_file = chars.next().and_then(|ch| File::from_char(char::from(ch))).ok_or(ParseSanError)?;
-                    let to_rank = chars.next().and_then(|ch| Rank::from_char(char::from(ch))).ok_or(ParseSanError)?;
-                    let square = Square::from_coords(to_file, to_rank);
-                    (true, file, rank, square, chars.next())
-                } else if next == b'=' {
-                    let square = Square::from_coords(file.ok_or(ParseSanError)?, rank.ok_or(ParseSanError)?);
-                    (false, None, None, square, Some(b'='))
-                } else {
-                    let to_file = File::from_char(char::from(next)).ok_or(ParseSanError)?;
-                    let to_rank = chars.next().and_then(|ch| Rank::from_char(char::from(ch))).ok_or(ParseSanError)?;
-                    let square = Square::from_coords(to_file, to_rank);
-                    (false, file, rank, square, chars.next())
+            // Parse the destination square. Coordinates that were already
+            // consumed as disambiguation can be used as the destination.
+            let (capture, file, rank, to) = match san.get(idx) {
+                Some(b'x') => {
+                    let to = square_at(idx + 1)?;
+                    idx += 3;
+                    (true, file, rank, to)
+                }
+                Some(b'=') => {
+                    let square = Square::from_coords(file.ok_or_else(|| at(idx))?, rank.ok_or_else(|| at(idx))?);
+                    (false, None, None, square)
+                }
+                Some(_) => {
+                    let to = square_at(idx)?;
+                    idx += 2;
+                    (false, file, rank, to)
+                }
+                None => {
+                    let square = Square::from_coords(file.ok_or_else(|| at(idx))?, rank.ok_or_else(|| at(idx))?);
+                    (false, None, None, square)
                 }
-            } else {
-                let square = Square::from_coords(file.ok_or(ParseSanError)?, rank.ok_or(ParseSanError)?);
-                (false, None, None, square, None)
             };
 
-            let promotion = match next {
+            let promotion = match san.get(idx) {
                 Some(b'=') =>
-                    Some(chars.next().and_then(|r| Role::from_char(char::from(r))).ok_or(ParseSanError)?),
-                Some(_) => return Err(ParseSanError),
+                    Some(san.get(idx + 1).and_then(|&r| Role::from_char(char::from(r))).ok_or_else(|| at(idx + 1))?),
+                Some(_) => return Err(ParseSanError::new(idx..san.len())),
                 None => None,
             };
 
@@ -226,6 +299,32 @@ impl San {
 
     /// Converts a move to Standard Algebraic Notation.
     pub fn from_move<P: Position>(pos: &P, m: &Move) -> San {
+        San::from_move_with_policy(pos, m, DisambiguationPolicy::Minimal)
+    }
+
+    /// Converts a move to Standard Algebraic Notation, disambiguating
+    /// piece moves according to `policy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Move, Role, Square};
+    /// use shakmaty::san::{DisambiguationPolicy, San};
+    ///
+    /// let pos = Chess::default();
+    /// let m = Move::Normal {
+    ///     role: Role::Knight,
+    ///     from: Square::G1,
+    ///     capture: None,
+    ///     to: Square::F3,
+    ///     promotion: None,
+    /// };
+    ///
+    /// assert_eq!(San::from_move_with_policy(&pos, &m, DisambiguationPolicy::Minimal).to_string(), "Nf3");
+    /// assert_eq!(San::from_move_with_policy(&pos, &m, DisambiguationPolicy::File).to_string(), "Ngf3");
+    /// assert_eq!(San::from_move_with_policy(&pos, &m, DisambiguationPolicy::Square).to_string(), "Ng1f3");
+    /// ```
+    pub fn from_move_with_policy<P: Position>(pos: &P, m: &Move, policy: DisambiguationPolicy) -> San {
         let mut legals = MoveList::new();
 
         match *m {
@@ -234,7 +333,7 @@ impl San {
             _ => (),
         }
 
-        San::disambiguate(m, &legals)
+        San::disambiguate_with_policy(m, &legals, policy)
     }
 
     /// Tries to convert the `San` to a legal move in the context of a
@@ -281,6 +380,12 @@ impl San {
     }
 
     pub fn disambiguate(m: &Move, moves: &MoveList) -> San {
+        San::disambiguate_with_policy(m, moves, DisambiguationPolicy::Minimal)
+    }
+
+    /// Like [`San::disambiguate()`], but adds the origin file or square of
+    /// piece moves as required by `policy`.
+    pub fn disambiguate_with_policy(m: &Move, moves: &MoveList, policy: DisambiguationPolicy) -> San {
         match *m {
             Move::Normal { role: Role::Pawn, from, capture, to, promotion } =>
                 San::Normal {
@@ -309,6 +414,12 @@ impl San {
                     _ => (rank, file)
                 });
 
+                let (rank, file) = match policy {
+                    DisambiguationPolicy::Minimal => (rank, file),
+                    DisambiguationPolicy::File => (rank, true),
+                    DisambiguationPolicy::Square => (true, true),
+                };
+
                 San::Normal {
                     role,
                     file: if file { Some(from.file()) } else { None },
@@ -401,7 +512,7 @@ impl San {
                     _ => false,
                 }
             },
-            San::Castle(side) => m.castling_side().map_or(false, |s| side == s),
+            San::Castle(side) => m.castling_side() == Some(side),
             San::Put { role, to } => {
                 match *m {
                     Move::Put { role: r, to: t } =>
@@ -415,20 +526,86 @@ impl San {
 }
 
 
+/// Parses a SAN that may use figurines like `♘f3` instead of piece letters.
+fn from_figurines<T, F>(san: &str, parse: F) -> Result<T, ParseSanError>
+where
+    F: FnOnce(&[u8]) -> Result<T, ParseSanError>,
+{
+    if san.is_ascii() {
+        return parse(san.as_bytes());
+    }
+
+    // Keep track of the original byte offsets to report error spans in
+    // terms of the input.
+    let mut ascii = Vec::with_capacity(san.len());
+    let mut offsets = Vec::with_capacity(san.len() + 1);
+    for (idx, ch) in san.char_indices() {
+        let replacement = match ch {
+            '♔' | '♚' => Some(b'K'),
+            '♕' | '♛' => Some(b'Q'),
+            '♖' | '♜' => Some(b'R'),
+            '♗' | '♝' => Some(b'B'),
+            '♘' | '♞' => Some(b'N'),
+            '♙' | '♟' => None,
+            _ if ch.is_ascii() => Some(ch as u8),
+            _ => return Err(ParseSanError::new(idx..idx + ch.len_utf8())),
+        };
+        if let Some(replacement) = replacement {
+            ascii.push(replacement);
+            offsets.push(idx);
+        }
+    }
+    offsets.push(san.len());
+
+    parse(&ascii).map_err(|err| ParseSanError::new(offsets[err.span.start]..offsets[err.span.end]))
+}
+
+fn figurine(role: Role) -> char {
+    match role {
+        Role::Pawn => '♙',
+        Role::Knight => '♘',
+        Role::Bishop => '♗',
+        Role::Rook => '♖',
+        Role::Queen => '♕',
+        Role::King => '♔',
+    }
+}
+
 impl FromStr for San {
     type Err = ParseSanError;
 
+    /// Parses a SAN, also accepting figurine algebraic notation.
     fn from_str(san: &str) -> Result<San, ParseSanError> {
-        San::from_ascii(san.as_bytes())
+        from_figurines(san, San::from_ascii)
     }
 }
 
-impl fmt::Display for San {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl San {
+    /// Displays the SAN in figurine algebraic notation, like `♘f3`. White
+    /// figurines are used for both sides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::san::San;
+    ///
+    /// let san: San = "♕xd5".parse()?;
+    /// assert_eq!(san.to_string(), "Qxd5");
+    /// assert_eq!(san.figurine().to_string(), "♕xd5");
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    pub fn figurine(&self) -> Figurine<'_, San> {
+        Figurine { inner: self }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, role_char: fn(Role) -> char) -> fmt::Result {
         match *self {
             San::Normal { role, file, rank, capture, to, promotion } => {
                 if role != Role::Pawn {
-                    write!(f, "{}", role.upper_char())?;
+                    write!(f, "{}", role_char(role))?;
                 }
                 if let Some(file) = file {
                     write!(f, "{}", file.char())?;
@@ -441,19 +618,44 @@ impl fmt::Display for San {
                 }
                 write!(f, "{}", to)?;
                 if let Some(promotion) = promotion {
-                    write!(f, "={}", promotion.upper_char())?;
+                    write!(f, "={}", role_char(promotion))?;
                 }
                 Ok(())
             },
             San::Castle(CastlingSide::KingSide) => write!(f, "O-O"),
             San::Castle(CastlingSide::QueenSide) => write!(f, "O-O-O"),
             San::Put { role: Role::Pawn, to } => write!(f, "@{}", to),
-            San::Put { role, to } => write!(f, "{}@{}", role.upper_char(), to),
+            San::Put { role, to } => write!(f, "{}@{}", role_char(role), to),
             San::Null => write!(f, "--"),
         }
     }
 }
 
+impl fmt::Display for San {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Role::upper_char)
+    }
+}
+
+/// Displays a [`San`] or [`SanPlus`] in figurine algebraic notation.
+#[derive(Debug)]
+pub struct Figurine<'a, T> {
+    inner: &'a T,
+}
+
+impl fmt::Display for Figurine<'_, San> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.write(f, figurine)
+    }
+}
+
+impl fmt::Display for Figurine<'_, SanPlus> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.san.write(f, figurine)?;
+        self.inner.write_suffix_and_nag(f)
+    }
+}
+
 /// Check (`+`) or checkmate (`#`) suffix.
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub enum Suffix {
@@ -494,23 +696,46 @@ impl fmt::Display for Suffix {
     }
 }
 
-/// A [`San`] and possible check and checkmate suffixes.
+/// A [`San`] and possible check and checkmate suffixes, followed by an
+/// optional move annotation like `!?` or `$14`.
+///
+/// When displayed, the annotation is only written if it is one of the
+/// traditional move annotations like `!?`. Other NAGs are left to the PGN
+/// layer, which writes them as separate tokens.
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct SanPlus {
     pub san: San,
     pub suffix: Option<Suffix>,
+    pub nag: Option<Nag>,
 }
 
 impl SanPlus {
-    /// Parses a SAN and possible check and checkmate suffix.
+    /// Parses a SAN and possible check and checkmate suffix and move
+    /// annotation.
     ///
     /// # Errors
     ///
     /// Returns [`ParseSanError`] if `san` is not syntactically valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::san::{Nag, SanPlus, Suffix};
+    ///
+    /// let san: SanPlus = "Qxf7#!!".parse()?;
+    /// assert_eq!(san.suffix, Some(Suffix::Checkmate));
+    /// assert_eq!(san.nag, Some(Nag::BRILLIANT_MOVE));
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
     pub fn from_ascii(san: &[u8]) -> Result<SanPlus, ParseSanError> {
-        San::from_ascii(san).map(|result| SanPlus {
+        let (without_nag, nag) = split_nag(san)?;
+        San::from_ascii(without_nag).map(|result| SanPlus {
             san: result,
-            suffix: san.last().cloned().and_then(|ch| Suffix::from_char(char::from(ch))),
+            suffix: without_nag.last().cloned().and_then(|ch| Suffix::from_char(char::from(ch))),
+            nag,
         })
     }
 
@@ -529,21 +754,13 @@ impl SanPlus {
         SanPlus {
             san,
             suffix: Suffix::from_position(pos),
+            nag: None,
         }
     }
 
     pub fn from_move<P: Position>(mut pos: P, m: &Move) -> SanPlus {
         let mut moves = MoveList::new();
-        match *m {
-            Move::Normal { role, to, .. } | Move::Put { role, to } =>
-                pos.san_candidates(role, to, &mut moves),
-            Move::EnPassant { to, .. } =>
-                pos.san_candidates(Role::Pawn, to, &mut moves),
-            Move::Castle { king, rook } if king.file() < rook.file() =>
-                pos.castling_moves(CastlingSide::KingSide, &mut moves),
-            Move::Castle { .. } =>
-                pos.castling_moves(CastlingSide::QueenSide, &mut moves),
-        }
+        pos.move_candidates(m, &mut moves);
         SanPlus {
             san: San::disambiguate(m, &moves),
             suffix: if moves.contains(m) {
@@ -551,7 +768,8 @@ impl SanPlus {
                 Suffix::from_position(&pos)
             } else {
                 None
-            }
+            },
+            nag: None,
         }
     }
 }
@@ -559,25 +777,227 @@ impl SanPlus {
 impl FromStr for SanPlus {
     type Err = ParseSanError;
 
+    /// Parses a SAN, also accepting figurine algebraic notation.
     fn from_str(san: &str) -> Result<SanPlus, ParseSanError> {
-        SanPlus::from_ascii(san.as_bytes())
+        from_figurines(san, SanPlus::from_ascii)
+    }
+}
+
+impl SanPlus {
+    /// Displays the SAN in figurine algebraic notation, like `♘f3+`.
+    pub fn figurine(&self) -> Figurine<'_, SanPlus> {
+        Figurine { inner: self }
+    }
+
+    fn write_suffix_and_nag(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(suffix) = self.suffix {
+            write!(f, "{}", suffix)?;
+        }
+        if let Some(glyph) = self.nag.and_then(Nag::glyph) {
+            f.write_str(glyph)?;
+        }
+        Ok(())
     }
 }
 
 impl fmt::Display for SanPlus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.san)?;
-        if let Some(suffix) = self.suffix {
-            write!(f, "{}", suffix)?;
+        self.write_suffix_and_nag(f)
+    }
+}
+
+/// Error when parsing an invalid NAG.
+#[derive(Clone, Debug)]
+pub struct ParseNagError;
+
+impl fmt::Display for ParseNagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "invalid nag".fmt(f)
+    }
+}
+
+impl Error for ParseNagError {
+    fn description(&self) -> &str {
+        "invalid nag"
+    }
+}
+
+/// A numeric annotation glyph like `$1` or `!`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash)]
+pub struct Nag(pub u8);
+
+impl Nag {
+    /// A good move (`!`).
+    pub const GOOD_MOVE: Nag = Nag(1);
+    /// A mistake (`?`).
+    pub const MISTAKE: Nag = Nag(2);
+    /// A brilliant move (`!!`).
+    pub const BRILLIANT_MOVE: Nag = Nag(3);
+    /// A blunder (`??`).
+    pub const BLUNDER: Nag = Nag(4);
+    /// A speculative move (`!?`).
+    pub const SPECULATIVE_MOVE: Nag = Nag(5);
+    /// A dubious move (`?!`).
+    pub const DUBIOUS_MOVE: Nag = Nag(6);
+
+    /// Parses a NAG like `$1` or one of the traditional move annotations
+    /// like `!?`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseNagError`] if the input is neither known move
+    /// annotation nor a valid NAG.
+    pub fn from_ascii(s: &[u8]) -> Result<Nag, ParseNagError> {
+        match s {
+            b"!" => Ok(Nag::GOOD_MOVE),
+            b"?" => Ok(Nag::MISTAKE),
+            b"!!" => Ok(Nag::BRILLIANT_MOVE),
+            b"??" => Ok(Nag::BLUNDER),
+            b"!?" => Ok(Nag::SPECULATIVE_MOVE),
+            b"?!" => Ok(Nag::DUBIOUS_MOVE),
+            [b'$', digits @ ..] if !digits.is_empty() && digits.iter().all(u8::is_ascii_digit) =>
+                btoi::btou(digits).map(Nag).map_err(|_| ParseNagError),
+            _ => Err(ParseNagError),
+        }
+    }
+
+    /// Gets the traditional move annotation like `!?`, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::san::Nag;
+    ///
+    /// assert_eq!(Nag::SPECULATIVE_MOVE.glyph(), Some("!?"));
+    /// assert_eq!(Nag(14).glyph(), None);
+    /// ```
+    pub fn glyph(self) -> Option<&'static str> {
+        Some(match self {
+            Nag::GOOD_MOVE => "!",
+            Nag::MISTAKE => "?",
+            Nag::BRILLIANT_MOVE => "!!",
+            Nag::BLUNDER => "??",
+            Nag::SPECULATIVE_MOVE => "!?",
+            Nag::DUBIOUS_MOVE => "?!",
+            _ => return None,
+        })
+    }
+}
+
+impl FromStr for Nag {
+    type Err = ParseNagError;
+
+    fn from_str(nag: &str) -> Result<Nag, ParseNagError> {
+        Nag::from_ascii(nag.as_bytes())
+    }
+}
+
+impl fmt::Display for Nag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${}", self.0)
+    }
+}
+
+/// Writes a sequence of moves as numbered SAN, starting from a given
+/// position.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// #
+/// use shakmaty::{CastlingMode, Chess};
+/// use shakmaty::fen::Fen;
+/// use shakmaty::san::{Line, Nag, San};
+///
+/// let pos: Chess = "rnb1kbnr/ppp1pppp/8/3q4/8/2N5/PPPP1PPP/R1BQKBNR b KQkq - 1 3"
+///     .parse::<Fen>()?
+///     .position(CastlingMode::Standard)?;
+///
+/// let mut line = Line::new(pos);
+/// for san in &["Qa5", "d4", "Nf6"] {
+///     let m = san.parse::<San>()?.to_move(line.position())?;
+///     line.push(&m)?;
+/// }
+/// line.push_nag(Nag::GOOD_MOVE);
+///
+/// assert_eq!(line.to_string(), "3... Qa5 4. d4 Nf6 $1");
+/// #
+/// # Ok::<_, Box<dyn Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Line<P> {
+    pos: P,
+    text: String,
+}
+
+impl<P: Position> Line<P> {
+    /// Starts an empty line from `pos`.
+    pub fn new(pos: P) -> Line<P> {
+        Line {
+            pos,
+            text: String::new(),
+        }
+    }
+
+    /// The position after the moves of the line.
+    pub fn position(&self) -> &P {
+        &self.pos
+    }
+
+    /// Appends a move to the line and plays it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SanError::IllegalSan`] if the move is not legal in the
+    /// current position.
+    pub fn push(&mut self, m: &Move) -> Result<(), SanError> {
+        if !self.pos.is_legal(m) {
+            return Err(SanError::IllegalSan);
         }
+
+        let turn = self.pos.turn();
+        if turn.is_white() || self.text.is_empty() {
+            if !self.text.is_empty() {
+                self.text.push(' ');
+            }
+            let _ = write!(self.text, "{}{}",
+                           self.pos.fullmoves(),
+                           turn.fold(". ", "... "));
+        } else {
+            self.text.push(' ');
+        }
+
+        let san = SanPlus::from_move_and_play_unchecked(&mut self.pos, m);
+        let _ = write!(self.text, "{}", san);
         Ok(())
     }
+
+    /// Appends a NAG after the last move.
+    pub fn push_nag(&mut self, nag: Nag) {
+        let _ = write!(self.text, " {}", nag);
+    }
+
+    /// The numbered SAN text of the line.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl<P> fmt::Display for Line<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::mem;
+    use crate::fen::Fen;
+    use crate::position::Chess;
+    use crate::types::CastlingMode;
 
     #[test]
     fn test_read_write() {
@@ -592,9 +1012,98 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_error_span() {
+        assert_eq!("".parse::<San>().expect_err("empty").span(), 0..0);
+        assert_eq!("Xe4".parse::<San>().expect_err("invalid role").span(), 0..1);
+        assert_eq!("Nxe9".parse::<San>().expect_err("invalid rank").span(), 3..4);
+        assert_eq!("e8=X+".parse::<San>().expect_err("invalid promotion").span(), 3..4);
+        assert_eq!("e4?!?".parse::<San>().expect_err("invalid annotation").span(), 2..5);
+        assert_eq!("Z@e4".parse::<San>().expect_err("invalid drop").span(), 0..1);
+    }
+
+    #[test]
+    fn test_annotations() {
+        let san: SanPlus = "e4?!".parse().expect("valid san");
+        assert_eq!(san.san, "e4".parse::<San>().expect("valid san"));
+        assert_eq!(san.suffix, None);
+        assert_eq!(san.nag, Some(Nag::DUBIOUS_MOVE));
+        assert_eq!(san.to_string(), "e4?!");
+
+        let san: SanPlus = "O-O+$14".parse().expect("valid san");
+        assert_eq!(san.suffix, Some(Suffix::Check));
+        assert_eq!(san.nag, Some(Nag(14)));
+        assert_eq!(san.to_string(), "O-O+");
+
+        assert_eq!("e8=Q#??".parse::<San>().expect("valid san").to_string(), "e8=Q");
+        assert_eq!("Nf3$".parse::<San>().expect_err("empty nag").span(), 3..4);
+    }
+
+    #[test]
+    fn test_figurines() {
+        for (fan, san) in &[("♘f3", "Nf3"), ("♞xd5+", "Nxd5+"), ("♙e4", "e4"), ("e8=♕#", "e8=Q#"), ("♘@f3", "N@f3")] {
+            let parsed: SanPlus = fan.parse().expect("valid fan");
+            assert_eq!(parsed.to_string(), *san);
+        }
+        assert_eq!("e8=♛".parse::<SanPlus>().expect("valid fan").figurine().to_string(), "e8=♕");
+        assert_eq!("♘x♘3".parse::<San>().expect_err("invalid rank").span(), 4..7);
+        assert_eq!("♘f3€".parse::<San>().expect_err("unknown symbol").span(), 5..8);
+    }
+
+    #[test]
+    fn test_nag() {
+        assert_eq!("!?".parse::<Nag>().expect("valid nag"), Nag::SPECULATIVE_MOVE);
+        assert_eq!("$140".parse::<Nag>().expect("valid nag").to_string(), "$140");
+        assert!("$".parse::<Nag>().is_err());
+        assert!("$256".parse::<Nag>().is_err());
+        assert!("!!!".parse::<Nag>().is_err());
+    }
+
+    #[test]
+    fn test_line() {
+        use crate::position::Chess;
+
+        let mut line = Line::new(Chess::default());
+        for san in &["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"] {
+            let m = san.parse::<San>().expect("valid san").to_move(line.position()).expect("legal move");
+            line.push(&m).expect("legal move");
+        }
+        assert_eq!(line.as_str(), "1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7#");
+
+        let m = Move::Put { role: Role::Knight, to: Square::E4 };
+        assert_eq!(line.push(&m), Err(SanError::IllegalSan));
+    }
+
+    #[test]
+    fn test_disambiguation_policy_round_trip() {
+        let pos: Chess = "4k3/8/8/8/R7/8/8/R3K3 w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+
+        let m = Move::Normal {
+            role: Role::Rook,
+            from: Square::A1,
+            capture: None,
+            to: Square::A2,
+            promotion: None,
+        };
+
+        for &(policy, expected) in &[
+            (DisambiguationPolicy::Minimal, "R1a2"),
+            (DisambiguationPolicy::File, "Ra1a2"),
+            (DisambiguationPolicy::Square, "Ra1a2"),
+        ] {
+            let san = San::from_move_with_policy(&pos, &m, policy);
+            assert_eq!(san.to_string(), expected);
+            let parsed: San = expected.parse().expect("valid san");
+            assert_eq!(parsed.to_move(&pos), Ok(m.clone()));
+        }
+    }
+
     #[test]
     fn test_size() {
         assert!(mem::size_of::<San>() <= 8);
-        assert!(mem::size_of::<SanPlus>() <= 8);
+        assert!(mem::size_of::<SanPlus>() <= 10); // including annotation
     }
 }
@@ -55,8 +55,17 @@ pub trait Setup {
     fn castling_rights(&self) -> Bitboard;
 
     /// En passant target square on the third or sixth rank.
+    ///
+    /// Positions only report the square if there is a legal en passant
+    /// capture.
     fn ep_square(&self) -> Option<Square>;
 
+    /// En passant target square after a double pawn push, even if there is
+    /// no en passant capture. Defaults to [`Setup::ep_square()`].
+    fn maybe_ep_square(&self) -> Option<Square> {
+        self.ep_square()
+    }
+
     /// Remaining checks in chess variants like Three-Check.
     fn remaining_checks(&self) -> Option<&RemainingChecks>;
 
@@ -181,6 +190,48 @@ pub trait Setup {
     fn their(&self, role: Role) -> Bitboard {
         self.them() & self.board().by_role(role)
     }
+
+    /// Tests if the position is unchanged when mirrored at the vertical
+    /// center line, so that the a-file becomes the h-file
+    /// (see [`Board::flip_horizontal()`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Setup};
+    ///
+    /// let pos = Chess::default();
+    /// assert!(!pos.is_mirror_symmetric()); // queen and king
+    /// ```
+    fn is_mirror_symmetric(&self) -> bool {
+        let mut board = self.board().clone();
+        board.flip_horizontal();
+        board == *self.board() &&
+            self.castling_rights().flip_horizontal() == self.castling_rights() &&
+            self.ep_square().map(Square::flip_horizontal) == self.ep_square()
+    }
+
+    /// Tests if the position looks the same from both sides: Mirroring
+    /// the board vertically and swapping the colors of all pieces
+    /// (see [`Board::mirror()`]) does not change the board, castling rights,
+    /// pockets and remaining checks. The side to move is not considered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Setup};
+    ///
+    /// let pos = Chess::default();
+    /// assert!(pos.is_color_symmetric());
+    /// ```
+    fn is_color_symmetric(&self) -> bool {
+        let mut board = self.board().clone();
+        board.mirror();
+        board == *self.board() &&
+            self.castling_rights().flip_vertical() == self.castling_rights() &&
+            self.pockets().map_or(true, |p| p.white == p.black) &&
+            self.remaining_checks().map_or(true, |r| r.white == r.black)
+    }
 }
 
 pub(crate) struct SwapTurn<S: Setup>(pub S);
@@ -409,4 +460,21 @@ mod tests {
     use super::*;
 
     struct _AssertObjectSafe(Box<dyn Setup>);
+
+    #[test]
+    fn test_symmetry() {
+        use crate::fen::Fen;
+
+        let fen: Fen = "2r1k1r1/8/8/8/8/8/8/2R1K1R1 w - - 0 1".parse().expect("valid fen");
+        assert!(fen.is_color_symmetric());
+        assert!(!fen.is_mirror_symmetric());
+
+        let fen: Fen = "8/8/2k2k2/8/8/2K2K2/8/8 b - - 0 1".parse().expect("valid fen");
+        assert!(fen.is_color_symmetric());
+        assert!(fen.is_mirror_symmetric());
+
+        let fen: Fen = "8/8/2k2k2/8/8/2K2K2/8/8[Qq] w - - 2+3 0 1".parse().expect("valid fen");
+        assert!(!fen.is_color_symmetric());
+        assert!(fen.is_mirror_symmetric());
+    }
 }
@@ -42,7 +42,7 @@ macro_rules! try_from_number_impl {
             #[allow(unused_comparisons)]
             #[allow(clippy::cast_lossless)]
             fn try_from(value: $t) -> Result<$type, Self::Error> {
-                if $lower <= value && value < $upper {
+                if ($lower..$upper).contains(&value) {
                     Ok(<$type>::new(value as u32))
                 } else {
                     Err(<$error>::from(()))
@@ -87,7 +87,7 @@ impl File {
 
     #[inline]
     pub fn from_char(ch: char) -> Option<File> {
-        if 'a' <= ch && ch <= 'h' {
+        if ('a'..='h').contains(&ch) {
             Some(File::new(u32::from(ch as u8 - b'a')))
         } else {
             None
@@ -178,7 +178,7 @@ impl Rank {
 
     #[inline]
     pub fn from_char(ch: char) -> Option<Rank> {
-        if '1' <= ch && ch <= '8' {
+        if ('1'..='8').contains(&ch) {
             Some(Rank::new(u32::from(ch as u8 - b'1')))
         } else {
             None
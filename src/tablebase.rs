@@ -0,0 +1,164 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Interface for endgame tablebases.
+//!
+//! Implement [`TablebaseProbe`] for Syzygy, Gaviota or network-backed
+//! tablebases, to let code like [`adjudicate()`] consult them.
+//!
+//! # Examples
+//!
+//! ```
+//! use shakmaty::{CastlingMode, Chess, Color, Outcome, Position, Setup};
+//! use shakmaty::fen::Fen;
+//! use shakmaty::kpk;
+//! use shakmaty::tablebase::{adjudicate, TablebaseProbe, Wdl};
+//!
+//! /// Knows only positions with a white pawn.
+//! struct Kpk;
+//!
+//! impl TablebaseProbe<Chess> for Kpk {
+//!     fn probe_wdl(&self, pos: &Chess) -> Option<Wdl> {
+//!         let board = pos.board();
+//!         if board.occupied().count() != 3 || (board.pawns() & board.white()).count() != 1 {
+//!             return None;
+//!         }
+//!         let pawn = board.pawns().first()?;
+//!         let win = kpk::probe(board.king_of(Color::White)?, pawn, board.king_of(Color::Black)?, pos.turn());
+//!         Some(match (win, pos.turn()) {
+//!             (false, _) => Wdl::Draw,
+//!             (true, Color::White) => Wdl::Win,
+//!             (true, Color::Black) => Wdl::Loss,
+//!         })
+//!     }
+//! }
+//!
+//! assert_eq!(adjudicate(&Chess::default(), &Kpk), None);
+//!
+//! // Rook pawn against the king in the corner.
+//! let pos: Chess = "7k/8/8/7P/8/8/8/K7 w - - 0 1".parse::<Fen>()?.position(CastlingMode::Standard)?;
+//! assert_eq!(adjudicate(&pos, &Kpk), Some(Outcome::Draw));
+//!
+//! let pos: Chess = "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1".parse::<Fen>()?.position(CastlingMode::Standard)?;
+//! assert_eq!(adjudicate(&pos, &Kpk), Some(Outcome::Decisive { winner: Color::White }));
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::ops::Neg;
+
+use crate::position::{Outcome, Position};
+use crate::types::Color;
+
+/// Win-draw-loss value of a position, from the point of view of the side
+/// to move.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Wdl {
+    Loss,
+    /// Loss that can be saved by the 50-move rule.
+    BlessedLoss,
+    Draw,
+    /// Win that can be frustrated by the 50-move rule.
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    /// The outcome with best play, if `turn` is to move and the 50-move
+    /// rule applies.
+    pub fn outcome(self, turn: Color) -> Outcome {
+        match self {
+            Wdl::Win => Outcome::Decisive { winner: turn },
+            Wdl::Loss => Outcome::Decisive { winner: !turn },
+            Wdl::BlessedLoss | Wdl::Draw | Wdl::CursedWin => Outcome::Draw,
+        }
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Wdl {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::BlessedLoss => Wdl::CursedWin,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::CursedWin => Wdl::BlessedLoss,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+/// A source of endgame tablebase information for positions of type `P`.
+///
+/// Each method returns `None` if the position is not covered, or if
+/// probing failed. Only [`TablebaseProbe::probe_wdl()`] is required.
+pub trait TablebaseProbe<P> {
+    /// Probes the win-draw-loss value of a position.
+    fn probe_wdl(&self, pos: &P) -> Option<Wdl>;
+
+    /// Probes the distance to zeroing (capture or pawn move) in plies,
+    /// with the sign of the win-draw-loss value, as in Syzygy
+    /// tablebases.
+    fn probe_dtz(&self, _pos: &P) -> Option<i32> {
+        None
+    }
+
+    /// Probes the distance to mate in plies, positive if the side to move
+    /// is winning, negative if it is losing, and `0` for draws.
+    fn probe_dtm(&self, _pos: &P) -> Option<i32> {
+        None
+    }
+}
+
+/// The outcome of a game, if it is over or decided according to the
+/// tablebase, assuming best play and the 50-move rule.
+pub fn adjudicate<P, T>(pos: &P, tablebase: &T) -> Option<Outcome>
+where
+    P: Position,
+    T: TablebaseProbe<P> + ?Sized,
+{
+    pos.outcome().or_else(|| tablebase.probe_wdl(pos).map(|wdl| wdl.outcome(pos.turn())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Chess;
+
+    struct AlwaysCursedWin;
+
+    impl TablebaseProbe<Chess> for AlwaysCursedWin {
+        fn probe_wdl(&self, _pos: &Chess) -> Option<Wdl> {
+            Some(Wdl::CursedWin)
+        }
+    }
+
+    #[test]
+    fn test_wdl() {
+        assert_eq!(-Wdl::Win, Wdl::Loss);
+        assert_eq!(-Wdl::BlessedLoss, Wdl::CursedWin);
+        assert_eq!(Wdl::Loss.outcome(Color::Black), Outcome::Decisive { winner: Color::White });
+        assert!(Wdl::CursedWin < Wdl::Win);
+    }
+
+    #[test]
+    fn test_adjudicate() {
+        let pos = Chess::default();
+        assert_eq!(adjudicate(&pos, &AlwaysCursedWin), Some(Outcome::Draw));
+        assert_eq!(AlwaysCursedWin.probe_dtz(&pos), None);
+        assert_eq!(AlwaysCursedWin.probe_dtm(&pos), None);
+    }
+}
@@ -522,6 +522,41 @@ impl CastlingMode {
     }
 }
 
+/// When to include the en passant square, e.g. in a FEN.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum EnPassantMode {
+    /// Only if there is a fully legal en passant capture.
+    Legal,
+    /// Only after a double pawn push with an enemy pawn next to the pushed
+    /// pawn, as in X-FEN and Polyglot.
+    PseudoLegal,
+    /// After every double pawn push, as in the FEN specification.
+    Always,
+}
+
+/// Which promotions to generate, e.g. in
+/// [`Position::legal_moves_with_promotions()`](crate::Position::legal_moves_with_promotions).
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum PromotionMode {
+    /// All promotions that are legal in the variant. Usually these are
+    /// promotions to queen, rook, bishop and knight, and additionally to
+    /// king in Antichess.
+    All,
+    /// Only promotions to queen, as used by some bulk tools and engines
+    /// that do not consider underpromotions.
+    QueenOnly,
+}
+
+impl PromotionMode {
+    /// Checks if a promotion to `role` is generated in this mode.
+    pub fn allows(self, role: Role) -> bool {
+        match self {
+            PromotionMode::All => true,
+            PromotionMode::QueenOnly => role == Role::Queen,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::mem;
@@ -87,14 +87,28 @@
 use std::fmt;
 use std::str::FromStr;
 use std::error::Error;
+use std::ops::Range;
 
 use crate::square::{Rank, Square};
 use crate::types::{CastlingMode, CastlingSide, Move, Role};
 use crate::position::Position;
 
 /// Error when parsing an invalid UCI.
-#[derive(Clone, Debug)]
-pub struct ParseUciError;
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseUciError {
+    span: Range<usize>,
+}
+
+impl ParseUciError {
+    fn new(span: Range<usize>) -> ParseUciError {
+        ParseUciError { span }
+    }
+
+    /// Byte range of the offending token in the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
 
 impl fmt::Display for ParseUciError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -189,24 +203,27 @@ impl Uci {
     /// ```
     pub fn from_ascii(uci: &[u8]) -> Result<Uci, ParseUciError> {
         if uci.len() != 4 && uci.len() != 5 {
-            return Err(ParseUciError);
+            return Err(ParseUciError::new(0..uci.len()));
         }
 
         if uci == b"0000" {
             return Ok(Uci::Null);
         }
 
-        let to = Square::from_ascii(&uci[2..4]).map_err(|_| ParseUciError)?;
+        let to = Square::from_ascii(&uci[2..4]).map_err(|_| ParseUciError::new(2..4))?;
 
         if uci[1] == b'@' {
-            Ok(Uci::Put { role: Role::from_char(char::from(uci[0])).ok_or(ParseUciError)?, to })
+            Ok(Uci::Put {
+                role: Role::from_char(char::from(uci[0])).ok_or_else(|| ParseUciError::new(0..1))?,
+                to,
+            })
         } else {
-            let from = Square::from_ascii(&uci[0..2]).map_err(|_| ParseUciError)?;
+            let from = Square::from_ascii(&uci[0..2]).map_err(|_| ParseUciError::new(0..2))?;
             if uci.len() == 5 {
                 Ok(Uci::Normal {
                     from,
                     to,
-                    promotion: Some(Role::from_char(char::from(uci[4])).ok_or(ParseUciError)?)
+                    promotion: Some(Role::from_char(char::from(uci[4])).ok_or_else(|| ParseUciError::new(4..5))?)
                 })
             } else {
                 Ok(Uci::Normal { from, to, promotion: None })
@@ -347,7 +364,14 @@ impl Move {
 mod tests {
     use super::*;
     use crate::position::Chess;
-    use crate::position::Crazyhouse;
+
+    #[test]
+    pub fn test_error_span() {
+        assert_eq!("e2e".parse::<Uci>().expect_err("too short").span(), 0..3);
+        assert_eq!("e2e9".parse::<Uci>().expect_err("invalid to").span(), 2..4);
+        assert_eq!("i2e4".parse::<Uci>().expect_err("invalid from").span(), 0..2);
+        assert_eq!("e7e8x".parse::<Uci>().expect_err("invalid promotion").span(), 4..5);
+    }
 
     #[test]
     pub fn test_uci_to_en_passant() {
@@ -365,8 +389,9 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "crazyhouse")]
     pub fn test_uci_to_crazyhouse() {
-        let mut pos = Crazyhouse::default();
+        let mut pos = crate::variants::Crazyhouse::default();
         let e4 = "e2e4".parse::<Uci>().expect("e4").to_move(&pos).expect("legal");
         pos.play_unchecked(&e4);
         let d5 = "d7d5".parse::<Uci>().expect("d5").to_move(&pos).expect("legal");
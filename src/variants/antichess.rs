@@ -0,0 +1,307 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroU32;
+
+use crate::board::Board;
+use crate::bitboard::Bitboard;
+use crate::square::Square;
+use crate::types::{CastlingMode, Color, Move, PromotionMode, RemainingChecks, Role, White};
+use crate::material::Material;
+use crate::setup::{Castles, EpSquare, Setup};
+use crate::movelist::MoveList;
+use crate::position::{FromSetup, Outcome, Position, PositionError, PositionErrorKinds};
+use crate::position::{do_move, gen_en_passant, gen_non_king, relevant_ep, validate, KingTag, Stepper};
+
+/// How a stalemate is scored in [`Antichess`].
+///
+/// Losers chess, where check rules still apply, is a separate variant:
+/// [`Losers`](super::Losers).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum AntichessRules {
+    /// The stalemated side wins, as on lichess.org and in FICS giveaway.
+    Giveaway,
+    /// The side with fewer pieces wins a stalemate, equal material is a
+    /// draw, as in FICS suicide.
+    Suicide,
+}
+
+impl Default for AntichessRules {
+    fn default() -> AntichessRules {
+        AntichessRules::Giveaway
+    }
+}
+
+/// An Antichess position. Antichess is also known as Giveaway, but players
+/// start without castling rights.
+///
+/// Stalemates are scored according to [`AntichessRules::Giveaway`], unless
+/// configured otherwise with [`Antichess::set_rules()`].
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{CastlingMode, Outcome, Position};
+/// use shakmaty::fen::Fen;
+/// use shakmaty::variants::{Antichess, AntichessRules};
+///
+/// // Black is stalemated, but has more pieces.
+/// let fen: Fen = "8/8/8/p7/p7/P7/8/8 b - - 0 1".parse()?;
+/// let mut pos: Antichess = fen.position(CastlingMode::Standard)?;
+/// assert_eq!(pos.outcome(), Some(Outcome::Decisive { winner: shakmaty::Color::Black }));
+///
+/// pos.set_rules(AntichessRules::Suicide);
+/// assert_eq!(pos.outcome(), Some(Outcome::Decisive { winner: shakmaty::Color::White }));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Antichess {
+    rules: AntichessRules,
+    board: Board,
+    turn: Color,
+    castles: Castles,
+    ep_square: Option<EpSquare>,
+    halfmoves: u32,
+    fullmoves: NonZeroU32,
+}
+
+impl Default for Antichess {
+    fn default() -> Antichess {
+        Antichess {
+            rules: AntichessRules::default(),
+            board: Board::default(),
+            turn: White,
+            castles: Castles::empty(CastlingMode::Standard),
+            ep_square: None,
+            halfmoves: 0,
+            fullmoves: NonZeroU32::new(1).unwrap(),
+        }
+    }
+}
+
+impl Antichess {
+    pub fn rules(&self) -> AntichessRules {
+        self.rules
+    }
+
+    /// Selects how stalemates are scored.
+    pub fn set_rules(&mut self, rules: AntichessRules) {
+        self.rules = rules;
+    }
+}
+
+impl Setup for Antichess {
+    fn board(&self) -> &Board { &self.board }
+    fn pockets(&self) -> Option<&Material> { None }
+    fn turn(&self) -> Color { self.turn }
+    fn castling_rights(&self) -> Bitboard { Bitboard(0) }
+    fn ep_square(&self) -> Option<Square> { self.ep_square.and_then(|ep| relevant_ep(ep, self)) }
+    fn maybe_ep_square(&self) -> Option<Square> { self.ep_square.map(Square::from) }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
+    fn halfmoves(&self) -> u32 { self.halfmoves }
+    fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
+}
+
+impl FromSetup for Antichess {
+    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Antichess, PositionError<Antichess>> {
+        let mut errors = PositionErrorKinds::empty();
+        let board = setup.board().clone();
+        let turn = setup.turn();
+
+        let ep_square = match EpSquare::from_setup(&board, turn, setup.ep_square()) {
+            Ok(ep_square) => ep_square,
+            Err(()) => {
+                errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS;
+                None
+            }
+        };
+
+        let pos = Antichess {
+            rules: AntichessRules::default(),
+            board,
+            turn,
+            castles: Castles::empty(mode),
+            ep_square,
+            halfmoves: setup.halfmoves(),
+            fullmoves: setup.fullmoves(),
+        };
+
+        if setup.castling_rights().any() {
+            errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS
+        }
+
+        errors |= validate(&pos)
+            - PositionErrorKinds::MISSING_KING
+            - PositionErrorKinds::TOO_MANY_KINGS
+            - PositionErrorKinds::OPPOSITE_CHECK
+            - PositionErrorKinds::IMPOSSIBLE_CHECK;
+
+        PositionError { errors, pos }.strict()
+    }
+}
+
+impl Position for Antichess {
+    fn play_unchecked(&mut self, m: &Move) {
+        do_move(&mut self.board, &mut self.turn, &mut self.castles,
+                &mut self.ep_square, &mut self.halfmoves,
+                &mut self.fullmoves, m);
+    }
+
+    fn castles(&self) -> &Castles {
+        &self.castles
+    }
+
+    fn en_passant_moves(&self, moves: &mut MoveList) {
+        moves.clear();
+        gen_en_passant(self.board(), self.turn, self.ep_square, moves);
+    }
+
+    fn capture_moves(&self, moves: &mut MoveList) {
+        self.gen_captures(PromotionMode::All, moves);
+    }
+
+    fn legal_moves(&self, moves: &mut MoveList) {
+        self.legal_moves_with_promotions(PromotionMode::All, moves);
+    }
+
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
+        self.gen_captures(mode, moves); // clears move list
+
+        if moves.is_empty() {
+            // No compulsory captures. Generate everything else.
+            gen_non_king(self, !self.board().occupied(), mode, moves);
+            add_king_promotions(mode, moves);
+            KingTag::gen_moves(self, !self.board().occupied(), moves);
+        }
+    }
+
+    fn king_attackers(&self, _square: Square, _attacker: Color, _occupied: Bitboard) -> Bitboard {
+        Bitboard(0)
+    }
+
+    fn gives_check(&self, _m: &Move) -> bool {
+        false
+    }
+
+    fn is_variant_end(&self) -> bool {
+        self.board().white().is_empty() || self.board().black().is_empty()
+    }
+
+    fn has_insufficient_material(&self, color: Color) -> bool {
+        // In a position with only bishops, check if all our bishops can be
+        // captured.
+        if self.board.occupied() == self.board.bishops() {
+            let we_some_on_light = (self.board.by_color(color) & Bitboard::LIGHT_SQUARES).any();
+            let we_some_on_dark = (self.board.by_color(color) & Bitboard::DARK_SQUARES).any();
+            let they_all_on_dark = (self.board.by_color(!color) & Bitboard::LIGHT_SQUARES).is_empty();
+            let they_all_on_light = (self.board.by_color(!color) & Bitboard::DARK_SQUARES).is_empty();
+            (we_some_on_light && they_all_on_dark) || (we_some_on_dark && they_all_on_light)
+        } else {
+            false
+        }
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        if self.us().is_empty() {
+            Some(Outcome::Decisive { winner: self.turn() })
+        } else if self.is_stalemate() {
+            Some(match self.rules {
+                AntichessRules::Giveaway => Outcome::Decisive { winner: self.turn() },
+                AntichessRules::Suicide => {
+                    let ours = self.us().count();
+                    let theirs = self.them().count();
+                    if ours < theirs {
+                        Outcome::Decisive { winner: self.turn() }
+                    } else if ours > theirs {
+                        Outcome::Decisive { winner: !self.turn() }
+                    } else {
+                        Outcome::Draw
+                    }
+                }
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Antichess {
+    fn gen_captures(&self, mode: PromotionMode, moves: &mut MoveList) {
+        self.en_passant_moves(moves); // clears move list
+        let them = self.them();
+        gen_non_king(self, them, mode, moves);
+        add_king_promotions(mode, moves);
+        KingTag::gen_moves(self, them, moves);
+    }
+}
+
+fn add_king_promotions(mode: PromotionMode, moves: &mut MoveList) {
+    if !mode.allows(Role::King) {
+        return;
+    }
+
+    let mut king_promotions = MoveList::new();
+
+    for m in &moves[..] {
+        if let Move::Normal { role, from, capture, to, promotion: Some(Role::Queen) } = *m {
+            king_promotions.push(Move::Normal {
+                role,
+                from,
+                capture,
+                to,
+                promotion: Some(Role::King),
+            });
+        }
+    }
+
+    moves.extend(king_promotions);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::position::tests::assert_insufficient_material;
+
+    #[test]
+    fn test_insufficient_material() {
+        let false_negative = false;
+
+        assert_insufficient_material::<Antichess>("8/4bk2/8/8/8/8/3KB3/8 w - - 0 1", false, false);
+        assert_insufficient_material::<Antichess>("4b3/5k2/8/8/8/8/3KB3/8 w - - 0 1", false, false);
+        assert_insufficient_material::<Antichess>("8/8/8/6b1/8/3B4/4B3/5B2 w - - 0 1", true, true);
+        assert_insufficient_material::<Antichess>("8/8/5b2/8/8/3B4/3B4/8 w - - 0 1", true, false);
+        assert_insufficient_material::<Antichess>("8/5p2/5P2/8/3B4/1bB5/8/8 b - - 0 1", false_negative, false_negative);
+    }
+
+    #[test]
+    fn test_promotion_mode() {
+        let pos: Antichess = "8/P7/8/8/8/8/8/7k w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+
+        let mut moves = MoveList::new();
+        pos.legal_moves_with_promotions(PromotionMode::All, &mut moves);
+        assert_eq!(moves.len(), 5);
+        assert!(moves.iter().any(|m| m.promotion() == Some(Role::King)));
+
+        moves.clear();
+        pos.legal_moves_with_promotions(PromotionMode::QueenOnly, &mut moves);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].promotion(), Some(Role::Queen));
+    }
+}
@@ -0,0 +1,285 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroU32;
+
+use crate::attacks;
+use crate::board::Board;
+use crate::bitboard::Bitboard;
+use crate::square::Square;
+use crate::types::{Black, CastlingMode, CastlingSide, Color, Move, PromotionMode, RemainingChecks, White};
+use crate::material::Material;
+use crate::setup::{Castles, EpSquare, Setup};
+use crate::movelist::{ArrayVecExt, MoveList};
+use crate::position::{FromSetup, Outcome, Position, PositionError, PositionErrorKinds};
+use crate::position::{do_move, gen_castling_moves, gen_en_passant, gen_non_king, relevant_ep, validate, KingTag, Stepper};
+
+/// An Atomic Chess position.
+#[derive(Clone, Debug)]
+pub struct Atomic {
+    board: Board,
+    turn: Color,
+    castles: Castles,
+    ep_square: Option<EpSquare>,
+    halfmoves: u32,
+    fullmoves: NonZeroU32,
+}
+
+impl Default for Atomic {
+    fn default() -> Atomic {
+        Atomic {
+            board: Board::default(),
+            turn: White,
+            castles: Castles::default(),
+            ep_square: None,
+            halfmoves: 0,
+            fullmoves: NonZeroU32::new(1).unwrap(),
+        }
+    }
+}
+
+impl Setup for Atomic {
+    fn board(&self) -> &Board { &self.board }
+    fn pockets(&self) -> Option<&Material> { None }
+    fn turn(&self) -> Color { self.turn }
+    fn castling_rights(&self) -> Bitboard { self.castles.castling_rights() }
+    fn ep_square(&self) -> Option<Square> { self.ep_square.and_then(|ep| relevant_ep(ep, self)) }
+    fn maybe_ep_square(&self) -> Option<Square> { self.ep_square.map(Square::from) }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
+    fn halfmoves(&self) -> u32 { self.halfmoves }
+    fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
+}
+
+impl FromSetup for Atomic {
+    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Atomic, PositionError<Atomic>> {
+        let mut errors = PositionErrorKinds::empty();
+        let board = setup.board().clone();
+        let turn = setup.turn();
+
+        let castles = match Castles::from_setup(&board, setup.castling_rights(), mode) {
+            Ok(castles) => castles,
+            Err(castles) => {
+                errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS;
+                castles
+            }
+        };
+
+        let ep_square = match EpSquare::from_setup(&board, turn, setup.ep_square()) {
+            Ok(ep_square) => ep_square,
+            Err(()) => {
+                errors |= PositionErrorKinds::INVALID_EP_SQUARE;
+                None
+            }
+        };
+
+        let pos = Atomic {
+            board,
+            turn,
+            castles,
+            ep_square,
+            halfmoves: setup.halfmoves(),
+            fullmoves: setup.fullmoves(),
+        };
+
+        errors |= validate(&pos) - PositionErrorKinds::IMPOSSIBLE_CHECK;
+
+        if (pos.them() & pos.board().kings()).any() {
+            // Our king just exploded. Game over, but valid position.
+            errors.remove(PositionErrorKinds::MISSING_KING);
+        }
+
+        PositionError { errors, pos }.strict()
+    }
+}
+
+impl Position for Atomic {
+    fn castles(&self) -> &Castles {
+        &self.castles
+    }
+
+    fn play_unchecked(&mut self, m: &Move) {
+        do_move(&mut self.board, &mut self.turn, &mut self.castles,
+                &mut self.ep_square, &mut self.halfmoves,
+                &mut self.fullmoves, m);
+
+        match *m {
+            Move::Normal { capture: Some(_), to, .. } | Move::EnPassant { to, .. } => {
+                self.board.remove_piece_at(to);
+
+                let explosion_radius = attacks::king_attacks(to) &
+                                       self.board().occupied() &
+                                       !self.board.pawns();
+
+                if (explosion_radius & self.board().kings() & self.us()).any() {
+                    self.castles.discard_side(self.turn());
+                }
+
+                for explosion in explosion_radius {
+                    self.board.remove_piece_at(explosion);
+                    self.castles.discard_rook(explosion);
+                }
+            },
+            _ => ()
+        }
+    }
+
+    fn legal_moves(&self, moves: &mut MoveList) {
+        self.legal_moves_with_promotions(PromotionMode::All, moves);
+    }
+
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
+        moves.clear();
+
+        gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
+        gen_non_king(self, !self.us(), mode, moves);
+        KingTag::gen_moves(self, !self.board().occupied(), moves);
+        if let Some(king) = self.board().king_of(self.turn()) {
+            gen_castling_moves(self, &self.castles, king, CastlingSide::KingSide, moves);
+            gen_castling_moves(self, &self.castles, king, CastlingSide::QueenSide, moves);
+        }
+
+        // Atomic move generation could be implemented more efficiently.
+        // For simplicity we filter all pseudo legal moves.
+        moves.swap_retain(|m| {
+            let mut after = self.clone();
+            after.play_unchecked(m);
+            if let Some(our_king) = after.board().king_of(self.turn()) {
+                (after.board.kings() & after.board().by_color(!self.turn())).is_empty() ||
+                after.king_attackers(our_king, !self.turn(), after.board.occupied()).is_empty()
+            } else {
+                false
+            }
+        });
+    }
+
+    fn king_attackers(&self, square: Square, attacker: Color, occupied: Bitboard) -> Bitboard {
+        if (attacks::king_attacks(square) & self.board().kings() & self.board().by_color(attacker)).any() {
+            Bitboard(0)
+        } else {
+            self.board().attacks_to(square, attacker, occupied)
+        }
+    }
+
+    fn gives_check(&self, m: &Move) -> bool {
+        // Explosions can remove blockers and kings.
+        let mut child = self.clone();
+        child.play_unchecked(m);
+        child.is_check()
+    }
+
+    fn is_variant_end(&self) -> bool {
+        self.variant_outcome().is_some()
+    }
+
+    fn has_insufficient_material(&self, color: Color) -> bool {
+        // Remaining material does not matter if the opponents king is already
+        // exploded.
+        if (self.board.by_color(!color) & self.board.kings()).is_empty() {
+            return false;
+        }
+
+        // Bare king can not mate.
+        if (self.board.by_color(color) & !self.board.kings()).is_empty() {
+            return true;
+        }
+
+        // As long as the opponent king is not alone there is always a chance
+        // their own piece explodes next to it.
+        if (self.board.by_color(!color) & !self.board.kings()).any() {
+            // Unless there are only bishops that cannot explode each other.
+            if self.board().occupied() == self.board().kings() | self.board().bishops() {
+                if (self.board().bishops() & self.board().white() & Bitboard::DARK_SQUARES).is_empty() {
+                    return (self.board().bishops() & self.board().black() & Bitboard::LIGHT_SQUARES).is_empty();
+                }
+                if (self.board().bishops() & self.board().white() & Bitboard::LIGHT_SQUARES).is_empty() {
+                    return (self.board().bishops() & self.board().black() & Bitboard::DARK_SQUARES).is_empty();
+                }
+            }
+
+            return false;
+        }
+
+        // Queen or pawn (future queen) can give mate against bare king.
+        if self.board().queens().any() || self.board.pawns().any() {
+            return false;
+        }
+
+        // Single knight, bishop or rook can not mate against bare king.
+        if (self.board().knights() | self.board().bishops() | self.board().rooks()).count() == 1 {
+            return true;
+        }
+
+        // Two knights can not mate against bare king.
+        if self.board().occupied() == self.board().kings() | self.board().knights() {
+            return self.board().knights().count() <= 2;
+        }
+
+        false
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        for &color in &[White, Black] {
+            if (self.board().by_color(color) & self.board().kings()).is_empty() {
+                return Some(Outcome::Decisive { winner: !color });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Role;
+    use crate::fen::Fen;
+    use crate::position::tests::assert_insufficient_material;
+
+    #[test]
+    fn test_insufficient_material() {
+        assert_insufficient_material::<Atomic>("8/3k4/8/8/2N5/8/3K4/8 b - - 0 1", true, true);
+        assert_insufficient_material::<Atomic>("8/4rk2/8/8/8/8/3K4/8 w - - 0 1", true, true);
+        assert_insufficient_material::<Atomic>("8/4qk2/8/8/8/8/3K4/8 w - - 0 1", true, false);
+        assert_insufficient_material::<Atomic>("8/1k6/8/2n5/8/3NK3/8/8 b - - 0 1", false, false);
+        assert_insufficient_material::<Atomic>("8/4bk2/8/8/8/8/3KB3/8 w - - 0 1", true, true);
+        assert_insufficient_material::<Atomic>("4b3/5k2/8/8/8/8/3KB3/8 w - - 0 1", false, false);
+        assert_insufficient_material::<Atomic>("3Q4/5kKB/8/8/8/8/8/8 b - - 0 1", false, true);
+        assert_insufficient_material::<Atomic>("8/5k2/8/8/8/8/5K2/4bb2 w - - 0 1", true, false);
+        assert_insufficient_material::<Atomic>("8/5k2/8/8/8/8/5K2/4nb2 w - - 0 1", true, false);
+        assert_insufficient_material::<Atomic>("8/1k6/8/8/8/3NK3/1p6/8 w - - 0 1", false, false);
+    }
+
+    #[test]
+    fn test_exploded_king_loses_castling_rights() {
+        let pos: Atomic = "rnb1kbnr/pppppppp/8/4q3/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Chess960)
+            .expect("valid position");
+
+        let pos = pos.play(&Move::Normal {
+            role: Role::Queen,
+            from: Square::E5,
+            to: Square::E2,
+            capture: Some(Role::Pawn),
+            promotion: None,
+        }).expect("Qxe2# is legal");
+
+        assert_eq!(pos.castling_rights(), Bitboard::from(Square::A8) | Bitboard::from(Square::H8));
+        assert_eq!(pos.castles().rook(Color::White, CastlingSide::QueenSide), None);
+        assert_eq!(pos.castles().rook(Color::White, CastlingSide::KingSide), None);
+        assert_eq!(pos.castles().rook(Color::Black, CastlingSide::QueenSide), Some(Square::A8));
+        assert_eq!(pos.castles().rook(Color::Black, CastlingSide::KingSide), Some(Square::H8));
+    }
+}
@@ -0,0 +1,288 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use crate::types::{Color, Move};
+use crate::movelist::MoveList;
+use crate::position::{Outcome, PlayError, Position};
+use crate::setup::Setup;
+use crate::variants::Crazyhouse;
+
+/// One of the two boards of a [`Bughouse`] game.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BughouseBoard {
+    A,
+    B,
+}
+
+impl BughouseBoard {
+    /// The partner board.
+    pub fn other(self) -> BughouseBoard {
+        match self {
+            BughouseBoard::A => BughouseBoard::B,
+            BughouseBoard::B => BughouseBoard::A,
+        }
+    }
+
+    fn idx(self) -> usize {
+        match self {
+            BughouseBoard::A => 0,
+            BughouseBoard::B => 1,
+        }
+    }
+}
+
+/// A Bughouse game: Two coupled [`Crazyhouse`] boards.
+///
+/// The player with white on board A is partnered with the player with black
+/// on board B, and vice versa. Teams are identified by the color they play
+/// on board A. Pieces captured on one board are passed to the partner, who
+/// can drop them on the other board.
+///
+/// Both boards move independently, each with its own side to move, so a
+/// team can have a player to move on one, both or none of the boards. See
+/// [`Bughouse::team_to_move()`].
+///
+/// Optionally, the game has a clock for each of the four players. The
+/// clocks of the players to move on both boards run at the same time, and
+/// a player that runs out of time loses the game for their team.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Color, Move, Role, Square};
+/// use shakmaty::variants::{Bughouse, BughouseBoard};
+/// use shakmaty::Setup;
+///
+/// let mut game = Bughouse::default();
+/// game = game.play(BughouseBoard::B, &Move::Normal {
+///     role: Role::Pawn,
+///     from: Square::E2,
+///     capture: None,
+///     to: Square::E4,
+///     promotion: None,
+/// })?;
+///
+/// // Board A is unaffected.
+/// assert_eq!(game.board(BughouseBoard::A).turn(), Color::White);
+/// assert_eq!(game.board(BughouseBoard::B).turn(), Color::Black);
+///
+/// // The team playing white on board A now has its players to move on
+/// // both boards.
+/// assert_eq!(game.team_to_move(BughouseBoard::A), Color::White);
+/// assert_eq!(game.team_to_move(BughouseBoard::B), Color::White);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// Playing with clocks:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use shakmaty::{Color, Outcome};
+/// use shakmaty::variants::{Bughouse, BughouseBoard};
+///
+/// let mut game = Bughouse::default().with_clocks(Duration::from_secs(120), Duration::from_secs(0));
+/// game.elapse(Duration::from_secs(30));
+/// assert_eq!(game.remaining_time(BughouseBoard::A, Color::White), Some(Duration::from_secs(90)));
+/// assert_eq!(game.remaining_time(BughouseBoard::B, Color::White), Some(Duration::from_secs(90)));
+/// assert_eq!(game.remaining_time(BughouseBoard::A, Color::Black), Some(Duration::from_secs(120)));
+///
+/// // White on board A runs out of time, so the team playing black on
+/// // board A wins.
+/// game.elapse(Duration::from_secs(90));
+/// assert_eq!(game.outcome(), Some(Outcome::Decisive { winner: Color::Black }));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Bughouse {
+    boards: [Crazyhouse; 2],
+    clocks: Option<Clocks>,
+}
+
+#[derive(Clone, Debug)]
+struct Clocks {
+    remaining: [[Duration; 2]; 2],
+    increment: Duration,
+}
+
+impl Bughouse {
+    /// Couples two Crazyhouse positions.
+    pub fn new(a: Crazyhouse, b: Crazyhouse) -> Bughouse {
+        Bughouse { boards: [a, b], clocks: None }
+    }
+
+    /// Starts all four clocks with `initial` time. Each player gets
+    /// `increment` after each of their moves.
+    pub fn with_clocks(mut self, initial: Duration, increment: Duration) -> Bughouse {
+        self.clocks = Some(Clocks {
+            remaining: [[initial; 2]; 2],
+            increment,
+        });
+        self
+    }
+
+    /// The Crazyhouse position on one of the boards.
+    pub fn board(&self, board: BughouseBoard) -> &Crazyhouse {
+        &self.boards[board.idx()]
+    }
+
+    /// The team that has the player to move on one of the boards, given as
+    /// the color the team plays on board A.
+    pub fn team_to_move(&self, board: BughouseBoard) -> Color {
+        match board {
+            BughouseBoard::A => self.board(board).turn(),
+            BughouseBoard::B => !self.board(board).turn(),
+        }
+    }
+
+    /// The remaining time of the player with `color` on one of the boards,
+    /// or `None` if the game is played without clocks.
+    pub fn remaining_time(&self, board: BughouseBoard, color: Color) -> Option<Duration> {
+        self.clocks.as_ref().map(|clocks| clocks.remaining[board.idx()][color.fold(0, 1)])
+    }
+
+    /// Runs the clocks of the players to move on both boards for `elapsed`
+    /// time.
+    pub fn elapse(&mut self, elapsed: Duration) {
+        let turns = [self.boards[0].turn(), self.boards[1].turn()];
+        if let Some(ref mut clocks) = self.clocks {
+            for (remaining, turn) in clocks.remaining.iter_mut().zip(&turns) {
+                let clock = &mut remaining[turn.fold(0, 1)];
+                *clock = clock.checked_sub(elapsed).unwrap_or_else(|| Duration::from_secs(0));
+            }
+        }
+    }
+
+    /// Collects all legal moves on one of the boards.
+    pub fn legal_moves(&self, board: BughouseBoard, moves: &mut MoveList) {
+        self.board(board).legal_moves(moves);
+    }
+
+    /// Plays a move on one of the boards. Captured pieces go to the pocket
+    /// of the partner on the other board.
+    ///
+    /// It is the callers responsibility to ensure the move is legal.
+    ///
+    /// # Panics
+    ///
+    /// Illegal moves can corrupt the state of the position and may
+    /// (or may not) panic or cause panics on future calls.
+    pub fn play_unchecked(&mut self, board: BughouseBoard, m: &Move) {
+        let pos = &mut self.boards[board.idx()];
+        let turn = pos.turn();
+        let capture = pos.captured_role(m);
+        pos.play_unchecked(m);
+
+        if let Some(ref mut clocks) = self.clocks {
+            clocks.remaining[board.idx()][turn.fold(0, 1)] += clocks.increment;
+        }
+
+        if let Some(role) = capture {
+            *pos.pockets_mut().by_color_mut(turn).by_role_mut(role) -= 1;
+            let partner = &mut self.boards[board.other().idx()];
+            *partner.pockets_mut().by_color_mut(!turn).by_role_mut(role) += 1;
+        }
+    }
+
+    /// Validates and plays a move on one of the boards.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PlayError`] if the move is not legal.
+    #[allow(clippy::result_large_err)] // the error carries the position
+    pub fn play<'a>(mut self, board: BughouseBoard, m: &'a Move) -> Result<Bughouse, PlayError<'a, Bughouse>> {
+        if self.board(board).is_legal(m) {
+            self.play_unchecked(board, m);
+            Ok(self)
+        } else {
+            Err(PlayError { m, inner: self })
+        }
+    }
+
+    /// The outcome of the game, or `None` if the game is not over. The
+    /// game ends as soon as a player is checkmated or runs out of time on
+    /// either board.
+    ///
+    /// The winner is given as the color the winning team plays on board A.
+    pub fn outcome(&self) -> Option<Outcome> {
+        for &board in &[BughouseBoard::A, BughouseBoard::B] {
+            let flagged = self.remaining_time(board, self.board(board).turn())
+                .map_or(false, |remaining| remaining == Duration::from_secs(0));
+            if flagged || self.board(board).is_checkmate() {
+                return Some(Outcome::Decisive { winner: !self.team_to_move(board) });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Role;
+    use crate::square::Square;
+
+    fn pawn_move(from: Square, to: Square, capture: Option<Role>) -> Move {
+        Move::Normal { role: Role::Pawn, from, capture, to, promotion: None }
+    }
+
+    #[test]
+    fn test_capture_feeds_partner() {
+        let game = Bughouse::default()
+            .play(BughouseBoard::A, &pawn_move(Square::E2, Square::E4, None)).expect("e4")
+            .play(BughouseBoard::A, &pawn_move(Square::D7, Square::D5, None)).expect("d5")
+            .play(BughouseBoard::A, &pawn_move(Square::E4, Square::D5, Some(Role::Pawn))).expect("exd5");
+
+        let a = game.board(BughouseBoard::A);
+        let b = game.board(BughouseBoard::B);
+        assert!(a.pockets().expect("pockets").is_empty());
+        assert_eq!(b.pockets().expect("pockets").black.pawns, 1);
+
+        // The partner can drop the pawn once it is their turn.
+        let game = game.play(BughouseBoard::B, &pawn_move(Square::G2, Square::G3, None)).expect("g3");
+        let drop = Move::Put { role: Role::Pawn, to: Square::E5 };
+        let game = game.play(BughouseBoard::B, &drop).expect("P@e5");
+        assert!(game.board(BughouseBoard::B).pockets().expect("pockets").is_empty());
+        assert_eq!(game.outcome(), None);
+    }
+
+    #[test]
+    fn test_clocks() {
+        let mut game = Bughouse::default();
+        assert_eq!(game.remaining_time(BughouseBoard::A, Color::White), None);
+        game.elapse(Duration::from_secs(1000));
+        assert_eq!(game.outcome(), None);
+
+        let secs = Duration::from_secs;
+        let mut game = game.with_clocks(secs(60), secs(2));
+        game.elapse(secs(10));
+        let mut game = game.play(BughouseBoard::A, &pawn_move(Square::E2, Square::E4, None)).expect("e4");
+        assert_eq!(game.remaining_time(BughouseBoard::A, Color::White), Some(secs(52)));
+        assert_eq!(game.team_to_move(BughouseBoard::A), Color::Black);
+        assert_eq!(game.team_to_move(BughouseBoard::B), Color::Black);
+
+        game.elapse(secs(5));
+        assert_eq!(game.remaining_time(BughouseBoard::A, Color::Black), Some(secs(55)));
+        assert_eq!(game.remaining_time(BughouseBoard::B, Color::White), Some(secs(45)));
+        assert_eq!(game.remaining_time(BughouseBoard::B, Color::Black), Some(secs(60)));
+
+        // Black on board A runs out of time first.
+        game.elapse(secs(55));
+        assert_eq!(game.remaining_time(BughouseBoard::A, Color::Black), Some(secs(0)));
+        assert_eq!(game.outcome(), Some(Outcome::Decisive { winner: Color::White }));
+    }
+}
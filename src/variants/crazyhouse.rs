@@ -0,0 +1,272 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroU32;
+
+use crate::attacks;
+use crate::board::Board;
+use crate::bitboard::Bitboard;
+use crate::square::Square;
+use crate::types::{CastlingMode, CastlingSide, Color, Move, PromotionMode, RemainingChecks, Role};
+use crate::material::{Material, MaterialSide};
+use crate::setup::{Castles, Setup};
+use crate::movelist::MoveList;
+use crate::position::{Chess, FromSetup, Outcome, Position, PositionError, PositionErrorKinds};
+
+/// A Crazyhouse position.
+#[derive(Clone, Debug, Default)]
+pub struct Crazyhouse {
+    chess: Chess,
+    pockets: Material,
+}
+
+impl Crazyhouse {
+    #[cfg(feature = "bughouse")]
+    pub(crate) fn pockets_mut(&mut self) -> &mut Material {
+        &mut self.pockets
+    }
+
+    /// The role that ends up in a pocket when playing `m`. Promoted pieces
+    /// are demoted to pawns.
+    pub(crate) fn captured_role(&self, m: &Move) -> Option<Role> {
+        match *m {
+            Move::Normal { capture: Some(_), to, .. } if self.board().promoted().contains(to) => Some(Role::Pawn),
+            Move::Normal { capture, .. } => capture,
+            Move::EnPassant { .. } => Some(Role::Pawn),
+            _ => None,
+        }
+    }
+
+    fn our_pocket(&self) -> &MaterialSide {
+        self.pockets.by_color(self.turn())
+    }
+
+    fn our_pocket_mut(&mut self) -> &mut MaterialSide {
+        let turn = self.turn();
+        self.pockets.by_color_mut(turn)
+    }
+
+    fn legal_put_squares(&self) -> Bitboard {
+        let checkers = self.checkers();
+
+        if checkers.is_empty() {
+            !self.board().occupied()
+        } else if let (Some(checker), Some(king)) = (checkers.single_square(), self.board().king_of(self.turn())) {
+            attacks::between(checker, king)
+        } else {
+            Bitboard(0)
+        }
+    }
+}
+
+impl Setup for Crazyhouse {
+    fn board(&self) -> &Board { self.chess.board() }
+    fn pockets(&self) -> Option<&Material> { Some(&self.pockets) }
+    fn turn(&self) -> Color { self.chess.turn() }
+    fn castling_rights(&self) -> Bitboard { self.chess.castling_rights() }
+    fn ep_square(&self) -> Option<Square> { self.chess.ep_square() }
+    fn maybe_ep_square(&self) -> Option<Square> { self.chess.maybe_ep_square() }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
+    fn halfmoves(&self) -> u32 { self.chess.halfmoves() }
+    fn fullmoves(&self) -> NonZeroU32 { self.chess.fullmoves() }
+}
+
+impl FromSetup for Crazyhouse {
+    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Crazyhouse, PositionError<Crazyhouse>> {
+        let (chess, mut errors) = Chess::from_setup_unchecked(setup, mode);
+
+        let pockets = setup.pockets().cloned().unwrap_or_default();
+        if pockets.count().saturating_add(chess.board().occupied().count()) > 64 {
+            errors |= PositionErrorKinds::VARIANT;
+        } else if pockets.white.kings > 0 || pockets.black.kings > 0 {
+            errors |= PositionErrorKinds::TOO_MANY_KINGS;
+        }
+
+        if pockets.count().saturating_add(chess.board().occupied().count()) <= 32 &&
+           usize::from(pockets.white.pawns.saturating_add(pockets.black.pawns)).saturating_add(chess.board().pawns().count()) <= 16
+        {
+            errors -= PositionErrorKinds::IMPOSSIBLE_MATERIAL;
+        }
+
+        PositionError {
+            errors,
+            pos: Crazyhouse { chess, pockets },
+        }.strict()
+    }
+}
+
+impl Position for Crazyhouse {
+    fn play_unchecked(&mut self, m: &Move) {
+        if let Some(capture) = self.captured_role(m) {
+            *self.our_pocket_mut().by_role_mut(capture) += 1;
+        } else if let Move::Put { role, .. } = *m {
+            *self.our_pocket_mut().by_role_mut(role) -= 1;
+        }
+
+        self.chess.play_unchecked(m);
+    }
+
+    fn castles(&self) -> &Castles {
+        self.chess.castles()
+    }
+
+    fn legal_moves(&self, moves: &mut MoveList) {
+        self.legal_moves_with_promotions(PromotionMode::All, moves);
+    }
+
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
+        self.chess.legal_moves_with_promotions(mode, moves);
+
+        let pocket = self.our_pocket();
+        let targets = self.legal_put_squares();
+
+        for to in targets {
+            for &role in &[Role::Knight, Role::Bishop, Role::Rook, Role::Queen] {
+                if pocket.by_role(role) > 0 {
+                    moves.push(Move::Put { role, to });
+                }
+            }
+        }
+
+        if pocket.pawns > 0 {
+            for to in targets & !Bitboard::BACKRANKS {
+                moves.push(Move::Put { role: Role::Pawn, to });
+            }
+        }
+    }
+
+    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
+        self.chess.castling_moves(side, moves);
+    }
+
+    fn en_passant_moves(&self, moves: &mut MoveList) {
+        self.chess.en_passant_moves(moves);
+    }
+
+    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
+        self.chess.san_candidates(role, to, moves);
+
+        if self.our_pocket().by_role(role) > 0 && self.legal_put_squares().contains(to) &&
+           (role != Role::Pawn || !Bitboard::BACKRANKS.contains(to))
+        {
+            moves.push(Move::Put { role, to });
+        }
+    }
+
+    fn is_irreversible(&self, m: &Move) -> bool {
+        match *m {
+            Move::Castle { .. } => true,
+            Move::Normal { role, from, to, .. } =>
+                self.castling_rights().contains(from) ||
+                self.castling_rights().contains(to) ||
+                (role == Role::King && self.chess.castles().has_side(self.turn())),
+            _ => false,
+        }
+    }
+
+    fn has_insufficient_material(&self, _color: Color) -> bool {
+        // In practise no material can leave the game, but this is simple
+        // to implement anyway. Bishops can be captured and put onto a
+        // different color complex.
+        self.board().occupied().count() + self.pockets.count() <= 3 &&
+        self.board().promoted().is_empty() &&
+        self.board().pawns().is_empty() &&
+        self.board().rooks_and_queens().is_empty() &&
+        self.pockets.white.pawns == 0 &&
+        self.pockets.black.pawns == 0 &&
+        self.pockets.white.rooks == 0 &&
+        self.pockets.black.rooks == 0 &&
+        self.pockets.white.queens == 0 &&
+        self.pockets.black.queens == 0
+    }
+
+    fn is_variant_end(&self) -> bool { false }
+    fn variant_outcome(&self) -> Option<Outcome> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::position::tests::assert_insufficient_material;
+    use crate::san::{San, SanError};
+    use crate::uci::Uci;
+
+    #[test]
+    fn test_insufficient_material() {
+        assert_insufficient_material::<Crazyhouse>("8/5k2/8/8/8/8/3K2N1/8 w - - 0 1", true, true);
+        assert_insufficient_material::<Crazyhouse>("8/5k2/8/8/8/5B2/3KB3/8 w - - 0 1", false, false);
+        assert_insufficient_material::<Crazyhouse>("8/8/8/8/3k4/3N~4/3K4/8 w - - 0 1", false, false);
+    }
+
+    #[test]
+    fn test_san_drops() {
+        let mut pos = Crazyhouse::default();
+        for san in &["e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5", "P@d4", "@e4", "Nxe4"] {
+            let m = san.parse::<San>().expect("valid san").to_move(&pos).expect("legal san");
+            assert_eq!(San::from_move(&pos, &m).to_string(), san.trim_start_matches('P'));
+            pos.play_unchecked(&m);
+        }
+
+        // Black has nothing left to drop.
+        assert_eq!("@e5".parse::<San>().expect("valid san").to_move(&pos), Err(SanError::IllegalSan));
+        assert_eq!("N@h3".parse::<San>().expect("valid san").to_move(&pos), Err(SanError::IllegalSan));
+
+        // White can drop the pawn again, but not onto an occupied square.
+        let m = "Nc6".parse::<San>().expect("valid san").to_move(&pos).expect("legal san");
+        pos.play_unchecked(&m);
+        assert!("@e2".parse::<San>().expect("valid san").to_move(&pos).is_ok());
+        assert_eq!("@d2".parse::<San>().expect("valid san").to_move(&pos), Err(SanError::IllegalSan));
+    }
+
+    #[test]
+    fn test_drop_restrictions() {
+        let pos: Crazyhouse = "4k3/8/8/8/8/8/4P3/4K3[Pp] w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid crazyhouse position");
+
+        let mut moves = MoveList::new();
+        pos.legal_moves(&mut moves);
+        let drops: Vec<Square> = moves.iter().filter_map(|m| match *m {
+            Move::Put { role: Role::Pawn, to } => Some(to),
+            _ => None,
+        }).collect();
+        assert_eq!(drops.len(), 47);
+        assert!(drops.iter().all(|sq| !Bitboard::BACKRANKS.contains(*sq)));
+        assert!(!drops.contains(&Square::E2));
+
+        assert!(pos.is_legal(&Move::Put { role: Role::Pawn, to: Square::E4 }));
+        assert!(!pos.is_legal(&Move::Put { role: Role::Pawn, to: Square::A8 }));
+        assert!(!pos.is_legal(&Move::Put { role: Role::Knight, to: Square::E4 }));
+
+        for &(uci, legal) in &[("P@e4", true), ("P@a1", false), ("P@h8", false), ("P@e2", false), ("N@c3", false)] {
+            let uci: Uci = uci.parse().expect("valid uci");
+            assert_eq!(uci.to_move(&pos).is_ok(), legal, "{}", uci);
+        }
+    }
+    #[test]
+    fn test_capture_promoted() {
+        let pos: Crazyhouse = "3rk3/8/8/3Q~4/8/8/8/4K3[] b - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid crazyhouse position");
+
+        let m = "Rxd5".parse::<San>().expect("valid san").to_move(&pos).expect("legal san");
+        let pos = pos.play(&m).expect("legal move");
+        assert_eq!(crate::fen::fen(&pos), "4k3/8/8/3r4/8/8/8/4K3[p] w - - 0 2");
+    }
+}
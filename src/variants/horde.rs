@@ -0,0 +1,237 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroU32;
+
+use crate::board::Board;
+use crate::bitboard::Bitboard;
+use crate::square::{Rank, Square};
+use crate::types::{Black, CastlingMode, CastlingSide, Color, Move, PromotionMode, RemainingChecks, White};
+use crate::material::Material;
+use crate::setup::{Castles, EpSquare, Setup};
+use crate::movelist::{ArrayVecExt, MoveList};
+use crate::position::{FromSetup, Outcome, Position, PositionError, PositionErrorKinds};
+use crate::position::{do_move, evasions, gen_castling_moves, gen_en_passant, gen_non_king, gen_safe_king, is_safe, relevant_ep, slider_blockers, validate};
+
+/// A Horde position.
+#[derive(Clone, Debug)]
+pub struct Horde {
+    board: Board,
+    turn: Color,
+    castles: Castles,
+    ep_square: Option<EpSquare>,
+    halfmoves: u32,
+    fullmoves: NonZeroU32,
+}
+
+impl Default for Horde {
+    fn default() -> Horde {
+        let mut castles = Castles::default();
+        castles.discard_side(White);
+
+        Horde {
+            board: Board::horde(),
+            turn: White,
+            castles,
+            ep_square: None,
+            halfmoves: 0,
+            fullmoves: NonZeroU32::new(1).unwrap(),
+        }
+    }
+}
+
+impl Setup for Horde {
+    fn board(&self) -> &Board { &self.board }
+    fn pockets(&self) -> Option<&Material> { None }
+    fn turn(&self) -> Color { self.turn }
+    fn castling_rights(&self) -> Bitboard { self.castles.castling_rights() }
+    fn ep_square(&self) -> Option<Square> { self.ep_square.and_then(|ep| relevant_ep(ep, self)) }
+    fn maybe_ep_square(&self) -> Option<Square> { self.ep_square.map(Square::from) }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
+    fn halfmoves(&self) -> u32 { self.halfmoves }
+    fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
+}
+
+impl FromSetup for Horde {
+    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Horde, PositionError<Horde>> {
+        let mut errors = PositionErrorKinds::empty();
+        let board = setup.board().clone();
+        let turn = setup.turn();
+
+        let castles = match Castles::from_setup(&board, setup.castling_rights(), mode) {
+            Ok(castles) => castles,
+            Err(castles) => {
+                errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS;
+                castles
+            }
+        };
+
+        let ep_square = match EpSquare::from_setup(&board, turn, setup.ep_square()) {
+            Ok(ep_square) => ep_square,
+            Err(()) => {
+                errors |= PositionErrorKinds::INVALID_EP_SQUARE;
+                None
+            }
+        };
+
+        let pos = Horde {
+            board,
+            turn,
+            castles,
+            ep_square,
+            halfmoves: setup.halfmoves(),
+            fullmoves: setup.fullmoves(),
+        };
+
+        errors |= validate(&pos)
+            - PositionErrorKinds::PAWNS_ON_BACKRANK
+            - PositionErrorKinds::MISSING_KING
+            - PositionErrorKinds::IMPOSSIBLE_MATERIAL;
+
+        if (pos.board().kings() & pos.board.white()).is_empty() {
+            if pos.board().white().count() > 36 || pos.board().black().count() > 16 || (pos.board().black() & pos.board().pawns()).count() > 8 {
+                errors |= PositionErrorKinds::IMPOSSIBLE_MATERIAL;
+            }
+        } else if pos.board().black().count() > 36 || pos.board().white().count() > 16 || (pos.board().white() & pos.board().pawns()).count() > 8 {
+            errors |= PositionErrorKinds::IMPOSSIBLE_MATERIAL;
+        }
+
+        if (pos.board().pawns() & pos.board().white() & Rank::Eighth).any() ||
+           (pos.board().pawns() & pos.board().black() & Rank::First).any()
+        {
+            errors |= PositionErrorKinds::PAWNS_ON_BACKRANK;
+        }
+
+        if (pos.board().kings() & !pos.board().promoted()).is_empty() {
+            errors |= PositionErrorKinds::MISSING_KING;
+        }
+
+        if (pos.board().kings() & pos.board().white()).any() &&
+           (pos.board().kings() & pos.board().black()).any()
+        {
+            errors |= PositionErrorKinds::VARIANT;
+        }
+
+        PositionError { errors, pos }.strict()
+    }
+}
+
+impl Position for Horde {
+    fn play_unchecked(&mut self, m: &Move) {
+        do_move(&mut self.board, &mut self.turn, &mut self.castles,
+                &mut self.ep_square, &mut self.halfmoves,
+                &mut self.fullmoves, m);
+    }
+
+    fn legal_moves(&self, moves: &mut MoveList) {
+        self.legal_moves_with_promotions(PromotionMode::All, moves);
+    }
+
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
+        moves.clear();
+
+        let king = self.board().king_of(self.turn());
+        let has_ep = gen_en_passant(self.board(), self.turn(), self.ep_square, moves);
+
+        let checkers = self.checkers();
+        if checkers.is_empty() {
+            let target = !self.us();
+            gen_non_king(self, target, mode, moves);
+            if let Some(king) = king {
+                gen_safe_king(self, king, target, moves);
+                gen_castling_moves(self, &self.castles, king, CastlingSide::KingSide, moves);
+                gen_castling_moves(self, &self.castles, king, CastlingSide::QueenSide, moves);
+            }
+        } else {
+            evasions(self, king.expect("king in check"), checkers, mode, moves);
+        }
+
+        if let Some(king) = king {
+            let blockers = slider_blockers(self.board(), self.them(), king);
+            if blockers.any() || has_ep {
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
+            }
+        }
+    }
+
+    fn castles(&self) -> &Castles {
+        &self.castles
+    }
+
+    fn is_variant_end(&self) -> bool {
+        self.board().white().is_empty() || self.board().black().is_empty()
+    }
+
+    fn has_insufficient_material(&self, color: Color) -> bool {
+        // The side with the king can always win by capturing the horde.
+        if (self.board.by_color(color) & self.board.kings()).any() {
+            return false;
+        }
+
+        // TODO: Detect when the horde can not mate. Note that it does not have
+        // a king.
+        false
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        if self.board().occupied().is_empty() {
+            Some(Outcome::Draw)
+        } else if self.board().white().is_empty() {
+            Some(Outcome::Decisive { winner: Black })
+        } else if self.board().black().is_empty() {
+            Some(Outcome::Decisive { winner: White })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::position::tests::assert_insufficient_material;
+
+    #[test]
+    fn test_insufficient_material() {
+        let false_negative = false;
+
+        assert_insufficient_material::<Horde>("8/5k2/8/8/8/4NN2/8/8 w - - 0 1", false_negative, false);
+    }
+
+    #[test]
+    fn test_horde() {
+        let pos: Horde = "4k3/8/8/8/8/8/8/P7 w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        let mut moves = MoveList::new();
+        pos.legal_moves(&mut moves);
+        assert_eq!(moves.len(), 2, "single and double push from the first rank");
+        assert!(!pos.is_variant_end());
+
+        let pos: Horde = "4k3/8/8/8/8/8/8/8 w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        assert_eq!(pos.outcome(), Some(Outcome::Decisive { winner: Black }));
+
+        let res = "4k3/8/8/8/8/8/8/P3K3 w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position::<Horde>(CastlingMode::Standard);
+        assert_eq!(res.expect_err("two kings").kinds(), PositionErrorKinds::VARIANT);
+    }
+}
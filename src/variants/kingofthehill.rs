@@ -0,0 +1,129 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroU32;
+
+use crate::board::Board;
+use crate::bitboard::Bitboard;
+use crate::square::Square;
+use crate::types::{Black, CastlingMode, CastlingSide, Color, Move, PromotionMode, RemainingChecks, Role, White};
+use crate::material::Material;
+use crate::setup::{Castles, Setup};
+use crate::movelist::MoveList;
+use crate::position::{Chess, FromSetup, Outcome, Position, PositionError};
+
+/// A King of the Hill position.
+#[derive(Clone, Debug, Default)]
+pub struct KingOfTheHill {
+    chess: Chess,
+}
+
+impl Setup for KingOfTheHill {
+    fn board(&self) -> &Board { self.chess.board() }
+    fn pockets(&self) -> Option<&Material> { None }
+    fn turn(&self) -> Color { self.chess.turn() }
+    fn castling_rights(&self) -> Bitboard { self.chess.castling_rights() }
+    fn ep_square(&self) -> Option<Square> { self.chess.ep_square() }
+    fn maybe_ep_square(&self) -> Option<Square> { self.chess.maybe_ep_square() }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
+    fn halfmoves(&self) -> u32 { self.chess.halfmoves() }
+    fn fullmoves(&self) -> NonZeroU32 { self.chess.fullmoves() }
+}
+
+impl FromSetup for KingOfTheHill {
+    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<KingOfTheHill, PositionError<KingOfTheHill>> {
+        let (chess, errors) = Chess::from_setup_unchecked(setup, mode);
+        PositionError {
+            errors,
+            pos: KingOfTheHill { chess },
+        }.strict()
+    }
+}
+
+impl Position for KingOfTheHill {
+    fn play_unchecked(&mut self, m: &Move) {
+        self.chess.play_unchecked(m);
+    }
+
+    fn castles(&self) -> &Castles {
+        self.chess.castles()
+    }
+
+    fn legal_moves(&self, moves: &mut MoveList) {
+        self.legal_moves_with_promotions(PromotionMode::All, moves);
+    }
+
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.legal_moves_with_promotions(mode, moves);
+        }
+    }
+
+    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.castling_moves(side, moves);
+        }
+    }
+
+    fn en_passant_moves(&self, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.en_passant_moves(moves);
+        }
+    }
+
+    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.san_candidates(role, to, moves);
+        }
+    }
+
+    fn has_insufficient_material(&self, _color: Color) -> bool {
+        // Even a lone king can walk onto the hill.
+        false
+    }
+
+    fn is_variant_end(&self) -> bool {
+        (self.chess.board().kings() & Bitboard::CENTER).any()
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        for &color in &[White, Black] {
+            if (self.board().by_color(color) & self.board().kings() & Bitboard::CENTER).any() {
+                return Some(Outcome::Decisive { winner: color });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::tests::assert_insufficient_material;
+
+    #[test]
+    fn test_insufficient_material() {
+        assert_insufficient_material::<KingOfTheHill>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", false, false);
+    }
+}
@@ -0,0 +1,158 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroU32;
+
+use crate::board::Board;
+use crate::bitboard::Bitboard;
+use crate::square::Square;
+use crate::types::{Black, CastlingMode, Color, Move, PromotionMode, RemainingChecks, White};
+use crate::material::Material;
+use crate::setup::{Castles, Setup};
+use crate::movelist::MoveList;
+use crate::position::{Chess, FromSetup, Outcome, Position, PositionError};
+
+/// A Losers chess position.
+///
+/// Captures are compulsory, but unlike in [`Antichess`](super::Antichess)
+/// the usual check rules apply. A player wins by losing all pieces except
+/// the king, or by being checkmated or stalemated.
+#[derive(Clone, Debug, Default)]
+pub struct Losers {
+    chess: Chess,
+}
+
+impl Losers {
+    fn only_king(&self, color: Color) -> bool {
+        (self.board().by_color(color) & !self.board().kings()).is_empty()
+    }
+}
+
+impl Setup for Losers {
+    fn board(&self) -> &Board { self.chess.board() }
+    fn pockets(&self) -> Option<&Material> { None }
+    fn turn(&self) -> Color { self.chess.turn() }
+    fn castling_rights(&self) -> Bitboard { self.chess.castling_rights() }
+    fn ep_square(&self) -> Option<Square> { self.chess.ep_square() }
+    fn maybe_ep_square(&self) -> Option<Square> { self.chess.maybe_ep_square() }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
+    fn halfmoves(&self) -> u32 { self.chess.halfmoves() }
+    fn fullmoves(&self) -> NonZeroU32 { self.chess.fullmoves() }
+}
+
+impl FromSetup for Losers {
+    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<Losers, PositionError<Losers>> {
+        let (chess, errors) = Chess::from_setup_unchecked(setup, mode);
+        PositionError {
+            errors,
+            pos: Losers { chess },
+        }.strict()
+    }
+}
+
+impl Position for Losers {
+    fn play_unchecked(&mut self, m: &Move) {
+        self.chess.play_unchecked(m);
+    }
+
+    fn castles(&self) -> &Castles {
+        self.chess.castles()
+    }
+
+    fn legal_moves(&self, moves: &mut MoveList) {
+        self.legal_moves_with_promotions(PromotionMode::All, moves);
+    }
+
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.legal_moves_with_promotions(mode, moves);
+            if moves.iter().any(|m| m.is_capture()) {
+                moves.retain(|m| m.is_capture());
+            }
+        }
+    }
+
+    fn has_insufficient_material(&self, _color: Color) -> bool {
+        // It is always possible to be mated or to give away the remaining
+        // pieces.
+        false
+    }
+
+    fn is_variant_end(&self) -> bool {
+        self.only_king(White) || self.only_king(Black)
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        match (self.only_king(White), self.only_king(Black)) {
+            (true, true) => Some(Outcome::Draw),
+            (true, false) => Some(Outcome::Decisive { winner: White }),
+            (false, true) => Some(Outcome::Decisive { winner: Black }),
+            (false, false) => {
+                let mut moves = MoveList::new();
+                self.legal_moves(&mut moves);
+                if moves.is_empty() {
+                    // Checkmated or stalemated.
+                    Some(Outcome::Decisive { winner: self.turn() })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::types::Role;
+
+    fn setup_fen(fen: &str) -> Losers {
+        fen.parse::<Fen>().expect("valid fen")
+            .position(CastlingMode::Standard).expect("valid losers position")
+    }
+
+    #[test]
+    fn test_compulsory_capture() {
+        let pos = setup_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+        let mut moves = MoveList::new();
+        pos.legal_moves(&mut moves);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].capture(), Some(Role::Pawn));
+    }
+
+    #[test]
+    fn test_check_rules_apply() {
+        // The pawn on d4 can not be captured, because the knight is pinned.
+        let pos = setup_fen("4r1k1/8/8/8/3p4/8/4N3/4K3 w - - 0 1");
+        let mut moves = MoveList::new();
+        pos.legal_moves(&mut moves);
+        assert!(moves.iter().all(|m| !m.is_capture()));
+        assert!(moves.iter().all(|m| m.role() == Role::King));
+    }
+
+    #[test]
+    fn test_outcome() {
+        let pos = setup_fen("4k3/8/8/8/8/8/3P4/4K3 b - - 0 1");
+        assert_eq!(pos.outcome(), Some(Outcome::Decisive { winner: Black }));
+
+        // White is checkmated and wins.
+        let pos = setup_fen("7k/8/8/8/8/8/5PPP/r5K1 w - - 0 1");
+        assert_eq!(pos.outcome(), Some(Outcome::Decisive { winner: White }));
+    }
+}
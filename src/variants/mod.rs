@@ -0,0 +1,556 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Chess variants.
+//!
+//! These are games played with normal chess pieces but special rules.
+//! Every chess variant implements [`Setup`], [`FromSetup`] and [`Position`],
+//! except for `Bughouse`, which couples two Crazyhouse boards.
+//!
+//! Each variant is behind a cargo feature of the same (lowercase) name,
+//! e.g. `crazyhouse`. All of them are enabled by the default `variants`
+//! feature.
+//!
+//! Only variants played on the standard 8x8 board with the standard pieces
+//! are supported. Large-board variants like Capablanca chess, with
+//! Archbishops and Chancellors on ten files, would require a different
+//! [`Bitboard`] representation and attack tables.
+//!
+//! Positions compare and hash equal if they are equal for the purposes of
+//! repetitions: Move counters are ignored, and the en passant square is
+//! only considered if there is a legal en passant capture.
+//!
+//! [`Bitboard`]: super::Bitboard
+//! [`Setup`]: super::Setup
+//! [`FromSetup`]: super::FromSetup
+//! [`Position`]: super::Position
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU32;
+
+#[cfg(feature = "atomic")]
+mod atomic;
+#[cfg(feature = "antichess")]
+mod antichess;
+#[cfg(feature = "kingofthehill")]
+mod kingofthehill;
+#[cfg(feature = "threecheck")]
+mod threecheck;
+#[cfg(feature = "crazyhouse")]
+mod crazyhouse;
+#[cfg(feature = "racingkings")]
+mod racingkings;
+#[cfg(feature = "horde")]
+mod horde;
+#[cfg(feature = "bughouse")]
+mod bughouse;
+#[cfg(feature = "losers")]
+mod losers;
+
+pub use crate::Chess;
+#[cfg(feature = "atomic")]
+pub use self::atomic::Atomic;
+#[cfg(feature = "antichess")]
+pub use self::antichess::{Antichess, AntichessRules};
+#[cfg(feature = "kingofthehill")]
+pub use self::kingofthehill::KingOfTheHill;
+#[cfg(feature = "threecheck")]
+pub use self::threecheck::ThreeCheck;
+#[cfg(feature = "crazyhouse")]
+pub use self::crazyhouse::Crazyhouse;
+#[cfg(feature = "racingkings")]
+pub use self::racingkings::RacingKings;
+#[cfg(feature = "horde")]
+pub use self::horde::Horde;
+#[cfg(feature = "bughouse")]
+pub use self::bughouse::{Bughouse, BughouseBoard};
+#[cfg(feature = "losers")]
+pub use self::losers::Losers;
+
+use crate::{Board, Color, Bitboard, Square, Material, RemainingChecks};
+use crate::{Role, Move, MoveList, CastlingSide, CastlingMode, Outcome, Castles, PromotionMode};
+use crate::{Setup, FromSetup, Position, PositionError};
+use crate::setup::SwapTurn;
+use crate::types::ROLES;
+
+// Positions are equal if they are equal for the purposes of repetitions,
+// i.e. move counters are ignored, the en passant square is only
+// considered if there is a legal en passant capture, and promoted pieces
+// are only distinguished if there are pockets.
+fn setup_eq(a: &dyn Setup, b: &dyn Setup) -> bool {
+    a.board().white() == b.board().white() &&
+    a.board().black() == b.board().black() &&
+    ROLES.iter().all(|&role| a.board().by_role(role) == b.board().by_role(role)) &&
+    (a.pockets().is_none() || a.board().promoted() == b.board().promoted()) &&
+    a.pockets() == b.pockets() &&
+    a.turn() == b.turn() &&
+    a.castling_rights() == b.castling_rights() &&
+    a.ep_square() == b.ep_square() &&
+    a.remaining_checks() == b.remaining_checks()
+}
+
+// Registers all supported variants. Each variant can be disabled with its
+// own cargo feature.
+macro_rules! variants {
+    ($($(#[$attr:meta])* $variant:ident, $uci:expr;)+) => {
+        /// Discriminant of [`VariantPosition`].
+        #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+        pub enum Variant {
+            $($(#[$attr])* $variant,)+
+        }
+
+        impl Variant {
+            /// Gets the name of the variant, as expected by the `UCI_Variant`
+            /// option of chess engines.
+            pub fn uci(self) -> &'static str {
+                match self {
+                    $($(#[$attr])* Variant::$variant => $uci,)+
+                }
+            }
+
+            pub fn from_uci(s: &str) -> Option<Variant> {
+                Some(match s {
+                    $($(#[$attr])* $uci => Variant::$variant,)+
+                    _ => return None,
+                })
+            }
+        }
+
+        /// Dynamically dispatched chess variant [`Position`].
+        ///
+        /// # Example
+        ///
+        /// Setting up a position from a variant name and a FEN:
+        ///
+        /// ```
+        /// # use std::error::Error;
+        /// #
+        /// use shakmaty::{CastlingMode, MoveList, Position};
+        /// use shakmaty::fen::Fen;
+        /// use shakmaty::variants::{Variant, VariantPosition};
+        ///
+        /// let variant = Variant::from_uci("chess").expect("known variant");
+        /// let fen: Fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".parse()?;
+        /// let pos = VariantPosition::from_setup(variant, &fen, CastlingMode::Standard)?;
+        ///
+        /// assert_eq!(pos.variant(), Variant::Chess);
+        ///
+        /// let mut moves = MoveList::new();
+        /// pos.legal_moves(&mut moves);
+        /// assert_eq!(moves.len(), 20);
+        /// #
+        /// # Ok::<_, Box<dyn Error>>(())
+        /// ```
+        ///
+        /// [`Position`]: super::Position
+        #[derive(Debug, Clone)]
+        pub enum VariantPosition {
+            $($(#[$attr])* $variant($variant),)+
+        }
+
+        $(
+            $(#[$attr])*
+            impl From<$variant> for VariantPosition {
+                fn from(pos: $variant) -> VariantPosition {
+                    VariantPosition::$variant(pos)
+                }
+            }
+
+            $(#[$attr])*
+            impl PartialEq for $variant {
+                fn eq(&self, other: &$variant) -> bool {
+                    setup_eq(self, other)
+                }
+            }
+
+            $(#[$attr])*
+            impl Eq for $variant {}
+
+            $(#[$attr])*
+            impl Hash for $variant {
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    state.write_u64(self.zobrist_hash());
+                }
+            }
+        )+
+
+        impl VariantPosition {
+            pub fn new(variant: Variant) -> VariantPosition {
+                match variant {
+                    $($(#[$attr])* Variant::$variant => $variant::default().into(),)+
+                }
+            }
+
+            #[allow(clippy::result_large_err)] // the error carries the position
+            pub fn from_setup(variant: Variant, setup: &dyn Setup, mode: CastlingMode) -> Result<VariantPosition, PositionError<VariantPosition>> {
+                fn wrap<F, P, U>(result: Result<P, PositionError<P>>, f: F) -> Result<U, PositionError<U>>
+                where
+                    F: FnOnce(P) -> U,
+                {
+                    match result {
+                        Ok(p) => Ok(f(p)),
+                        Err(PositionError { errors, pos }) => Err(PositionError { errors, pos: f(pos) }),
+                    }
+                }
+
+                match variant {
+                    $($(#[$attr])* Variant::$variant => wrap($variant::from_setup(setup, mode), VariantPosition::$variant),)+
+                }
+            }
+
+            pub fn variant(&self) -> Variant {
+                match self {
+                    $($(#[$attr])* VariantPosition::$variant(_) => Variant::$variant,)+
+                }
+            }
+
+            fn borrow(&self) -> &dyn Position {
+                match *self {
+                    $($(#[$attr])* VariantPosition::$variant(ref pos) => pos,)+
+                }
+            }
+
+            fn borrow_mut(&mut self) -> &mut dyn Position {
+                match *self {
+                    $($(#[$attr])* VariantPosition::$variant(ref mut pos) => pos,)+
+                }
+            }
+        }
+    }
+}
+
+variants! {
+    Chess, "chess";
+    #[cfg(feature = "atomic")]
+    Atomic, "atomic";
+    #[cfg(feature = "antichess")]
+    Antichess, "antichess";
+    #[cfg(feature = "kingofthehill")]
+    KingOfTheHill, "kingofthehill";
+    #[cfg(feature = "threecheck")]
+    ThreeCheck, "3check";
+    #[cfg(feature = "crazyhouse")]
+    Crazyhouse, "crazyhouse";
+    #[cfg(feature = "racingkings")]
+    RacingKings, "racingkings";
+    #[cfg(feature = "horde")]
+    Horde, "horde";
+    #[cfg(feature = "losers")]
+    Losers, "losers";
+}
+
+impl Variant {
+    /// Gets the key of the variant as used by the lichess.org API, or `None`
+    /// if the variant is not available on lichess.org.
+    pub fn lichess_key(self) -> Option<&'static str> {
+        Some(match self {
+            Variant::Chess => "standard",
+            #[cfg(feature = "atomic")]
+            Variant::Atomic => "atomic",
+            #[cfg(feature = "antichess")]
+            Variant::Antichess => "antichess",
+            #[cfg(feature = "kingofthehill")]
+            Variant::KingOfTheHill => "kingOfTheHill",
+            #[cfg(feature = "threecheck")]
+            Variant::ThreeCheck => "threeCheck",
+            #[cfg(feature = "crazyhouse")]
+            Variant::Crazyhouse => "crazyhouse",
+            #[cfg(feature = "racingkings")]
+            Variant::RacingKings => "racingKings",
+            #[cfg(feature = "horde")]
+            Variant::Horde => "horde",
+            #[cfg(feature = "losers")]
+            Variant::Losers => return None,
+        })
+    }
+
+    /// Parses a variant key from the lichess.org API. `chess960` and
+    /// `fromPosition` are both mapped to [`Variant::Chess`].
+    pub fn from_lichess_key(s: &str) -> Option<Variant> {
+        Some(match s {
+            "standard" | "chess960" | "fromPosition" => Variant::Chess,
+            #[cfg(feature = "atomic")]
+            "atomic" => Variant::Atomic,
+            #[cfg(feature = "antichess")]
+            "antichess" => Variant::Antichess,
+            #[cfg(feature = "kingofthehill")]
+            "kingOfTheHill" => Variant::KingOfTheHill,
+            #[cfg(feature = "threecheck")]
+            "threeCheck" => Variant::ThreeCheck,
+            #[cfg(feature = "crazyhouse")]
+            "crazyhouse" => Variant::Crazyhouse,
+            #[cfg(feature = "racingkings")]
+            "racingKings" => Variant::RacingKings,
+            #[cfg(feature = "horde")]
+            "horde" => Variant::Horde,
+            _ => return None,
+        })
+    }
+
+    /// Guesses the variant of a setup, e.g. when importing positions without
+    /// a variant tag.
+    ///
+    /// Pockets indicate [`Variant::Crazyhouse`] and remaining checks indicate
+    /// [`Variant::ThreeCheck`]. A side without king, but with more than 8
+    /// pawns indicates [`Variant::Horde`], while any other number of kings
+    /// than one per side indicates [`Variant::Antichess`]. The starting
+    /// position of [`Variant::RacingKings`] is recognized as such. Everything
+    /// else is assumed to be [`Variant::Chess`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// #
+    /// use shakmaty::fen::Fen;
+    /// use shakmaty::variants::Variant;
+    ///
+    /// let fen: Fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse()?;
+    /// assert_eq!(Variant::guess(&fen), Variant::Chess);
+    #[cfg_attr(feature = "crazyhouse", doc = r#"
+let fen: Fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1".parse()?;
+assert_eq!(Variant::guess(&fen), Variant::Crazyhouse);"#)]
+    #[cfg_attr(feature = "threecheck", doc = r#"
+let fen: Fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1".parse()?;
+assert_eq!(Variant::guess(&fen), Variant::ThreeCheck);"#)]
+    /// #
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    #[allow(unused_variables)]
+    pub fn guess(setup: &dyn Setup) -> Variant {
+        #[cfg(feature = "crazyhouse")]
+        {
+            if setup.pockets().is_some() {
+                return Variant::Crazyhouse;
+            }
+        }
+
+        #[cfg(feature = "threecheck")]
+        {
+            if setup.remaining_checks().is_some() {
+                return Variant::ThreeCheck;
+            }
+        }
+
+        let board = setup.board();
+
+        #[cfg(feature = "horde")]
+        {
+            for &color in &[Color::White, Color::Black] {
+                let ours = board.by_color(color);
+                if (board.kings() & ours).is_empty() && (board.pawns() & ours).count() > 8 {
+                    return Variant::Horde;
+                }
+            }
+        }
+
+        #[cfg(feature = "antichess")]
+        {
+            if (board.kings() & board.white()).count() != 1 || (board.kings() & board.black()).count() != 1 {
+                return Variant::Antichess;
+            }
+        }
+
+        #[cfg(feature = "racingkings")]
+        {
+            if *board == Board::racing_kings() {
+                return Variant::RacingKings;
+            }
+        }
+
+        Variant::Chess
+    }
+
+    #[allow(clippy::match_like_matches_macro)]
+    pub fn distinguishes_promoted(self) -> bool {
+        match self {
+            #[cfg(feature = "crazyhouse")]
+            Variant::Crazyhouse => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.uci())
+    }
+}
+
+impl PartialEq for VariantPosition {
+    fn eq(&self, other: &VariantPosition) -> bool {
+        self.variant() == other.variant() && setup_eq(self, other)
+    }
+}
+
+impl Eq for VariantPosition {}
+
+impl Hash for VariantPosition {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.zobrist_hash());
+    }
+}
+
+impl VariantPosition {
+    #[allow(clippy::result_large_err)] // the error carries the position
+    pub fn swap_turn(self) -> Result<VariantPosition, PositionError<VariantPosition>> {
+        let mode = self.castles().mode();
+        VariantPosition::from_setup(self.variant(), &SwapTurn(self), mode)
+    }
+}
+
+impl Setup for VariantPosition {
+    fn board(&self) -> &Board { self.borrow().board() }
+    fn pockets(&self) -> Option<&Material> { self.borrow().pockets() }
+    fn turn(&self) -> Color { self.borrow().turn() }
+    fn castling_rights(&self) -> Bitboard { self.borrow().castling_rights() }
+    fn ep_square(&self) -> Option<Square> { self.borrow().ep_square() }
+    fn maybe_ep_square(&self) -> Option<Square> { self.borrow().maybe_ep_square() }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { self.borrow().remaining_checks() }
+    fn halfmoves(&self) -> u32 { self.borrow().halfmoves() }
+    fn fullmoves(&self) -> NonZeroU32 { self.borrow().fullmoves() }
+}
+
+impl Position for VariantPosition {
+    fn legal_moves(&self, moves: &mut MoveList) { self.borrow().legal_moves(moves) }
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) { self.borrow().legal_moves_with_promotions(mode, moves) }
+    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) { self.borrow().san_candidates(role, to, moves) }
+    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) { self.borrow().castling_moves(side, moves) }
+    fn en_passant_moves(&self, moves: &mut MoveList) { self.borrow().en_passant_moves(moves) }
+    fn capture_moves(&self, moves: &mut MoveList) { self.borrow().capture_moves(moves) }
+    fn promotion_moves(&self, moves: &mut MoveList) { self.borrow().promotion_moves(moves) }
+    fn evasion_moves(&self, moves: &mut MoveList) { self.borrow().evasion_moves(moves) }
+    fn quiet_moves(&self, moves: &mut MoveList) { self.borrow().quiet_moves(moves) }
+    fn legal_moves_masked(&self, from: Bitboard, to: Bitboard, moves: &mut MoveList) { self.borrow().legal_moves_masked(from, to, moves) }
+    fn count_legal_moves(&self) -> usize { self.borrow().count_legal_moves() }
+    fn has_legal_moves(&self) -> bool { self.borrow().has_legal_moves() }
+    fn is_irreversible(&self, m: &Move) -> bool { self.borrow().is_irreversible(m) }
+    fn king_attackers(&self, square: Square, attacker: Color, occupied: Bitboard) -> Bitboard { self.borrow().king_attackers(square, attacker, occupied) }
+    fn gives_check(&self, m: &Move) -> bool { self.borrow().gives_check(m) }
+    fn castles(&self) -> &Castles { self.borrow().castles() }
+    fn is_variant_end(&self) -> bool { self.borrow().is_variant_end() }
+    fn has_insufficient_material(&self, color: Color) -> bool { self.borrow().has_insufficient_material(color) }
+    fn variant_outcome(&self) -> Option<Outcome> { self.borrow().variant_outcome() }
+    fn play_unchecked(&mut self, m: &Move) { self.borrow_mut().play_unchecked(m) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_position_play() {
+        let pos = VariantPosition::new(Variant::Chess);
+        let pos = pos.play(&Move::Normal {
+            role: Role::Knight,
+            from: Square::G1,
+            to: Square::F3,
+            capture: None,
+            promotion: None,
+        }).expect("legal move");
+        assert_eq!(pos.variant(), Variant::Chess);
+    }
+
+    #[test]
+    #[cfg(feature = "variants")]
+    fn test_variant_names() {
+        assert_eq!(Variant::from_uci("3check"), Some(Variant::ThreeCheck));
+        assert_eq!(Variant::from_lichess_key("threeCheck"), Some(Variant::ThreeCheck));
+        assert_eq!(Variant::from_lichess_key("chess960"), Some(Variant::Chess));
+        assert_eq!(Variant::from_lichess_key("3check"), None);
+        assert_eq!(Variant::KingOfTheHill.lichess_key(), Some("kingOfTheHill"));
+        assert_eq!(Variant::Losers.lichess_key(), None);
+        assert_eq!(Variant::Crazyhouse.to_string(), "crazyhouse");
+    }
+
+    #[test]
+    fn test_eq_ignores_counters() {
+        use std::collections::HashSet;
+        use crate::fen::Fen;
+
+        fn setup<P: FromSetup>(fen: &str) -> P {
+            fen.parse::<Fen>().expect("valid fen").position(CastlingMode::Standard).expect("legal")
+        }
+
+        let a: Chess = setup("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+        let b: Chess = setup("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 7 30");
+        let c: Chess = setup("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b Kkq - 1 2");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        // En passant square without legal en passant capture.
+        let d: Chess = setup("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let e: Chess = setup("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_eq!(d, e);
+
+        let set: HashSet<Chess> = vec![a, b, c, d, e].into_iter().collect();
+        assert_eq!(set.len(), 3);
+
+        // Promoted pieces are only distinguished if there are pockets.
+        let mut f: Chess = setup("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        f.play_unchecked(&Move::Normal { role: Role::Pawn, from: Square::B7, capture: None, to: Square::B8, promotion: Some(Role::Queen) });
+        let g: Chess = setup("1Q2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert_eq!(f, g);
+        assert_eq!(f.zobrist_hash(), g.zobrist_hash());
+
+        #[cfg(feature = "atomic")]
+        {
+            let pos = VariantPosition::new(Variant::Chess);
+            assert_eq!(pos, VariantPosition::from(Chess::default()));
+            assert_ne!(pos, VariantPosition::new(Variant::Atomic));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "variants")]
+    fn test_variant_outcome() {
+        use crate::fen::Fen;
+
+        for &(variant, fen, outcome) in &[
+            (Variant::KingOfTheHill, "8/8/8/3K4/8/8/8/k7 b - - 0 1", Some(Outcome::Decisive { winner: Color::White })),
+            (Variant::KingOfTheHill, "8/8/8/2K5/8/8/8/k7 b - - 0 1", None),
+            (Variant::ThreeCheck, "4k3/8/8/8/8/8/8/4K3 w - - 3+0 0 1", Some(Outcome::Decisive { winner: Color::Black })),
+            (Variant::ThreeCheck, "4k3/8/8/8/8/8/8/3QK3 w - - 3+1 0 1", None),
+            (Variant::RacingKings, "1K6/8/k7/8/8/8/8/8 b - - 0 1", Some(Outcome::Decisive { winner: Color::White })),
+            (Variant::RacingKings, "1K6/5k2/8/8/8/8/8/8 b - - 0 1", None),
+        ] {
+            let setup: Fen = fen.parse().expect("valid fen");
+            let pos = VariantPosition::from_setup(variant, &setup, CastlingMode::Standard).expect("valid position");
+            assert_eq!(pos.outcome(), outcome);
+            assert_eq!(pos.is_variant_end(), outcome.is_some());
+            assert_eq!(pos.is_game_over(), outcome.is_some());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "variants")]
+    fn test_guess() {
+        use crate::fen::Fen;
+
+        for &(fen, variant) in &[
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", Variant::Chess),
+            ("r2q1r1k/ppp2pQ~p/2np4/2b1p3/4P3/2NP1N2/PPP2PPP/R1B1K2R/NBpnb b KQ - 0 12", Variant::Crazyhouse),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +1+0", Variant::ThreeCheck),
+            ("rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1", Variant::Horde),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1BNR w kq - 0 1", Variant::Antichess),
+            ("8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1", Variant::RacingKings),
+        ] {
+            let fen: Fen = fen.parse().expect("valid fen");
+            assert_eq!(Variant::guess(&fen), variant);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroU32;
+
+use crate::attacks;
+use crate::board::Board;
+use crate::bitboard::Bitboard;
+use crate::square::{Rank, Square};
+use crate::types::{Black, CastlingMode, Color, Move, PromotionMode, RemainingChecks, White};
+use crate::material::Material;
+use crate::setup::{Castles, Setup};
+use crate::movelist::{ArrayVecExt, MoveList};
+use crate::position::{FromSetup, Outcome, Position, PositionError, PositionErrorKinds};
+use crate::position::{do_move, gen_non_king, gen_safe_king, is_safe, slider_blockers, validate};
+
+/// A Racing Kings position.
+#[derive(Clone, Debug)]
+pub struct RacingKings {
+    board: Board,
+    turn: Color,
+    castles: Castles,
+    halfmoves: u32,
+    fullmoves: NonZeroU32,
+}
+
+impl Default for RacingKings {
+    fn default() -> RacingKings {
+        RacingKings {
+            board: Board::racing_kings(),
+            turn: White,
+            castles: Castles::empty(CastlingMode::Standard),
+            halfmoves: 0,
+            fullmoves: NonZeroU32::new(1).unwrap(),
+        }
+    }
+}
+
+impl Setup for RacingKings {
+    fn board(&self) -> &Board { &self.board }
+    fn pockets(&self) -> Option<&Material> { None }
+    fn turn(&self) -> Color { self.turn }
+    fn castling_rights(&self) -> Bitboard { Bitboard(0) }
+    fn ep_square(&self) -> Option<Square> { None }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { None }
+    fn halfmoves(&self) -> u32 { self.halfmoves }
+    fn fullmoves(&self) -> NonZeroU32 { self.fullmoves }
+}
+
+impl FromSetup for RacingKings {
+    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<RacingKings, PositionError<RacingKings>> {
+        let mut errors = PositionErrorKinds::empty();
+
+        if setup.castling_rights().any() {
+            errors |= PositionErrorKinds::INVALID_CASTLING_RIGHTS;
+        }
+
+        let board = setup.board().clone();
+        if board.pawns().any() {
+            errors |= PositionErrorKinds::VARIANT;
+        }
+        if setup.ep_square().is_some() {
+            errors |= PositionErrorKinds::INVALID_EP_SQUARE;
+        }
+
+        let pos = RacingKings {
+            board,
+            turn: setup.turn(),
+            castles: Castles::empty(mode),
+            halfmoves: setup.halfmoves(),
+            fullmoves: setup.fullmoves(),
+        };
+
+        if pos.is_check() {
+            errors |= PositionErrorKinds::IMPOSSIBLE_CHECK;
+        }
+
+        if pos.turn().is_black() &&
+           (pos.board().white() & pos.board().kings() & Rank::Eighth).any() &&
+           (pos.board().black() & pos.board().kings() & Rank::Eighth).any()
+        {
+            errors |= PositionErrorKinds::VARIANT;
+        }
+
+        errors |= validate(&pos);
+
+        PositionError { errors, pos }.strict()
+    }
+}
+
+impl Position for RacingKings {
+    fn play_unchecked(&mut self, m: &Move) {
+        do_move(&mut self.board, &mut self.turn, &mut self.castles,
+                &mut None, &mut self.halfmoves,
+                &mut self.fullmoves, m);
+    }
+
+    fn legal_moves(&self, moves: &mut MoveList) {
+        moves.clear();
+
+        if self.is_variant_end() {
+            return;
+        }
+
+        // Generate all legal moves (no castling, no ep).
+        let target = !self.us();
+        gen_non_king(self, target, PromotionMode::All, moves);
+        if let Some(king) = self.board().king_of(self.turn()) {
+            gen_safe_king(self, king, target, moves);
+
+            let blockers = slider_blockers(self.board(), self.them(), king);
+            if blockers.any() {
+                moves.swap_retain(|m| is_safe(self, king, m, blockers));
+            }
+        }
+
+        // Do not allow giving check.
+        moves.swap_retain(|m| !self.gives_check(m));
+    }
+
+    fn castles(&self) -> &Castles {
+        &self.castles
+    }
+
+    fn has_insufficient_material(&self, _color: Color) -> bool {
+        // Even a lone king can win the race.
+        false
+    }
+
+    fn is_variant_end(&self) -> bool {
+        let in_goal = self.board().kings() & Rank::Eighth;
+        if in_goal.is_empty() {
+            return false;
+        }
+
+        if self.turn().is_white() || (in_goal & self.board().black()).any() {
+            return true;
+        }
+
+        // White has reached the backrank. Check if black can catch up.
+        let black_king = match self.board().king_of(Black) {
+            Some(king) => king,
+            None => return true,
+        };
+        for target in attacks::king_attacks(black_king) & Rank::Eighth & !self.board().black() {
+            if self.king_attackers(target, White, self.board().occupied()).is_empty() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        if self.is_variant_end() {
+            let in_goal = self.board().kings() & Rank::Eighth;
+            if (in_goal & self.board().white()).any() && (in_goal & self.board().black()).any() {
+                Some(Outcome::Draw)
+            } else if (in_goal & self.board().white()).any() {
+                Some(Outcome::Decisive { winner: White })
+            } else {
+                Some(Outcome::Decisive { winner: Black })
+            }
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::position::tests::assert_insufficient_material;
+
+    #[test]
+    fn test_insufficient_material() {
+        assert_insufficient_material::<RacingKings>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", false, false);
+    }
+
+    #[test]
+    fn test_no_checks() {
+        let pos: RacingKings = "8/k7/8/8/8/8/8/1R5K w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Chess960)
+            .expect("valid position");
+        let mut moves = MoveList::new();
+        pos.legal_moves(&mut moves);
+        assert_eq!(moves.len(), 14);
+        assert!(moves.iter().all(|m| !pos.clone().play(m).expect("legal").is_check()));
+    }
+
+    #[test]
+    fn test_racing_kings_end() {
+        // Both players reached the backrank.
+        let pos: RacingKings = "kr3NK1/1q2R3/8/8/8/5n2/2N5/1rb2B1R w - - 11 14".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Chess960)
+            .expect("valid position");
+        assert!(pos.is_variant_end());
+        assert_eq!(pos.variant_outcome(), Some(Outcome::Draw));
+
+        // White to move is lost because black reached the backrank.
+        let pos: RacingKings = "1k6/6K1/8/8/8/8/8/8 w - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Chess960)
+            .expect("valid position");
+        assert!(pos.is_variant_end());
+        assert_eq!(pos.variant_outcome(), Some(Outcome::Decisive { winner: Color::Black }));
+
+        // Black is given a chance to catch up.
+        let pos: RacingKings = "1K6/7k/8/8/8/8/8/8 b - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Chess960)
+            .expect("valid position");
+        assert!(!pos.is_variant_end());
+        assert_eq!(pos.variant_outcome(), None);
+
+        // Black near backrank but cannot move there.
+        let pos: RacingKings = "2KR4/k7/2Q5/4q3/8/8/8/2N5 b - - 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Chess960)
+            .expect("valid position");
+        assert!(pos.is_variant_end());
+        assert_eq!(pos.variant_outcome(), Some(Outcome::Decisive { winner: Color::White }));
+    }
+}
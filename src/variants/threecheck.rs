@@ -0,0 +1,176 @@
+// This file is part of the shakmaty library.
+// Copyright (C) 2017-2019 Niklas Fiekas <niklas.fiekas@backscattering.de>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroU32;
+
+use crate::board::Board;
+use crate::bitboard::Bitboard;
+use crate::square::Square;
+use crate::types::{Black, CastlingMode, CastlingSide, Color, Move, PromotionMode, RemainingChecks, Role, White};
+use crate::material::Material;
+use crate::setup::{Castles, Setup};
+use crate::movelist::MoveList;
+use crate::position::{Chess, FromSetup, Outcome, Position, PositionError, PositionErrorKinds};
+
+/// A Three-Check position.
+#[derive(Clone, Debug, Default)]
+pub struct ThreeCheck {
+    chess: Chess,
+    remaining_checks: RemainingChecks,
+}
+
+impl Setup for ThreeCheck {
+    fn board(&self) -> &Board { self.chess.board() }
+    fn pockets(&self) -> Option<&Material> { None }
+    fn turn(&self) -> Color { self.chess.turn() }
+    fn castling_rights(&self) -> Bitboard { self.chess.castling_rights() }
+    fn ep_square(&self) -> Option<Square> { self.chess.ep_square() }
+    fn maybe_ep_square(&self) -> Option<Square> { self.chess.maybe_ep_square() }
+    fn remaining_checks(&self) -> Option<&RemainingChecks> { Some(&self.remaining_checks) }
+    fn halfmoves(&self) -> u32 { self.chess.halfmoves() }
+    fn fullmoves(&self) -> NonZeroU32 { self.chess.fullmoves() }
+}
+
+impl FromSetup for ThreeCheck {
+    fn from_setup(setup: &dyn Setup, mode: CastlingMode) -> Result<ThreeCheck, PositionError<ThreeCheck>> {
+        let (chess, mut errors) = Chess::from_setup_unchecked(setup, mode);
+
+        let remaining_checks = setup.remaining_checks().cloned().unwrap_or_default();
+        if remaining_checks.white == 0 && remaining_checks.black == 0 {
+            errors |= PositionErrorKinds::VARIANT
+        }
+
+        PositionError {
+            errors,
+            pos: ThreeCheck { chess, remaining_checks },
+        }.strict()
+    }
+}
+
+impl Position for ThreeCheck {
+    fn play_unchecked(&mut self, m: &Move) {
+        let turn = self.chess.turn();
+        self.chess.play_unchecked(m);
+        if self.is_check() {
+            self.remaining_checks.decrement(turn);
+        }
+    }
+
+    fn castles(&self) -> &Castles {
+        self.chess.castles()
+    }
+
+    fn legal_moves(&self, moves: &mut MoveList) {
+        self.legal_moves_with_promotions(PromotionMode::All, moves);
+    }
+
+    fn legal_moves_with_promotions(&self, mode: PromotionMode, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.legal_moves_with_promotions(mode, moves);
+        }
+    }
+
+    fn castling_moves(&self, side: CastlingSide, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.castling_moves(side, moves);
+        }
+    }
+
+    fn en_passant_moves(&self, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.en_passant_moves(moves);
+        }
+    }
+
+    fn san_candidates(&self, role: Role, to: Square, moves: &mut MoveList) {
+        if self.is_variant_end() {
+            moves.clear();
+        } else {
+            self.chess.san_candidates(role, to, moves);
+        }
+    }
+
+    fn has_insufficient_material(&self, color: Color) -> bool {
+        // Any remaining piece can give check.
+        (self.board().by_color(color) & !self.board().kings()).is_empty()
+    }
+
+    fn is_irreversible(&self, m: &Move) -> bool {
+        self.chess.is_irreversible(m) || self.chess.gives_check(m)
+    }
+
+    fn is_variant_end(&self) -> bool {
+        self.remaining_checks.white == 0 || self.remaining_checks.black == 0
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        if self.remaining_checks.white == 0 && self.remaining_checks.black == 0 {
+            Some(Outcome::Draw)
+        } else if self.remaining_checks.white == 0 {
+            Some(Outcome::Decisive { winner: White })
+        } else if self.remaining_checks.black == 0 {
+            Some(Outcome::Decisive { winner: Black })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::{fen, Fen};
+    use crate::position::tests::assert_insufficient_material;
+
+    #[test]
+    fn test_insufficient_material() {
+        assert_insufficient_material::<ThreeCheck>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", true, true);
+        assert_insufficient_material::<ThreeCheck>("8/5k2/8/8/8/8/3K2N1/8 w - - 0 1", false, true);
+    }
+
+    #[test]
+    fn test_remaining_checks() {
+        let pos: ThreeCheck = "4k3/8/8/8/8/8/8/4KQ2 w - - 1+2 0 1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        assert_eq!(fen(&pos), "4k3/8/8/8/8/8/8/4KQ2 w - - 1+2 0 1");
+
+        let scid: ThreeCheck = "4k3/8/8/8/8/8/8/4KQ2 w - - 0 1 +2+1".parse::<Fen>()
+            .expect("valid fen")
+            .position(CastlingMode::Standard)
+            .expect("valid position");
+        assert_eq!(scid.remaining_checks(), pos.remaining_checks());
+
+        let pos = pos.play(&Move::Normal {
+            role: Role::Queen,
+            from: Square::F1,
+            capture: None,
+            to: Square::E2,
+            promotion: None,
+        }).expect("Qe2+ is legal");
+        assert!(pos.is_variant_end());
+        assert_eq!(pos.outcome(), Some(Outcome::Decisive { winner: White }));
+
+        assert!("4k3/8/8/8/8/8/8/4KQ2 w - - 1+x 0 1".parse::<Fen>().is_err());
+    }
+}
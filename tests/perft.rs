@@ -15,7 +15,6 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use shakmaty::{CastlingMode, Chess, FromSetup, Position};
-use shakmaty::variants::{Atomic, Antichess, Crazyhouse, RacingKings, Horde};
 use shakmaty::fen::Fen;
 use shakmaty::perft;
 
@@ -78,31 +77,36 @@ fn test_tricky() {
 }
 
 #[test]
+#[cfg(feature = "atomic")]
 #[cfg_attr(miri, ignore)]
 fn test_atomic() {
-    test_perft_file::<Atomic>("tests/atomic.perft", 1_000_000);
+    test_perft_file::<shakmaty::variants::Atomic>("tests/atomic.perft", 1_000_000);
 }
 
 #[test]
+#[cfg(feature = "antichess")]
 #[cfg_attr(miri, ignore)]
 fn test_antichess() {
-    test_perft_file::<Antichess>("tests/antichess.perft", 1_000_000);
+    test_perft_file::<shakmaty::variants::Antichess>("tests/antichess.perft", 1_000_000);
 }
 
 #[test]
+#[cfg(feature = "crazyhouse")]
 #[cfg_attr(miri, ignore)]
 fn test_crazyhouse() {
-    test_perft_file::<Crazyhouse>("tests/crazyhouse.perft", 1_000_000);
+    test_perft_file::<shakmaty::variants::Crazyhouse>("tests/crazyhouse.perft", 1_000_000);
 }
 
 #[test]
+#[cfg(feature = "racingkings")]
 #[cfg_attr(miri, ignore)]
 fn test_racingkings() {
-    test_perft_file::<RacingKings>("tests/racingkings.perft", 1_000_000);
+    test_perft_file::<shakmaty::variants::RacingKings>("tests/racingkings.perft", 1_000_000);
 }
 
 #[test]
+#[cfg(feature = "horde")]
 #[cfg_attr(miri, ignore)]
 fn test_horde() {
-    test_perft_file::<Horde>("tests/horde.perft", 1_000_000);
+    test_perft_file::<shakmaty::variants::Horde>("tests/horde.perft", 1_000_000);
 }